@@ -0,0 +1,22 @@
+#![no_main]
+
+use bridge_ai::ai::bidding::{AuctionPattern, Constraint};
+use bridge_ai::ai::bidding::format::load;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  let text = match std::str::from_utf8(data) {
+    Ok(text) => text,
+    Err(_) => return,
+  };
+  if let Ok(system) = load(text) {
+    let reloaded = load(&system.to_text()).expect("a written system loads");
+    assert_eq!(reloaded.rules(), system.rules());
+  }
+  if let Ok(constraint) = Constraint::parse(text) {
+    assert_eq!(Constraint::parse(&constraint.to_string()), Ok(constraint));
+  }
+  if let Some(pattern) = AuctionPattern::parse(text) {
+    assert_eq!(AuctionPattern::parse(&pattern.to_string()), Some(pattern));
+  }
+});
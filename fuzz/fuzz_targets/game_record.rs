@@ -0,0 +1,11 @@
+#![no_main]
+
+use bridge_ai::game_model::record::GameRecord;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  if let Ok(record) = std::str::from_utf8(data).map_err(|_| ()).and_then(|json| GameRecord::from_json(json).map_err(|_| ())) {
+    GameRecord::from_json(&record.to_json()).expect("a written record reads back");
+    let _ = record.to_game();
+  }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use bridge_ai::game_model::Seat;
+use bridge_ai::game_model::lin::{read_auction, write_auction};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  let (dealer, text) = match data.split_first() {
+    Some((&first, rest)) => ([Seat::North, Seat::East, Seat::South, Seat::West][first as usize % 4], rest),
+    None => return,
+  };
+  if let Some(auction) = std::str::from_utf8(text).ok().and_then(|text| read_auction(text, dealer)) {
+    let read = read_auction(&write_auction(&auction), dealer).expect("written auction reads back");
+    assert!(read.calls().eq(auction.calls()));
+  }
+});
@@ -0,0 +1,34 @@
+#![no_main]
+
+use bridge_ai::game_model::{HandResult, Seat, Vulnerability};
+use bridge_ai::game_model::bidding::{Bid, Call, CallMeaning, Contract, DoubleLevel, Strain};
+use bridge_ai::game_model::cards::{Card, PlayerHand, Rank, Suit};
+use bridge_ai::game_model::notation::Notation;
+use libfuzzer_sys::fuzz_target;
+
+/** Whatever parses must write out as notation that parses back to the same notation. */
+fn round_trip<T: Notation>(text: &str) {
+  if let Some(value) = T::from_notation(text) {
+    let written = value.notation();
+    let read = T::from_notation(&written).expect("written notation parses");
+    assert_eq!(read.notation(), written);
+  }
+}
+
+fuzz_target!(|data: &[u8]| {
+  if let Ok(text) = std::str::from_utf8(data) {
+    round_trip::<Suit>(text);
+    round_trip::<Rank>(text);
+    round_trip::<Card>(text);
+    round_trip::<Seat>(text);
+    round_trip::<Strain>(text);
+    round_trip::<Bid>(text);
+    round_trip::<Call>(text);
+    round_trip::<DoubleLevel>(text);
+    round_trip::<Contract>(text);
+    round_trip::<HandResult>(text);
+    round_trip::<Vulnerability>(text);
+    round_trip::<PlayerHand>(text);
+    round_trip::<CallMeaning>(text);
+  }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use bridge_ai::game_model::pbn::{read_auction, write_auction};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  if let Some(auction) = std::str::from_utf8(data).ok().and_then(read_auction) {
+    let read = read_auction(&write_auction(&auction)).expect("written auction reads back");
+    assert!(read.calls().eq(auction.calls()));
+  }
+});
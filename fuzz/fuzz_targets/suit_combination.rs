@@ -0,0 +1,19 @@
+#![no_main]
+
+use bridge_ai::ai::declarer::{OurHand, SuitCombination, VacantPlaces, layouts};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  let text = match std::str::from_utf8(data) {
+    Ok(text) => text,
+    Err(_) => return,
+  };
+  let (declarer, dummy) = text.split_once('/').unwrap_or((text, ""));
+  if let Some(combination) = SuitCombination::parse(declarer, dummy) {
+    let held = combination.hand(OurHand::Declarer).len() + combination.hand(OurHand::Dummy).len();
+    assert_eq!(held + combination.missing().len(), 13);
+    assert!(combination.sure_tricks() as usize <= held);
+    let total: f64 = layouts(&combination.missing(), VacantPlaces::default()).iter().map(|(_, p)| p).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+  }
+});
@@ -1,124 +1,272 @@
-use std::os::raw::c_int;
-use enum_map::EnumMap;
-use crate::ai::dds::dds_deal;
-use crate::ai::dds_bindings::{AnalysePlayBin, boards, deal, futureTricks, SolveAllBoards, SolveAllBoardsBin, solvedBoards};
-use crate::game_model::bidding::{Auction, Call};
-use crate::game_model::cards::{Card, PlayerHand, Rank, Suit};
-use crate::game_model::{Board, HandResult, Seat};
-use crate::game_model::play::Play;
-use crate::interface::Player;
+use std::cell::RefCell;
+use enum_map::enum_map;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use strum::IntoEnumIterator;
+use crate::ai::bidding::{BiddingRule, BiddingSystem};
+use crate::ai::bidding::sayc::sayc;
+use crate::ai::sampling::{DealSampler, infer_constraints};
+use crate::ai::search::{CacheStats, SearchClock, SearchContext};
+use crate::ai::signals::{Carding, read_signals};
+use crate::ai::trace::{CardScore, DecisionTrace, PlayTrace, WorldTrace};
+use crate::game_model::bidding::{Auction, Call, CallAnnotation};
+use crate::game_model::cards::{Card, PlayerHand};
+use crate::game_model::{Board, Seat};
+use crate::game_model::play::{Claim, Play};
+use crate::interface::{Deadline, Player};
 
 pub struct AlphaMuPlayer {
   hand: PlayerHand,
   dummy: Option<PlayerHand>,
+  system: BiddingSystem,
+  auction: Option<Auction>,
+  carding: Carding,
+  context: SearchContext,
+  seed: u64,
+  trace: RefCell<Option<DecisionTrace>>,
 }
 
+/** The deals added to the search at a time. */
+const WORLDS_PER_ROUND: u64 = 16;
+
+/** The deals a claim is checked in before the player makes or accepts it. */
+const CLAIM_WORLDS: usize = 16;
+
 impl AlphaMuPlayer {
-  fn alpha_mu_search(&self, state: &Play, moves: u32, worlds: &mut Vec<World>) -> Card {
-    if self.stop(state, moves, worlds) {
-      // sort it out
+  /** Bids with the given system instead of SAYC. */
+  pub fn with_system(mut self, system: BiddingSystem) -> Self {
+    self.system = system;
+    self
+  }
+
+  /** The carding agreements partner is taken to signal by when defending. */
+  pub fn with_carding(mut self, carding: Carding) -> Self {
+    self.carding = carding;
+    self
+  }
+
+  /** Remembers up to `positions` solved positions through the hand instead of the default. */
+  pub fn with_cache_size(mut self, positions: usize) -> Self {
+    self.context = SearchContext::new(positions);
+    self
+  }
+
+  /** How often the search has found a position it solved earlier in the hand. */
+  pub fn cache_stats(&self) -> CacheStats {
+    self.context.stats()
+  }
+
+  /** Seeds the deals the search samples. The same seed, position and node budget always give the
+      same card.
+   */
+  pub fn with_seed(mut self, seed: u64) -> Self {
+    self.seed = seed;
+    self
+  }
+
+  /** Deals consistent with what the player has seen: their own hand, dummy once it is down, the
+      cards played, partner's signals when defending and the auction. The second sampler ignores
+      the auction, for when the hands it implies cannot be found.
+   */
+  fn sampler(&self, play: &Play, me: Seat, dummy: Option<&PlayerHand>) -> (DealSampler, DealSampler) {
+    let dummy_seat = play.declarer().partner();
+    let mut sampler = DealSampler::new().with_known_hand(me, &self.hand);
+    if let Some(dummy) = dummy {
+      sampler = sampler.with_known_hand(dummy_seat, dummy);
     }
-    Card::from(Suit::Spades, Rank::Ace)
-  }
-
-  fn stop(&self, state: &Play, moves: u32, worlds: &mut Vec<World>) -> bool {
-    if state.is_complete() {
-      let result = state.result().unwrap();
-      match result {
-        HandResult::Played(contract, diff) => {
-          if diff >= 0 {
-            for w in worlds {
-              w.result = Some(true);
-            }
-          } else {
-            for w in worlds {
-              w.result = Some(false);
-            }
-          }
+    for trick in play.tricks() {
+      for (seat, card) in trick.cards_in_order() {
+        if seat != me && !(seat == dummy_seat && dummy.is_some()) {
+          sampler = sampler.with_known_cards(seat, &[card]);
         }
-        HandResult::Passout => unreachable!()
       }
-      true
-    } else if moves == 0 {
-      Self::double_dummy_solve(worlds);
-      true
+    }
+    if me.is_opponent(play.declarer()) {
+      sampler = sampler.with_signals(read_signals(play, me.partner(), &self.carding));
+    }
+    let constrained = match &self.auction {
+      Some(auction) => sampler.clone().with_all_constraints(infer_constraints(auction)).with_max_attempts(1_000),
+      None => sampler.clone(),
+    };
+    (constrained, sampler)
+  }
+
+  /** Seeds the deals sampled for a position from what the player can see of it: the board number
+      and the cards played so far. The hidden hands play no part, so the same visible position
+      always samples the same deals.
+   */
+  fn rng(&self, play: &Play, board: &Board) -> StdRng {
+    let played = play.tricks().iter().flat_map(|trick| trick.cards_in_order());
+    let seed = played.fold(self.seed ^ board.number() as u64, |seed, (_, card)| {
+      seed.wrapping_mul(0x100_0000_01b3) ^ (card.suit() as u64 * 13 + card.rank() as u64 + 1)
+    });
+    StdRng::seed_from_u64(seed)
+  }
+
+  /** The fewest of the remaining tricks that `claimer`'s side takes double dummy in deals sampled
+      from what the player in `me` can see, or `None` when no deal could be sampled and solved.
+      Only the player's own hand and dummy are known, so a claim that rests on the hidden cards
+      lying well is not counted on.
+   */
+  fn sure_tricks(&mut self, play: &Play, board: &Board, me: Seat, claimer: Seat) -> Option<u8> {
+    let dummy = if play.cards_played() > 0 {
+      Some(board.player_hand(play.declarer().partner()).clone())
     } else {
-      false
+      None
+    };
+    let (constrained, unconstrained) = self.sampler(play, me, dummy.as_ref());
+    let mut rng = self.rng(play, board);
+    let mut deals = constrained.samples(CLAIM_WORLDS, &mut rng);
+    if deals.is_empty() {
+      deals = unconstrained.samples(CLAIM_WORLDS, &mut rng);
     }
+    let boards: Vec<Board> = deals.into_iter().map(|hands| Board::from_hands(board.number(), hands)).collect();
+    let positions: Vec<(&Play, &Board)> = boards.iter().map(|board| (play, board)).collect();
+    let solved = self.context.solve_tricks(&positions).ok()?;
+    let claimers_move = !play.next_player().is_opponent(claimer);
+    solved.into_iter()
+        .map(|tricks| if claimers_move { tricks } else { play.remaining_tricks() - tricks })
+        .min()
   }
 
-  fn double_dummy_solve(worlds: &mut Vec<World>) {
-    let num_boards = worlds.len() as c_int;
-    let mut deals = [deal {
-      trump: 0,
-      first: 0,
-      currentTrickSuit: [0; 3],
-      currentTrickRank: [0; 3],
-      remainCards: [[0; 4]; 4],
-    }; 200];
-    let mut target = [0; 200];
-    let solutions = [1; 200];
-    let mode = [1; 200];
-    worlds
-        .iter()
-        .enumerate()
-        .for_each(|(i, w)| {
-          deals[i] = dds_deal(&w.play, &w.board);
-          target[i] = (w.play.contract().level() + 6 - w.play.declarer_tricks()) as c_int;
-        });
-    let mut boards = boards {
-      noOfBoards: num_boards,
-      deals,
-      target,
-      solutions,
-      mode
+  /** Anytime search: deals are added a round at a time and each solved double dummy, until the
+      deadline passes. The card chosen is the one that reaches the side's target in the most deals
+      so far, with the most tricks breaking ties, so stopping at any point gives a sensible card.
+   */
+  fn search(&mut self, play: &Play, board: &Board, deadline: Deadline) -> PlayTrace {
+    let declarer = play.declarer();
+    let seat = play.next_player();
+    let me = if seat == declarer.partner() { declarer } else { seat };
+    let dummy = if me == declarer && play.cards_played() > 0 {
+      Some(board.player_hand(declarer.partner()).clone())
+    } else {
+      self.dummy.clone()
     };
-    let fut = futureTricks {
-      nodes: 0,
-      cards: 0,
-      suit: [0; 13],
-      rank: [0; 13],
-      equals: [0; 13],
-      score: [0; 13],
+    let hand = if seat == me { &self.hand } else { dummy.as_ref().expect("declarer sees dummy") };
+    let mut cards = play.legal_plays(hand);
+    cards.sort_by_key(|card| (card.rank(), card.suit()));
+    if cards.len() <= 1 {
+      return PlayTrace::new(seat, cards[0], "the only legal card");
+    }
+    let (taken, target) = if seat.is_opponent(declarer) {
+      (play.defense_tricks(), 8 - play.contract().level())
+    } else {
+      (play.declarer_tricks(), play.contract().level() + 6)
     };
-    let mut solved = solvedBoards {
-      noOfBoards: num_boards,
-      solvedBoard: [fut; 200]
+    let (constrained, unconstrained) = self.sampler(play, me, dummy.as_ref());
+    let mut rng = self.rng(play, board);
+    let mut clock = SearchClock::start(deadline);
+    let mut scores = vec![(0u32, 0u32); cards.len()];
+    let mut worlds = Vec::new();
+    let mut best = 0;
+    while !clock.is_expired() {
+      let round = clock.remaining_nodes().map_or(WORLDS_PER_ROUND, |nodes| nodes.min(WORLDS_PER_ROUND)) as usize;
+      let mut deals = constrained.samples(round, &mut rng);
+      if deals.is_empty() {
+        deals = unconstrained.samples(round, &mut rng);
+      }
+      if deals.is_empty() {
+        break;
+      }
+      let boards: Vec<Board> = deals.into_iter().map(|hands| Board::from_hands(board.number(), hands)).collect();
+      let positions: Vec<(&Play, &Board)> = boards.iter().map(|board| (play, board)).collect();
+      let solved = match self.context.solve_plays(&positions) {
+        Ok(solved) => solved,
+        Err(_) => break,
+      };
+      clock.count(boards.len() as u64);
+      for (board, plays) in boards.iter().zip(solved) {
+        let tricks: Vec<u8> = cards.iter()
+            .map(|card| plays.iter().find(|&&(c, _)| c == *card).map_or(0, |&(_, tricks)| tricks))
+            .collect();
+        for (score, &tricks) in scores.iter_mut().zip(&tricks) {
+          score.0 += (taken + tricks >= target) as u32;
+          score.1 += tricks as u32;
+        }
+        worlds.push(WorldTrace { hands: enum_map! { seat => board.player_hand(seat).clone() }, tricks });
+      }
+      best = (0..cards.len()).fold(0, |best, i| if scores[i] > scores[best] { i } else { best });
+    }
+    let reason = if worlds.is_empty() {
+      "no deal was solved in time, so the lowest card".to_string()
+    } else {
+      format!("the best of {} deals solved double dummy, needing {} of the remaining tricks",
+              worlds.len(), target.saturating_sub(taken))
     };
-    unsafe {
-      SolveAllBoardsBin(&mut boards, &mut solved);
+    let deals = worlds.len().max(1) as f64;
+    PlayTrace {
+      seat,
+      chosen: cards[best],
+      reason,
+      scores: cards.iter().zip(&scores).map(|(&card, &(successes, tricks))| {
+        CardScore { card, successes, average_tricks: tricks as f64 / deals }
+      }).collect(),
+      worlds,
     }
   }
 }
 
 impl Player for AlphaMuPlayer {
   fn new(hand: &PlayerHand) -> Self {
-    AlphaMuPlayer { hand: hand.clone(), dummy: None }
+    AlphaMuPlayer {
+      hand: hand.clone(),
+      dummy: None,
+      system: sayc(),
+      auction: None,
+      carding: Carding::default(),
+      context: SearchContext::default(),
+      seed: 0,
+      trace: RefCell::new(None),
+    }
   }
 
   fn get_call(&self, auction: &Auction) -> Call {
-    Call::Pass
+    let trace = self.system.trace(auction, &self.hand);
+    let call = trace.chosen;
+    *self.trace.borrow_mut() = Some(DecisionTrace::Call(trace));
+    call
+  }
+
+  fn explain_call(&self, auction: &Auction, call: Call) -> Option<CallAnnotation> {
+    self.system.explain(auction, call).map(BiddingRule::annotation)
   }
 
-  fn get_play(&mut self, play: &Play, board: &Board) -> Card {
-    let mut worlds = vec![World::new(play.clone(), board.clone())];
-    self.alpha_mu_search(play, 0, &mut worlds)
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: Deadline) -> Card {
+    let trace = self.search(play, board, deadline);
+    let card = trace.chosen;
+    *self.trace.get_mut() = Some(DecisionTrace::Play(trace));
+    card
   }
 
   fn notify_dummy(&mut self, dummy: &PlayerHand) {
     self.dummy = Some(dummy.clone())
   }
-}
 
-pub struct World {
-  play: Play,
-  board: Board,
-  result: Option<bool>,
-  valid: bool,
-}
+  fn notify_auction(&mut self, auction: &Auction) {
+    self.auction = Some(auction.clone())
+  }
+
+  fn offer_claim(&mut self, play: &Play, board: &Board) -> Option<(u8, String)> {
+    let seat = play.next_player();
+    let me = if seat == play.declarer().partner() { play.declarer() } else { seat };
+    let remaining = play.remaining_tricks();
+    match self.sure_tricks(play, board, me, me) {
+      Some(tricks) if tricks == remaining => Some((remaining, "The rest are mine.".to_string())),
+      _ => None,
+    }
+  }
+
+  fn respond_to_claim(&mut self, play: &Play, board: &Board, claim: &Claim) -> bool {
+    let me = Seat::iter()
+        .filter(|&seat| claim.is_responder(seat, play.declarer()))
+        .find(|&seat| self.hand.cards().iter().all(|&card| board.player_hand(seat).has_card(card)));
+    match me {
+      Some(me) => self.sure_tricks(play, board, me, claim.claimer()).is_some_and(|tricks| tricks >= claim.tricks()),
+      None => false,
+    }
+  }
 
-impl World {
-  fn new(play: Play, board: Board) -> Self {
-    World { play, board, result: None, valid: true }
+  fn last_decision(&self) -> Option<DecisionTrace> {
+    self.trace.borrow().clone()
   }
 }
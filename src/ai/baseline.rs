@@ -0,0 +1,165 @@
+use std::cell::RefCell;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use strum::IntoEnumIterator;
+use crate::ai::bidding::HandFeatures;
+use crate::game_model::{Board, Seat};
+use crate::game_model::bidding::{Auction, Bid, Call, Strain};
+use crate::game_model::cards::{Card, PlayerHand, Suit};
+use crate::game_model::play::Play;
+use crate::interface::{Deadline, Player};
+
+/** The cards the seat due to play may play. Players are only asked for their own cards and, as
+    declarer, dummy's, so this shows them nothing they could not see at the table.
+ */
+fn legal_plays(play: &Play, board: &Board) -> Vec<Card> {
+  play.legal_plays(board.player_hand(play.next_player()))
+}
+
+/** A player that makes every call and plays every card at random from the legal ones. */
+pub struct RandomPlayer {
+  rng: RefCell<StdRng>,
+}
+
+impl RandomPlayer {
+  pub fn with_seed(self, seed: u64) -> Self {
+    RandomPlayer { rng: RefCell::new(StdRng::seed_from_u64(seed)) }
+  }
+}
+
+impl Player for RandomPlayer {
+  fn new(_hand: &PlayerHand) -> Self {
+    RandomPlayer { rng: RefCell::new(StdRng::from_entropy()) }
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    *auction.legal_calls().choose(&mut *self.rng.borrow_mut()).unwrap_or(&Call::Pass)
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, _deadline: Deadline) -> Card {
+    *legal_plays(play, board).choose(self.rng.get_mut()).expect("the player to play holds a card")
+  }
+
+  fn notify_dummy(&mut self, _dummy: &PlayerHand) {}
+}
+
+/** A player that passes throughout the auction and plays its lowest legal card. */
+pub struct PassPlayer;
+
+impl Player for PassPlayer {
+  fn new(_hand: &PlayerHand) -> Self {
+    PassPlayer
+  }
+
+  fn get_call(&self, _auction: &Auction) -> Call {
+    Call::Pass
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, _deadline: Deadline) -> Card {
+    legal_plays(play, board).into_iter().min_by_key(|card| (card.rank(), card.suit())).expect("the player to play holds a card")
+  }
+
+  fn notify_dummy(&mut self, _dummy: &PlayerHand) {}
+}
+
+/** A player that bids naturally on high-card points and suit length, and plays to win each trick
+    as cheaply as it can: it leads its highest card, beats the card winning the trick with its
+    lowest card that does so unless partner is winning, and otherwise plays its lowest card,
+    keeping its trumps.
+ */
+pub struct GreedyPlayer {
+  hand: PlayerHand,
+}
+
+impl GreedyPlayer {
+  /** The suit to bid: the longest, and the higher of two as long. */
+  fn longest_suit(features: &HandFeatures) -> Suit {
+    Suit::iter().max_by_key(|&suit| (features.lengths[suit], suit)).expect("there are four suits")
+  }
+
+  /** The cheapest legal bid in `strain` up to `max_level`. */
+  fn cheapest_bid(auction: &Auction, strain: Strain, max_level: u8) -> Option<Call> {
+    (1..=max_level).map(|level| Call::Bid(Bid::from(level, strain))).find(|&call| auction.clone().make_call(call))
+  }
+
+  /** The game bid in `strain` if it is legal. */
+  fn game_bid(auction: &Auction, strain: Strain) -> Option<Call> {
+    let level = match strain {
+      Strain::Notrump => 3,
+      Strain::Trump(suit) if suit >= Suit::Hearts => 4,
+      Strain::Trump(_) => 5,
+    };
+    Some(Call::Bid(Bid::from(level, strain))).filter(|&call| auction.clone().make_call(call))
+  }
+
+  /** The strain of the last bid made by `seat`. */
+  fn last_strain(auction: &Auction, seat: Seat) -> Option<Strain> {
+    auction.calls().enumerate()
+        .filter(|&(i, _)| auction.bidder(i) == seat)
+        .filter_map(|(_, call)| match call {
+          Call::Bid(bid) => Some(bid.strain()),
+          _ => None,
+        })
+        .last()
+  }
+
+  fn choose_call(&self, auction: &Auction) -> Option<Call> {
+    let features = HandFeatures::of(&self.hand);
+    let bidder = auction.current_bidder();
+    if Self::last_strain(auction, bidder).is_some() {
+      return None;
+    }
+    match Self::last_strain(auction, bidder.partner()) {
+      None if features.hcp >= 12 => {
+        if features.is_balanced() && (15..=17).contains(&features.hcp) {
+          if let Some(call) = Self::cheapest_bid(auction, Strain::Notrump, 1) {
+            return Some(call);
+          }
+        }
+        Self::cheapest_bid(auction, Strain::Trump(Self::longest_suit(&features)), 2)
+      }
+      None => None,
+      Some(_) if features.hcp < 6 => None,
+      Some(Strain::Notrump) => Self::game_bid(auction, Strain::Notrump).filter(|_| features.hcp >= 10),
+      Some(Strain::Trump(suit)) if features.lengths[suit] >= 3 => {
+        let strain = Strain::Trump(suit);
+        let game = Self::game_bid(auction, strain).filter(|_| features.hcp >= 13);
+        game.or_else(|| Self::cheapest_bid(auction, strain, 3))
+      }
+      Some(_) => Self::cheapest_bid(auction, Strain::Trump(Self::longest_suit(&features)), 2)
+          .or_else(|| Self::cheapest_bid(auction, Strain::Notrump, 2)),
+    }
+  }
+}
+
+impl Player for GreedyPlayer {
+  fn new(hand: &PlayerHand) -> Self {
+    GreedyPlayer { hand: hand.clone() }
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    self.choose_call(auction).unwrap_or(Call::Pass)
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, _deadline: Deadline) -> Card {
+    let seat = play.next_player();
+    let trump = play.contract().strain();
+    let is_trump = |card: &Card| matches!(trump, Strain::Trump(suit) if card.suit() == suit);
+    let cards = legal_plays(play, board);
+    let lowest = |cards: &[Card]| *cards.iter().min_by_key(|&card| (is_trump(card), card.rank())).expect("the player to play holds a card");
+    let trick = match play.tricks().last().filter(|trick| trick.card_count() < 4) {
+      Some(trick) => trick,
+      None => return *cards.iter().max_by_key(|card| (card.rank(), card.suit())).expect("the player to play holds a card"),
+    };
+    let (winner, winning) = trick.cards_in_order()
+        .reduce(|best, next| if best.1.compare_with_trump(next.1, trump).is_lt() { next } else { best })
+        .expect("the trick has been led to");
+    if winner == seat.partner() {
+      return lowest(&cards);
+    }
+    let winners: Vec<Card> = cards.iter().copied().filter(|&card| winning.compare_with_trump(card, trump).is_lt()).collect();
+    if winners.is_empty() { lowest(&cards) } else { lowest(&winners) }
+  }
+
+  fn notify_dummy(&mut self, _dummy: &PlayerHand) {}
+}
@@ -0,0 +1,137 @@
+use std::os::raw::c_int;
+use crate::ai::dds::{dds_deal, dds_remain_cards, dds_result, DdsError, DoubleDummyTable, empty_future_tricks,
+                     lock_dds_with, plays_from_future, table_from_results};
+use crate::ai::dds_bindings::{allParResults, boards, CalcAllTables, ddTableDeal, ddTableDeals, ddTableResults,
+                              ddTablesRes, futureTricks, MAXNOOFBOARDS, MAXNOOFTABLES, parResults, SolveAllBoardsBin,
+                              solvedBoards};
+use crate::game_model::Board;
+use crate::game_model::cards::Card;
+use crate::game_model::play::Play;
+
+/** How far a batch has got: the positions or deals solved so far out of the whole batch. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BatchProgress {
+  pub solved: usize,
+  pub total: usize,
+}
+
+/** Solves large numbers of positions double dummy. Each call to DDS takes as many positions as it
+    allows, and DDS spreads them over its own threads; the results always come back in the order
+    the positions were given, however many threads there are.
+ */
+pub struct BatchSolver<'a> {
+  threads: usize,
+  memory: usize,
+  progress: Option<Box<dyn FnMut(BatchProgress) + 'a>>,
+}
+
+impl Default for BatchSolver<'_> {
+  fn default() -> Self {
+    BatchSolver::new()
+  }
+}
+
+impl<'a> BatchSolver<'a> {
+  /** A solver that leaves DDS to choose its threads and memory. */
+  pub fn new() -> Self {
+    BatchSolver { threads: 0, memory: 0, progress: None }
+  }
+
+  /** Limits the threads DDS solves with. Zero lets DDS use every core. */
+  pub fn with_threads(mut self, threads: usize) -> Self {
+    self.threads = threads;
+    self
+  }
+
+  /** Limits the memory DDS may use for its transposition tables, in megabytes. Zero lets DDS
+      decide.
+   */
+  pub fn with_memory(mut self, megabytes: usize) -> Self {
+    self.memory = megabytes;
+    self
+  }
+
+  /** Calls `progress` after each call to DDS with the number of positions solved so far. */
+  pub fn with_progress(mut self, progress: impl FnMut(BatchProgress) + 'a) -> Self {
+    self.progress = Some(Box::new(progress));
+    self
+  }
+
+  fn report(&mut self, solved: usize, total: usize) {
+    if let Some(progress) = &mut self.progress {
+      progress(BatchProgress { solved, total });
+    }
+  }
+
+  fn solve(&mut self, positions: &[(&Play, &Board)], solutions: c_int) -> Result<Vec<futureTricks>, DdsError> {
+    let mut results = Vec::with_capacity(positions.len());
+    if positions.is_empty() {
+      return Ok(results);
+    }
+    for chunk in positions.chunks(MAXNOOFBOARDS as usize) {
+      let mut bop = boards {
+        noOfBoards: chunk.len() as c_int,
+        deals: [dds_deal(chunk[0].0, chunk[0].1); MAXNOOFBOARDS as usize],
+        target: [-1; MAXNOOFBOARDS as usize],
+        solutions: [solutions; MAXNOOFBOARDS as usize],
+        mode: [1; MAXNOOFBOARDS as usize],
+      };
+      for (i, &(play, board)) in chunk.iter().enumerate() {
+        bop.deals[i] = dds_deal(play, board);
+      }
+      let mut solved = solvedBoards { noOfBoards: 0, solvedBoard: [empty_future_tricks(); MAXNOOFBOARDS as usize] };
+      {
+        let _dds = lock_dds_with(self.memory, self.threads);
+        dds_result(unsafe { SolveAllBoardsBin(&mut bop, &mut solved) })?;
+      }
+      results.extend_from_slice(&solved.solvedBoard[..chunk.len()]);
+      self.report(results.len(), positions.len());
+    }
+    Ok(results)
+  }
+
+  /** The most tricks the side due to play next can take in each position, as `double_dummy_tricks`
+      finds for one.
+   */
+  pub fn solve_tricks(&mut self, positions: &[(&Play, &Board)]) -> Result<Vec<u8>, DdsError> {
+    Ok(self.solve(positions, 1)?.iter().map(|fut| fut.score[0] as u8).collect())
+  }
+
+  /** Every legal card in each position with the tricks it leads to, as `double_dummy_plays` finds
+      for one.
+   */
+  pub fn solve_plays(&mut self, positions: &[(&Play, &Board)]) -> Result<Vec<Vec<(Card, u8)>>, DdsError> {
+    Ok(self.solve(positions, 3)?.iter().map(plays_from_future).collect())
+  }
+
+  /** The double-dummy table of each deal, as `double_dummy_table` finds for one. */
+  pub fn solve_tables(&mut self, deals: &[&Board]) -> Result<Vec<DoubleDummyTable>, DdsError> {
+    let mut tables = Vec::with_capacity(deals.len());
+    if deals.is_empty() {
+      return Ok(tables);
+    }
+    let empty_table = ddTableResults { resTable: [[0; 4]; 5] };
+    let empty_par = parResults { parScore: [[0; 16]; 2], parContractsString: [[0; 128]; 2] };
+    for chunk in deals.chunks(MAXNOOFTABLES as usize) {
+      let mut table_deals = ddTableDeals {
+        noOfTables: chunk.len() as c_int,
+        deals: [ddTableDeal { cards: [[0; 4]; 4] }; MAXNOOFBOARDS as usize],
+      };
+      for (i, board) in chunk.iter().enumerate() {
+        table_deals.deals[i] = ddTableDeal { cards: dds_remain_cards(board) };
+      }
+      let mut trump_filter = [0; 5];
+      let mut results = ddTablesRes { noOfBoards: 0, results: [empty_table; MAXNOOFBOARDS as usize] };
+      let mut par = allParResults { presults: [empty_par; MAXNOOFTABLES as usize] };
+      {
+        let _dds = lock_dds_with(self.memory, self.threads);
+        dds_result(unsafe {
+          CalcAllTables(&mut table_deals, -1, trump_filter.as_mut_ptr(), &mut results, &mut par)
+        })?;
+      }
+      tables.extend(results.results[..chunk.len()].iter().map(table_from_results));
+      self.report(tables.len(), deals.len());
+    }
+    Ok(tables)
+  }
+}
@@ -0,0 +1,309 @@
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+use crate::ai::bidding::{AuctionPattern, BiddingRule, BiddingSystem, Constraint};
+use crate::game_model::bidding::Call;
+use crate::game_model::cards::Suit;
+use crate::game_model::notation::Notation;
+
+// A bidding system file lists the rules for each point of the auction, under a heading naming the
+// auction so far. Comments start with `#`.
+//
+//   system SAYC
+//
+//   opening
+//     2C [alert] hcp 22+                 ; strong and artificial
+//     1N hcp 15-17 & balanced            ; 15-17 balanced
+//
+//   after 1N
+//     2D [alert] 5+ H & H >= S           ; transfer to hearts
+//     2C hcp 8+ & (4 S | 4 H)            ; Stayman, asks for a four-card major
+//
+// Each rule is a call, optional flags in brackets (`alert`, `priority N`), a constraint and, after
+// a semicolon, the meaning. Headings use the auction patterns of `AuctionPattern`; `opening` is the
+// auction before anyone has bid.
+
+/** An error in a bidding system file, with the line it was found on, counting from one.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LoadError {
+  pub line: usize,
+  pub message: String,
+}
+
+impl Display for LoadError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "line {}: {}", self.line, self.message)
+  }
+}
+
+impl std::error::Error for LoadError {}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+  Word(String),
+  Range(u8, u8),
+  And,
+  Or,
+  Not,
+  Open,
+  Close,
+  AtLeast,
+}
+
+fn number(chars: &mut Peekable<Chars>) -> Option<u8> {
+  let mut digits = String::new();
+  while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+    digits.push(c);
+    chars.next();
+  }
+  digits.parse().ok()
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+  let mut tokens = Vec::new();
+  let mut chars = text.chars().peekable();
+  while let Some(&c) = chars.peek() {
+    match c {
+      c if c.is_whitespace() => {
+        chars.next();
+      }
+      '&' | '|' | '!' | '(' | ')' => {
+        chars.next();
+        tokens.push(match c {
+          '&' => Token::And,
+          '|' => Token::Or,
+          '!' => Token::Not,
+          '(' => Token::Open,
+          _ => Token::Close,
+        });
+      }
+      '>' => {
+        chars.next();
+        if chars.next() != Some('=') {
+          return Err("expected >=".to_string());
+        }
+        tokens.push(Token::AtLeast);
+      }
+      c if c.is_ascii_digit() => {
+        let min = number(&mut chars).ok_or("bad number")?;
+        let max = match chars.peek() {
+          Some('+') => {
+            chars.next();
+            u8::MAX
+          }
+          Some('-') => {
+            chars.next();
+            number(&mut chars).ok_or("expected the end of a range")?
+          }
+          _ => min,
+        };
+        if max < min {
+          return Err(format!("empty range {}-{}", min, max));
+        }
+        tokens.push(Token::Range(min, max));
+      }
+      _ => {
+        let mut word = String::new();
+        while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || "♠♥♦♣".contains(**c)) {
+          word.push(c);
+          chars.next();
+        }
+        if word.is_empty() {
+          return Err(format!("unexpected {:?}", c));
+        }
+        tokens.push(Token::Word(word));
+      }
+    }
+  }
+  Ok(tokens)
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  position: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.position).cloned();
+    self.position += 1;
+    token
+  }
+
+  fn or(&mut self) -> Result<Constraint, String> {
+    let mut constraint = self.and()?;
+    while self.peek() == Some(&Token::Or) {
+      self.next();
+      constraint = constraint | self.and()?;
+    }
+    Ok(constraint)
+  }
+
+  fn and(&mut self) -> Result<Constraint, String> {
+    let mut constraint = self.unary()?;
+    while self.peek() == Some(&Token::And) {
+      self.next();
+      constraint = constraint & self.unary()?;
+    }
+    Ok(constraint)
+  }
+
+  fn unary(&mut self) -> Result<Constraint, String> {
+    match self.next() {
+      Some(Token::Not) => Ok(!self.unary()?),
+      Some(Token::Open) => {
+        let inner = self.or()?;
+        match self.next() {
+          Some(Token::Close) => Ok(inner),
+          _ => Err("expected )".to_string()),
+        }
+      }
+      Some(Token::Range(min, max)) => match self.next() {
+        Some(Token::Word(word)) => Ok(Constraint::Length(suit(&word)?, min, max.min(13))),
+        _ => Err("expected a suit after a length".to_string()),
+      },
+      Some(Token::Word(word)) => match word.to_ascii_lowercase().as_str() {
+        "any" => Ok(Constraint::Any),
+        "balanced" => Ok(Constraint::Balanced),
+        "hcp" => self.range().map(|(min, max)| Constraint::Hcp(min, max.min(37))),
+        "aces" => self.range().map(|(min, max)| Constraint::Aces(min, max.min(4))),
+        _ => {
+          let first = suit(&word)?;
+          match (self.next(), self.next()) {
+            (Some(Token::AtLeast), Some(Token::Word(other))) => {
+              Ok(Constraint::LongerOrEqual(first, suit(&other)?))
+            }
+            _ => Err(format!("expected >= after {}", word)),
+          }
+        }
+      },
+      Some(token) => Err(format!("unexpected {:?}", token)),
+      None => Err("unexpected end of constraint".to_string()),
+    }
+  }
+
+  fn range(&mut self) -> Result<(u8, u8), String> {
+    match self.next() {
+      Some(Token::Range(min, max)) => Ok((min, max)),
+      _ => Err("expected a number or range".to_string()),
+    }
+  }
+}
+
+fn suit(word: &str) -> Result<Suit, String> {
+  let mut chars = word.chars();
+  match (chars.next(), chars.next()) {
+    (Some(c), None) => Suit::try_from(c).map_err(|_| format!("unknown suit {}", word)),
+    _ => Err(format!("unknown word {}", word)),
+  }
+}
+
+impl Constraint {
+  /** Parses a constraint in the syntax that `Display` prints. */
+  pub fn parse(text: &str) -> Result<Self, String> {
+    let mut parser = Parser { tokens: tokenize(text)?, position: 0 };
+    let constraint = parser.or()?;
+    match parser.peek() {
+      None => Ok(constraint),
+      Some(token) => Err(format!("unexpected {:?}", token)),
+    }
+  }
+}
+
+fn parse_rule(line: &str, pattern: &AuctionPattern) -> Result<BiddingRule, String> {
+  let (rule, meaning) = line.split_once(';').unwrap_or((line, ""));
+  let rule = rule.trim();
+  let (call, rest) = rule.split_once(char::is_whitespace).unwrap_or((rule, ""));
+  let call = Call::from_notation(call).ok_or_else(|| format!("unknown call {}", call))?;
+  let mut rest = rest.trim_start();
+  let (mut priority, mut alertable) = (0, false);
+  if let Some(flagged) = rest.strip_prefix('[') {
+    let (flags, after) = flagged.split_once(']').ok_or("unclosed [")?;
+    let mut words = flags.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty());
+    while let Some(flag) = words.next() {
+      match flag {
+        "alert" => alertable = true,
+        "priority" => {
+          priority = words.next().and_then(|p| p.parse().ok()).ok_or("expected a priority")?;
+        }
+        _ => return Err(format!("unknown flag {}", flag)),
+      }
+    }
+    rest = after;
+  }
+  Ok(BiddingRule {
+    pattern: pattern.clone(),
+    constraint: if rest.trim().is_empty() { Constraint::Any } else { Constraint::parse(rest)? },
+    call,
+    meaning: meaning.trim().to_string(),
+    priority,
+    alertable,
+  })
+}
+
+/** Loads a bidding system from the text of a system file.
+ */
+pub fn load(text: &str) -> Result<BiddingSystem, LoadError> {
+  let mut system = BiddingSystem::new("");
+  let mut pattern = None;
+  for (i, line) in text.lines().enumerate() {
+    let error = |message: String| LoadError { line: i + 1, message };
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() {
+      continue;
+    }
+    if let Some(name) = line.strip_prefix("system ") {
+      system.name = name.trim().to_string();
+    } else if line == "opening" {
+      pattern = AuctionPattern::parse("");
+    } else if let Some(sequence) = line.strip_prefix("after ") {
+      pattern = Some(AuctionPattern::parse(sequence)
+          .ok_or_else(|| error(format!("bad auction {}", sequence.trim())))?);
+    } else {
+      let pattern = pattern.as_ref().ok_or_else(|| error("rule before any auction".to_string()))?;
+      system.add(parse_rule(line, pattern).map_err(error)?);
+    }
+  }
+  Ok(system)
+}
+
+impl BiddingSystem {
+  /** Writes the system in the file format. Rules are grouped under the first heading for their
+      auction, which keeps their order within each auction.
+   */
+  pub fn to_text(&self) -> String {
+    let mut patterns: Vec<&AuctionPattern> = Vec::new();
+    for rule in &self.rules {
+      if !patterns.contains(&&rule.pattern) {
+        patterns.push(&rule.pattern);
+      }
+    }
+    let mut text = if self.name.is_empty() { String::new() } else { format!("system {}\n", self.name) };
+    for pattern in patterns {
+      let heading = pattern.to_string();
+      if heading.is_empty() {
+        text.push_str("\nopening\n");
+      } else {
+        text.push_str(&format!("\nafter {}\n", heading));
+      }
+      for rule in self.rules.iter().filter(|rule| &rule.pattern == pattern) {
+        let mut flags = Vec::new();
+        if rule.alertable {
+          flags.push("alert".to_string());
+        }
+        if rule.priority != 0 {
+          flags.push(format!("priority {}", rule.priority));
+        }
+        let flags = if flags.is_empty() { String::new() } else { format!("[{}] ", flags.join(" ")) };
+        let line = format!("  {} {}{}", rule.call.notation(), flags, rule.constraint);
+        text.push_str(&format!("{:<40} ; {}\n", line, rule.meaning));
+      }
+    }
+    text
+  }
+}
@@ -0,0 +1,475 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitOr, Not};
+use enum_map::{enum_map, EnumMap};
+use crate::ai::trace::{CallTrace, RuleTrace};
+use crate::game_model::Seat;
+use crate::game_model::bidding::{Auction, Call, CallAnnotation, CallMeaning};
+use crate::game_model::cards::{PlayerHand, Rank, Suit};
+use crate::game_model::notation::Notation;
+
+pub mod format;
+pub mod sayc;
+pub mod simulation;
+pub mod validate;
+
+/** The features of a hand that bidding rules can test.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HandFeatures {
+  pub hcp: u8,
+  pub lengths: EnumMap<Suit, u8>,
+  pub aces: u8,
+}
+
+impl HandFeatures {
+  pub fn of(hand: &PlayerHand) -> Self {
+    HandFeatures {
+      hcp: hand.hcp(),
+      lengths: enum_map! { suit => hand.suit_length(suit) },
+      aces: hand.cards().iter().filter(|card| card.rank() == Rank::Ace).count() as u8,
+    }
+  }
+
+  /** A hand is balanced if it has no void or singleton and at most one doubleton: 4333, 4432 or
+      5332.
+   */
+  pub fn is_balanced(&self) -> bool {
+    let doubletons = self.lengths.values().filter(|&&length| length == 2).count();
+    self.lengths.values().all(|&length| length >= 2) && doubletons <= 1
+  }
+}
+
+/** A condition on a hand. Constraints are combined with `&`, `|` and `!`, and print in the syntax
+    of bidding system files, such as `hcp 8+ & (4 S | 4 H)`.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Constraint {
+  Any,
+  Hcp(u8, u8),
+  Length(Suit, u8, u8),
+  Aces(u8, u8),
+  Balanced,
+  /** At least as many cards in the first suit as in the second. */
+  LongerOrEqual(Suit, Suit),
+  Not(Box<Constraint>),
+  All(Vec<Constraint>),
+  AnyOf(Vec<Constraint>),
+}
+
+pub fn hcp(min: u8, max: u8) -> Constraint {
+  Constraint::Hcp(min, max)
+}
+
+pub fn length(suit: Suit, min: u8, max: u8) -> Constraint {
+  Constraint::Length(suit, min, max)
+}
+
+pub fn aces(min: u8, max: u8) -> Constraint {
+  Constraint::Aces(min, max)
+}
+
+pub fn balanced() -> Constraint {
+  Constraint::Balanced
+}
+
+pub fn longer_or_equal(suit: Suit, other: Suit) -> Constraint {
+  Constraint::LongerOrEqual(suit, other)
+}
+
+impl Constraint {
+  pub fn accepts(&self, hand: &HandFeatures) -> bool {
+    match self {
+      Constraint::Any => true,
+      Constraint::Hcp(min, max) => (min..=max).contains(&&hand.hcp),
+      Constraint::Length(suit, min, max) => (min..=max).contains(&&hand.lengths[*suit]),
+      Constraint::Aces(min, max) => (min..=max).contains(&&hand.aces),
+      Constraint::Balanced => hand.is_balanced(),
+      Constraint::LongerOrEqual(suit, other) => hand.lengths[*suit] >= hand.lengths[*other],
+      Constraint::Not(inner) => !inner.accepts(hand),
+      Constraint::All(parts) => parts.iter().all(|part| part.accepts(hand)),
+      Constraint::AnyOf(parts) => parts.iter().any(|part| part.accepts(hand)),
+    }
+  }
+
+  /** Returns the ranges of high-card points and suit lengths that every hand meeting the
+      constraint falls in. Conditions that are not ranges, such as negations, are left out.
+   */
+  pub fn bounds(&self) -> CallMeaning {
+    let mut meaning = CallMeaning::default();
+    match self {
+      Constraint::Hcp(min, max) => meaning.hcp = (*min, *max),
+      Constraint::Length(suit, min, max) => meaning.lengths[*suit] = (*min, *max),
+      Constraint::Balanced => meaning.lengths = enum_map! { _ => (2, 5) },
+      Constraint::All(parts) => {
+        for bounds in parts.iter().map(Constraint::bounds) {
+          meaning.hcp = intersect(meaning.hcp, bounds.hcp);
+          for (suit, range) in meaning.lengths.iter_mut() {
+            *range = intersect(*range, bounds.lengths[suit]);
+          }
+        }
+      }
+      Constraint::AnyOf(parts) => {
+        let mut parts = parts.iter().map(Constraint::bounds);
+        if let Some(first) = parts.next() {
+          meaning = parts.fold(first, |hull, bounds| CallMeaning {
+            hcp: union(hull.hcp, bounds.hcp),
+            lengths: enum_map! { suit => union(hull.lengths[suit], bounds.lengths[suit]) },
+          });
+        }
+      }
+      _ => {}
+    }
+    meaning
+  }
+}
+
+fn intersect((min, max): (u8, u8), (other_min, other_max): (u8, u8)) -> (u8, u8) {
+  (min.max(other_min), max.min(other_max))
+}
+
+fn union((min, max): (u8, u8), (other_min, other_max): (u8, u8)) -> (u8, u8) {
+  (min.min(other_min), max.max(other_max))
+}
+
+impl BitAnd for Constraint {
+  type Output = Constraint;
+
+  fn bitand(self, rhs: Self) -> Self::Output {
+    match (self, rhs) {
+      (Constraint::Any, other) | (other, Constraint::Any) => other,
+      (Constraint::All(mut parts), Constraint::All(more)) => {
+        parts.extend(more);
+        Constraint::All(parts)
+      }
+      (Constraint::All(mut parts), other) => {
+        parts.push(other);
+        Constraint::All(parts)
+      }
+      (first, second) => Constraint::All(vec![first, second]),
+    }
+  }
+}
+
+impl BitOr for Constraint {
+  type Output = Constraint;
+
+  fn bitor(self, rhs: Self) -> Self::Output {
+    match (self, rhs) {
+      (Constraint::AnyOf(mut parts), Constraint::AnyOf(more)) => {
+        parts.extend(more);
+        Constraint::AnyOf(parts)
+      }
+      (Constraint::AnyOf(mut parts), other) => {
+        parts.push(other);
+        Constraint::AnyOf(parts)
+      }
+      (first, second) => Constraint::AnyOf(vec![first, second]),
+    }
+  }
+}
+
+impl Not for Constraint {
+  type Output = Constraint;
+
+  fn not(self) -> Self::Output {
+    Constraint::Not(Box::new(self))
+  }
+}
+
+fn fmt_range(f: &mut Formatter<'_>, min: u8, max: u8, top: u8) -> std::fmt::Result {
+  if min == max {
+    write!(f, "{}", min)
+  } else if max >= top {
+    write!(f, "{}+", min)
+  } else if min == 0 {
+    write!(f, "0-{}", max)
+  } else {
+    write!(f, "{}-{}", min, max)
+  }
+}
+
+impl Display for Constraint {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let group = |f: &mut Formatter<'_>, part: &Constraint| match part {
+      Constraint::All(_) | Constraint::AnyOf(_) => write!(f, "({})", part),
+      _ => write!(f, "{}", part),
+    };
+    match self {
+      Constraint::Any => f.write_str("any"),
+      Constraint::Hcp(min, max) => {
+        f.write_str("hcp ")?;
+        fmt_range(f, *min, *max, 37)
+      }
+      Constraint::Length(suit, min, max) => {
+        fmt_range(f, *min, *max, 13)?;
+        write!(f, " {}", suit.letter())
+      }
+      Constraint::Aces(min, max) => {
+        f.write_str("aces ")?;
+        fmt_range(f, *min, *max, 4)
+      }
+      Constraint::Balanced => f.write_str("balanced"),
+      Constraint::LongerOrEqual(suit, other) => write!(f, "{} >= {}", suit.letter(), other.letter()),
+      Constraint::Not(inner) => {
+        f.write_str("!")?;
+        group(f, inner)
+      }
+      Constraint::All(parts) | Constraint::AnyOf(parts) => {
+        let separator = if matches!(self, Constraint::All(_)) { " & " } else { " | " };
+        for (i, part) in parts.iter().enumerate() {
+          if i > 0 {
+            f.write_str(separator)?;
+          }
+          group(f, part)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CallPattern {
+  Exact(Call),
+  Any,
+}
+
+impl CallPattern {
+  fn matches(&self, call: Call) -> bool {
+    match self {
+      CallPattern::Exact(expected) => *expected == call,
+      CallPattern::Any => true,
+    }
+  }
+}
+
+/** A pattern for the auction so far, counted from the opening bid. It is written as the calls
+    separated by `-`, with the opponents' calls in parentheses: `1N-2C` is a one notrump opening
+    and a two club response, and `(1H)-X` an opposing one heart opening and a double. Where two
+    calls in a row are by the same side, the other side is taken to have passed, and the pattern
+    always ends with the bidder's turn to call. The empty pattern matches an auction with no bid
+    yet, `*` matches any call, and a leading `...` matches any calls before the rest.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuctionPattern {
+  open_start: bool,
+  tokens: Vec<(bool, CallPattern)>,
+}
+
+impl AuctionPattern {
+  pub fn parse(text: &str) -> Option<Self> {
+    let mut pattern = AuctionPattern { open_start: false, tokens: Vec::new() };
+    let text = text.trim();
+    if text.is_empty() {
+      return Some(pattern);
+    }
+    for (i, token) in text.split('-').map(str::trim).enumerate() {
+      if token == "..." && i == 0 {
+        pattern.open_start = true;
+        continue;
+      }
+      let (theirs, call) = match token.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(call) => (true, call.trim()),
+        None => (false, token),
+      };
+      let call = if call == "*" {
+        CallPattern::Any
+      } else {
+        CallPattern::Exact(Call::from_notation(call)?)
+      };
+      pattern.tokens.push((theirs, call));
+    }
+    Some(pattern)
+  }
+
+  /** Returns the calls the pattern stands for, with the implied passes filled in. */
+  fn expanded(&self) -> Vec<CallPattern> {
+    let mut calls = Vec::with_capacity(self.tokens.len() * 2);
+    let mut last_theirs = None;
+    for &(theirs, call) in &self.tokens {
+      if last_theirs == Some(theirs) {
+        calls.push(CallPattern::Exact(Call::Pass));
+      }
+      calls.push(call);
+      last_theirs = Some(theirs);
+    }
+    if last_theirs == Some(false) {
+      calls.push(CallPattern::Exact(Call::Pass));
+    }
+    calls
+  }
+
+  /** Returns whether the pattern is a fixed sequence of calls, with no wildcards. */
+  pub fn is_exact(&self) -> bool {
+    !self.open_start && self.tokens.iter().all(|(_, call)| matches!(call, CallPattern::Exact(_)))
+  }
+
+  /** Builds the auction an exact pattern describes, opened by `dealer`. Returns `None` if the
+      pattern has wildcards or its calls are not a legal auction.
+   */
+  pub fn auction(&self, dealer: Seat) -> Option<Auction> {
+    if !self.is_exact() {
+      return None;
+    }
+    let mut auction = Auction::new(dealer);
+    for call in self.expanded() {
+      match call {
+        CallPattern::Exact(call) if auction.make_call(call) => {}
+        _ => return None,
+      }
+    }
+    Some(auction)
+  }
+
+  pub fn matches(&self, auction: &Auction) -> bool {
+    let calls: Vec<Call> = auction.calls().copied().skip_while(|&call| call == Call::Pass).collect();
+    let expected = self.expanded();
+    if self.open_start {
+      calls.len() >= expected.len()
+          && expected.iter().rev().zip(calls.iter().rev()).all(|(pattern, &call)| pattern.matches(call))
+    } else {
+      calls.len() == expected.len()
+          && expected.iter().zip(&calls).all(|(pattern, &call)| pattern.matches(call))
+    }
+  }
+}
+
+impl Display for AuctionPattern {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let mut tokens = Vec::with_capacity(self.tokens.len() + 1);
+    if self.open_start {
+      tokens.push("...".to_string());
+    }
+    for &(theirs, call) in &self.tokens {
+      let call = match call {
+        CallPattern::Exact(call) => call.notation(),
+        CallPattern::Any => "*".to_string(),
+      };
+      tokens.push(if theirs { format!("({})", call) } else { call });
+    }
+    f.write_str(&tokens.join("-"))
+  }
+}
+
+/** A single agreement: after an auction matching `pattern`, a hand meeting `constraint` makes
+    `call`, which carries `meaning`. Rules with a higher priority are preferred over earlier ones.
+    An alertable call is one the opponents must be told about.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BiddingRule {
+  pub pattern: AuctionPattern,
+  pub constraint: Constraint,
+  pub call: Call,
+  pub meaning: String,
+  pub priority: i32,
+  pub alertable: bool,
+}
+
+impl BiddingRule {
+  /** Returns what the rule discloses to the opponents about its call. */
+  pub fn annotation(&self) -> CallAnnotation {
+    CallAnnotation {
+      alert: self.alertable,
+      explanation: Some(self.meaning.clone()).filter(|meaning| !meaning.is_empty()),
+      meaning: Some(self.constraint.bounds()),
+    }
+  }
+}
+
+impl Display for BiddingRule {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}: {} ({})", self.call.notation(), self.meaning, self.constraint)
+  }
+}
+
+/** The call chosen by a bidding system, with the reason for it.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BidChoice {
+  pub call: Call,
+  pub explanation: String,
+}
+
+/** A bidding system: an ordered list of rules. For each decision the rule chosen is the one of
+    highest priority, and then the first, whose pattern matches the auction, whose constraint the
+    hand meets and whose call is legal.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct BiddingSystem {
+  name: String,
+  rules: Vec<BiddingRule>,
+}
+
+impl BiddingSystem {
+  pub fn new(name: &str) -> Self {
+    BiddingSystem { name: name.to_string(), rules: Vec::new() }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn rules(&self) -> &[BiddingRule] {
+    &self.rules
+  }
+
+  pub fn add(&mut self, rule: BiddingRule) {
+    self.rules.push(rule);
+  }
+
+  /** Adds a rule written as an auction pattern and a call in notation. Panics if either does not
+      parse, so it is meant for systems written in code.
+   */
+  pub fn rule(&mut self, pattern: &str, constraint: Constraint, call: &str, meaning: &str) {
+    self.add(BiddingRule {
+      pattern: AuctionPattern::parse(pattern).unwrap_or_else(|| panic!("bad pattern {:?}", pattern)),
+      constraint,
+      call: Call::from_notation(call).unwrap_or_else(|| panic!("bad call {:?}", call)),
+      meaning: meaning.to_string(),
+      priority: 0,
+      alertable: false,
+    });
+  }
+
+  /** Returns the rules that could apply at this point of the auction, in the order they are
+      considered.
+   */
+  pub fn candidates(&self, auction: &Auction) -> Vec<&BiddingRule> {
+    let mut candidates: Vec<&BiddingRule> = self.rules.iter()
+        .filter(|rule| {
+          rule.pattern.matches(auction) && {
+            let mut after = auction.clone();
+            after.make_call(rule.call)
+          }
+        })
+        .collect();
+    candidates.sort_by_key(|rule| -rule.priority);
+    candidates
+  }
+
+  /** Returns the rule that gives `call` its meaning after `auction`: the first candidate that
+      makes that call.
+   */
+  pub fn explain(&self, auction: &Auction, call: Call) -> Option<&BiddingRule> {
+    self.candidates(auction).into_iter().find(|rule| rule.call == call)
+  }
+
+  /** Chooses a call for `hand`. When no rule applies the hand passes. */
+  pub fn choose(&self, auction: &Auction, hand: &PlayerHand) -> BidChoice {
+    let features = HandFeatures::of(hand);
+    match self.candidates(auction).into_iter().find(|rule| rule.constraint.accepts(&features)) {
+      Some(rule) => BidChoice { call: rule.call, explanation: rule.to_string() },
+      None => BidChoice { call: Call::Pass, explanation: "P: no agreement applies".to_string() },
+    }
+  }
+
+  /** Chooses a call as `choose` does, listing every rule considered along the way. */
+  pub fn trace(&self, auction: &Auction, hand: &PlayerHand) -> CallTrace {
+    let features = HandFeatures::of(hand);
+    let rules = self.candidates(auction).into_iter()
+        .map(|rule| RuleTrace { rule: rule.to_string(), accepted: rule.constraint.accepts(&features) })
+        .collect();
+    let choice = self.choose(auction, hand);
+    CallTrace { chosen: choice.call, reason: choice.explanation, rules, simulation: Vec::new() }
+  }
+}
@@ -0,0 +1,10 @@
+use crate::ai::bidding::BiddingSystem;
+use crate::ai::bidding::format::load;
+
+/** A base version of Standard American Yellow Card: five-card majors, a strong notrump with
+    Stayman and Jacoby transfers, weak twos, limit raises, Jacoby 2NT, simple overcalls, takeout and
+    negative doubles and Blackwood. The rules are in `systems/sayc.txt`.
+ */
+pub fn sayc() -> BiddingSystem {
+  load(include_str!("../../../systems/sayc.txt")).expect("the SAYC system file is valid")
+}
@@ -0,0 +1,345 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use strum::IntoEnumIterator;
+use crate::ai::dds::{DdsError, double_dummy_table, DoubleDummyTable};
+use crate::ai::sampling::{DealSampler, infer_constraints};
+use crate::ai::trace::{CallTrace, DecisionTrace};
+use crate::game_model::{Board, HandResult, Seat, Vulnerability};
+use crate::game_model::bidding::{Auction, Bid, Call, CallAnnotation, Contract, Strain};
+use crate::game_model::cards::{Card, PlayerHand, Suit};
+use crate::game_model::notation::Notation;
+use crate::game_model::play::{Claim, Play};
+use crate::game_model::scoring::{datum, imps, ScoringForm};
+use crate::interface::{Deadline, Player};
+
+/** The contract the auction ends in if everyone passes after `call`: `None` if the board is passed
+    out, or if the call is not legal.
+ */
+pub fn final_contract(auction: &Auction, call: Call) -> Option<Option<Contract>> {
+  let mut after = auction.clone();
+  if after.is_complete() || !after.make_call(call) {
+    return None;
+  }
+  while !after.is_complete() {
+    after.make_call(Call::Pass);
+  }
+  Some(after.play().map(|play| play.contract()))
+}
+
+/** The calls worth comparing at a decision: pass, any legal double or redouble and, in each
+    strain, the cheapest legal bid, the game bid and the small slam.
+ */
+pub fn candidate_calls(auction: &Auction) -> Vec<Call> {
+  let mut calls = vec![Call::Pass, Call::Double, Call::Redouble];
+  let strains = Suit::iter().map(Strain::Trump).chain([Strain::Notrump]);
+  for strain in strains {
+    let game = match strain {
+      Strain::Notrump => 3,
+      Strain::Trump(suit) if suit >= Suit::Hearts => 4,
+      _ => 5,
+    };
+    let cheapest = (1..=7).find(|&level| auction.clone().make_call(Call::Bid(Bid::from(level, strain))));
+    for level in cheapest.into_iter().chain([game, 6]) {
+      let call = Call::Bid(Bid::from(level, strain));
+      if !calls.contains(&call) {
+        calls.push(call);
+      }
+    }
+  }
+  calls.retain(|&call| final_contract(auction, call).is_some());
+  calls
+}
+
+/** The simulated value of one call, averaged over the sampled deals, for the side making it. IMPs
+    are measured against the average score of all the calls on each deal, and matchpoints are the
+    fraction of the other calls beaten, with ties counting half.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallEvaluation {
+  pub call: Call,
+  pub contract: Option<Contract>,
+  pub average_score: f64,
+  pub imps: f64,
+  pub matchpoints: f64,
+}
+
+impl CallEvaluation {
+  pub fn value(&self, form: ScoringForm) -> f64 {
+    match form {
+      ScoringForm::Imps => self.imps,
+      ScoringForm::Matchpoints => self.matchpoints,
+      ScoringForm::TotalPoints => self.average_score,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct BiddingReport {
+  samples: usize,
+  calls: Vec<CallEvaluation>,
+}
+
+impl BiddingReport {
+  /** Builds a report from the double-dummy tables of the sampled deals. Each call is scored as if
+      everyone passes after it, for the side of the player due to call. Calls that are not legal are
+      left out.
+   */
+  pub fn from_tables(auction: &Auction, vul: Vulnerability, calls: &[Call], tables: &[DoubleDummyTable]) -> Self {
+    let bidder = auction.current_bidder();
+    let mut evaluations: Vec<CallEvaluation> = calls.iter()
+        .filter_map(|&call| Some(CallEvaluation {
+          call,
+          contract: final_contract(auction, call)?,
+          average_score: 0.0,
+          imps: 0.0,
+          matchpoints: 0.0,
+        }))
+        .collect();
+    for table in tables {
+      let scores: Vec<i32> = evaluations.iter()
+          .map(|evaluation| match evaluation.contract {
+            None => 0,
+            Some(contract) => {
+              let tricks = table[contract.strain()][contract.declarer()] as i8;
+              let score = HandResult::Played(contract, tricks - contract.level() as i8 - 6).score(vul);
+              if contract.declarer().is_opponent(bidder) { -score } else { score }
+            }
+          })
+          .collect();
+      let datum = datum(&scores);
+      for (i, evaluation) in evaluations.iter_mut().enumerate() {
+        evaluation.average_score += scores[i] as f64;
+        evaluation.imps += imps(scores[i] - datum) as f64;
+        let others = scores.len() - 1;
+        evaluation.matchpoints += if others == 0 {
+          0.5
+        } else {
+          let beaten = scores.iter().filter(|&&s| s < scores[i]).count();
+          let tied = scores.iter().filter(|&&s| s == scores[i]).count() - 1;
+          (beaten as f64 + tied as f64 / 2.0) / others as f64
+        };
+      }
+    }
+    if !tables.is_empty() {
+      for evaluation in &mut evaluations {
+        evaluation.average_score /= tables.len() as f64;
+        evaluation.imps /= tables.len() as f64;
+        evaluation.matchpoints /= tables.len() as f64;
+      }
+    }
+    BiddingReport { samples: tables.len(), calls: evaluations }
+  }
+
+  pub fn samples(&self) -> usize {
+    self.samples
+  }
+
+  pub fn calls(&self) -> &[CallEvaluation] {
+    &self.calls
+  }
+
+  pub fn best(&self, form: ScoringForm) -> Option<&CallEvaluation> {
+    self.calls.iter().max_by(|a, b| a.value(form).total_cmp(&b.value(form)))
+  }
+}
+
+impl Display for BiddingReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "Calls ({} deals)", self.samples)?;
+    let mut calls = self.calls.clone();
+    calls.sort_by(|a, b| b.imps.total_cmp(&a.imps));
+    for call in calls {
+      let contract = call.contract.map_or("Passout".to_string(), |contract| contract.notation());
+      writeln!(f, "{:>4}  {:<8}  score {:+8.1}  imps {:+6.2}  mp {:5.1}%", call.call.notation(), contract,
+               call.average_score, call.imps, call.matchpoints * 100.0)?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SimulationError {
+  /** The auction is already over. */
+  AuctionOver,
+  /** No deal consistent with the auction was found. */
+  NoSamples,
+  Dds(DdsError),
+}
+
+impl From<DdsError> for SimulationError {
+  fn from(err: DdsError) -> Self {
+    SimulationError::Dds(err)
+  }
+}
+
+/** Simulates each of `calls` for the player due to call, who holds `hand`. The other three hands
+    are dealt at random to fit the auction, and each deal is solved double dummy in every strain.
+ */
+pub fn simulate_calls<R: Rng + ?Sized>(auction: &Auction, hand: &PlayerHand, vul: Vulnerability, calls: &[Call],
+                                       samples: usize, rng: &mut R) -> Result<BiddingReport, SimulationError> {
+  if auction.is_complete() {
+    return Err(SimulationError::AuctionOver);
+  }
+  let deals = DealSampler::new()
+      .with_all_constraints(infer_constraints(auction))
+      .with_known_hand(auction.current_bidder(), hand)
+      .samples(samples, rng);
+  if deals.is_empty() {
+    return Err(SimulationError::NoSamples);
+  }
+  let tables = deals.into_iter()
+      .map(|hands| double_dummy_table(&Board::from_hands(1, hands)))
+      .collect::<Result<Vec<_>, _>>()?;
+  Ok(BiddingReport::from_tables(auction, vul, calls, &tables))
+}
+
+/** A player that makes its competitive decisions by simulation and leaves every other decision to
+    another player. Once both sides have bid, the inner player's call is compared with passing,
+    doubling and bidding on in each strain, and the call with the best expected score wins. The
+    vulnerability is the board's, once the player is told of it.
+ */
+pub struct SimulatedBidder<P: Player> {
+  inner: P,
+  hand: PlayerHand,
+  vulnerability: Vulnerability,
+  samples: usize,
+  scoring: ScoringForm,
+  rng: RefCell<StdRng>,
+  trace: RefCell<Option<DecisionTrace>>,
+}
+
+impl<P: Player> SimulatedBidder<P> {
+  pub fn with_vulnerability(mut self, vulnerability: Vulnerability) -> Self {
+    self.vulnerability = vulnerability;
+    self
+  }
+
+  pub fn with_samples(mut self, samples: usize) -> Self {
+    self.samples = samples;
+    self
+  }
+
+  pub fn with_scoring(mut self, scoring: ScoringForm) -> Self {
+    self.scoring = scoring;
+    self
+  }
+
+  pub fn with_seed(mut self, seed: u64) -> Self {
+    self.rng = RefCell::new(StdRng::seed_from_u64(seed));
+    self
+  }
+
+  /** An auction is competitive once both sides have bid. */
+  fn is_competitive(auction: &Auction) -> bool {
+    let bidders: Vec<Seat> = auction.calls()
+        .enumerate()
+        .filter(|(_, call)| matches!(call, Call::Bid(_)))
+        .map(|(i, _)| auction.bidder(i))
+        .collect();
+    bidders.iter().any(|seat| seat.is_opponent(bidders[0]))
+  }
+
+  /** The calls compared with the inner player's: pass, double and the cheapest bid in each strain
+      the side has bid.
+   */
+  fn alternatives(auction: &Auction, call: Call) -> Vec<Call> {
+    let bidder = auction.current_bidder();
+    let mut candidates = vec![call, Call::Pass, Call::Double];
+    for (i, &made) in auction.calls().enumerate() {
+      if let Call::Bid(bid) = made {
+        if !auction.bidder(i).is_opponent(bidder) {
+          candidates.extend((bid.level()..=7)
+              .map(|level| Call::Bid(Bid::from(level, bid.strain())))
+              .find(|&call| auction.clone().make_call(call)));
+        }
+      }
+    }
+    let mut calls = Vec::new();
+    for call in candidates {
+      if !calls.contains(&call) && final_contract(auction, call).is_some() {
+        calls.push(call);
+      }
+    }
+    calls
+  }
+}
+
+impl<P: Player> Player for SimulatedBidder<P> {
+  fn new(hand: &PlayerHand) -> Self {
+    SimulatedBidder {
+      inner: P::new(hand),
+      hand: hand.clone(),
+      vulnerability: Vulnerability::Neither,
+      samples: 50,
+      scoring: ScoringForm::Imps,
+      rng: RefCell::new(StdRng::from_entropy()),
+      trace: RefCell::new(None),
+    }
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    *self.trace.borrow_mut() = None;
+    let call = self.inner.get_call(auction);
+    if !Self::is_competitive(auction) {
+      return call;
+    }
+    let calls = Self::alternatives(auction, call);
+    let report = simulate_calls(auction, &self.hand, self.vulnerability, &calls, self.samples,
+                                &mut *self.rng.borrow_mut());
+    let report = match report {
+      Ok(report) => report,
+      Err(_) => return call,
+    };
+    let chosen = report.best(self.scoring).map_or(call, |best| best.call);
+    let mut trace = match self.inner.last_decision() {
+      Some(DecisionTrace::Call(trace)) => trace,
+      _ => CallTrace { chosen: call, reason: String::new(), rules: Vec::new(), simulation: Vec::new() },
+    };
+    trace.reason = if chosen == call {
+      format!("{}; simulation over {} deals agrees", trace.reason, report.samples())
+    } else {
+      format!("{} rather than the system's {} over {} deals", chosen.notation(), call.notation(), report.samples())
+    };
+    trace.chosen = chosen;
+    trace.simulation = report.calls().to_vec();
+    *self.trace.borrow_mut() = Some(DecisionTrace::Call(trace));
+    chosen
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: Deadline) -> Card {
+    *self.trace.get_mut() = None;
+    self.inner.get_play(play, board, deadline)
+  }
+
+  fn notify_dummy(&mut self, dummy: &PlayerHand) {
+    self.inner.notify_dummy(dummy)
+  }
+
+  fn notify_board(&mut self, number: u32, vulnerability: Vulnerability) {
+    self.vulnerability = vulnerability;
+    self.inner.notify_board(number, vulnerability)
+  }
+
+  fn notify_auction(&mut self, auction: &Auction) {
+    self.inner.notify_auction(auction)
+  }
+
+  fn explain_call(&self, auction: &Auction, call: Call) -> Option<CallAnnotation> {
+    self.inner.explain_call(auction, call)
+  }
+
+  fn offer_claim(&mut self, play: &Play, board: &Board) -> Option<(u8, String)> {
+    self.inner.offer_claim(play, board)
+  }
+
+  fn respond_to_claim(&mut self, play: &Play, board: &Board, claim: &Claim) -> bool {
+    self.inner.respond_to_claim(play, board, claim)
+  }
+
+  fn last_decision(&self) -> Option<DecisionTrace> {
+    self.trace.borrow().clone().or_else(|| self.inner.last_decision())
+  }
+}
@@ -0,0 +1,191 @@
+use std::fmt::{Display, Formatter};
+use enum_map::enum_map;
+use crate::ai::bidding::{AuctionPattern, BiddingRule, BiddingSystem, Constraint, HandFeatures};
+use crate::game_model::Seat;
+use crate::game_model::cards::Suit;
+
+/** Two rules at the same auction that both accept some hands. Only the first of them is ever chosen
+    for those hands. Rules are given by their index in the system's rules.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Overlap {
+  pub first: usize,
+  pub second: usize,
+  pub hands: usize,
+  pub example: HandFeatures,
+}
+
+/** The problems found at one auction of a bidding system. Hands are counted as distinct
+    combinations of high-card points, shape and (where a rule asks about them) aces, not weighted
+    by how often they are dealt.
+ */
+#[derive(Debug, Clone)]
+pub struct NodeReport {
+  pub pattern: AuctionPattern,
+  /** The rules for the auction, in the order they were added. */
+  pub rules: Vec<usize>,
+  pub uncovered: usize,
+  pub uncovered_example: Option<HandFeatures>,
+  pub overlaps: Vec<Overlap>,
+  /** Rules that never apply, because earlier rules take every hand they accept. */
+  pub unreachable: Vec<usize>,
+  /** Rules whose call is not legal after the auction. */
+  pub illegal: Vec<usize>,
+}
+
+impl NodeReport {
+  pub fn is_clean(&self) -> bool {
+    self.uncovered == 0 && self.overlaps.is_empty() && self.unreachable.is_empty() && self.illegal.is_empty()
+  }
+}
+
+fn describe(hand: &HandFeatures) -> String {
+  let shape: Vec<String> = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs]
+      .iter()
+      .map(|&suit| hand.lengths[suit].to_string())
+      .collect();
+  format!("{} hcp, {} shape, {} aces", hand.hcp, shape.join("-"), hand.aces)
+}
+
+impl Display for NodeReport {
+  /** Rules are numbered from one in the order they appear under the auction's heading. */
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let number = |rule: &usize| self.rules.iter().position(|r| r == rule).unwrap_or(0) + 1;
+    let heading = self.pattern.to_string();
+    writeln!(f, "{}", if heading.is_empty() { "opening" } else { &heading })?;
+    if let Some(example) = &self.uncovered_example {
+      writeln!(f, "  {} hands are not covered, such as {}", self.uncovered, describe(example))?;
+    }
+    for overlap in &self.overlaps {
+      writeln!(f, "  rules {} and {} overlap on {} hands, such as {}", number(&overlap.first), number(&overlap.second),
+               overlap.hands, describe(&overlap.example))?;
+    }
+    for rule in &self.unreachable {
+      writeln!(f, "  rule {} is never used", number(rule))?;
+    }
+    for rule in &self.illegal {
+      writeln!(f, "  rule {} makes an illegal call", number(rule))?;
+    }
+    Ok(())
+  }
+}
+
+/** The most high-card points a suit of the given length can hold. */
+fn max_hcp(length: u8) -> u8 {
+  [0, 4, 7, 9, 10][length.min(4) as usize]
+}
+
+/** Every combination of high-card points, shape and aces that a hand can have. Aces are only
+    varied when `with_aces` is set.
+ */
+fn feature_space(with_aces: bool) -> Vec<HandFeatures> {
+  let mut space = Vec::new();
+  for spades in 0..=13u8 {
+    for hearts in 0..=13 - spades {
+      for diamonds in 0..=13 - spades - hearts {
+        let clubs = 13 - spades - hearts - diamonds;
+        let lengths = enum_map! {
+          Suit::Spades => spades,
+          Suit::Hearts => hearts,
+          Suit::Diamonds => diamonds,
+          Suit::Clubs => clubs,
+        };
+        let suits = lengths.values().filter(|&&length| length > 0).count() as u8;
+        let most = lengths.values().map(|&length| max_hcp(length)).sum::<u8>();
+        for hcp in 0..=most {
+          let aces = if with_aces { 0..=suits.min(hcp / 4) } else { 0..=0 };
+          for aces in aces {
+            space.push(HandFeatures { hcp, lengths, aces });
+          }
+        }
+      }
+    }
+  }
+  space
+}
+
+fn uses_aces(constraint: &Constraint) -> bool {
+  match constraint {
+    Constraint::Aces(_, _) => true,
+    Constraint::Not(inner) => uses_aces(inner),
+    Constraint::All(parts) | Constraint::AnyOf(parts) => parts.iter().any(uses_aces),
+    _ => false,
+  }
+}
+
+/** Checks every auction of a system for hands that no rule covers, rules that overlap, rules that
+    are never used and rules whose calls are illegal. Returns a report for each auction, in the
+    order the auctions first appear.
+ */
+pub fn validate(system: &BiddingSystem) -> Vec<NodeReport> {
+  let mut patterns: Vec<&AuctionPattern> = Vec::new();
+  for rule in system.rules() {
+    if !patterns.contains(&&rule.pattern) {
+      patterns.push(&rule.pattern);
+    }
+  }
+  let plain = feature_space(false);
+  let with_aces = feature_space(true);
+  patterns.into_iter().map(|pattern| {
+    let rules: Vec<usize> = (0..system.rules().len())
+        .filter(|&i| &system.rules()[i].pattern == pattern)
+        .collect();
+    validate_node(system, pattern, rules, &plain, &with_aces)
+  }).collect()
+}
+
+fn validate_node(system: &BiddingSystem, pattern: &AuctionPattern, mut rules: Vec<usize>,
+                 plain: &[HandFeatures], with_aces: &[HandFeatures]) -> NodeReport {
+  let all = system.rules();
+  let illegal = match pattern.auction(Seat::North) {
+    Some(auction) => rules.iter().copied().filter(|&i| {
+      let mut after = auction.clone();
+      !after.make_call(all[i].call)
+    }).collect(),
+    None => Vec::new(),
+  };
+  let mut report = NodeReport {
+    pattern: pattern.clone(),
+    rules: rules.clone(),
+    uncovered: 0,
+    uncovered_example: None,
+    overlaps: Vec::new(),
+    unreachable: Vec::new(),
+    illegal,
+  };
+  rules.retain(|i| !report.illegal.contains(i));
+  rules.sort_by_key(|&i| -all[i].priority);
+  let node: Vec<&BiddingRule> = rules.iter().map(|&i| &all[i]).collect();
+  let space = if node.iter().any(|rule| uses_aces(&rule.constraint)) { with_aces } else { plain };
+
+  let mut chosen = vec![0usize; node.len()];
+  let mut overlaps = vec![vec![(0usize, None); node.len()]; node.len()];
+  for hand in space {
+    let accepted: Vec<usize> = (0..node.len()).filter(|&r| node[r].constraint.accepts(hand)).collect();
+    match accepted.first() {
+      Some(&first) => {
+        chosen[first] += 1;
+        for &other in &accepted[1..] {
+          if node[other].call != node[first].call && node[other].constraint != Constraint::Any {
+            let (count, example) = &mut overlaps[first][other];
+            *count += 1;
+            example.get_or_insert(*hand);
+          }
+        }
+      }
+      None => {
+        report.uncovered += 1;
+        report.uncovered_example.get_or_insert(*hand);
+      }
+    }
+  }
+  for (first, row) in overlaps.iter().enumerate() {
+    for (second, &(hands, example)) in row.iter().enumerate() {
+      if let Some(example) = example {
+        report.overlaps.push(Overlap { first: rules[first], second: rules[second], hands, example });
+      }
+    }
+  }
+  report.unreachable = (0..node.len()).filter(|&r| chosen[r] == 0).map(|r| rules[r]).collect();
+  report
+}
@@ -0,0 +1,23 @@
+use crate::ai::dds::{DdsError, double_dummy_tricks};
+use crate::game_model::{Board, Seat};
+use crate::game_model::play::{Claim, Play};
+
+/** Returns the number of remaining tricks that `seat`'s side can take double dummy from the current
+    position.
+ */
+pub fn claimable_tricks(play: &Play, board: &Board, seat: Seat) -> Result<u8, DdsError> {
+  let tricks = double_dummy_tricks(play, board)?;
+  if play.next_player().is_opponent(seat) {
+    Ok(play.remaining_tricks() - tricks)
+  } else {
+    Ok(tricks)
+  }
+}
+
+/** Checks a claim double dummy. The claim holds if the claimer's side can take the claimed number
+    of tricks against every defence. This looks at all four hands, so it is for the table to rule
+    on a claim with, not for a player to decide whether to make or accept one.
+ */
+pub fn validate_claim(play: &Play, board: &Board, claim: &Claim) -> Result<bool, DdsError> {
+  Ok(claimable_tricks(play, board, claim.claimer())? >= claim.tricks())
+}
@@ -0,0 +1,424 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use strum::IntoEnumIterator;
+use crate::ai::declarer::{Defender, Layout, layouts, OurHand, SuitCombination, VacantPlaces};
+use crate::game_model::cards::Rank;
+
+/** What a line of play is chosen for. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Objective {
+  /** The most tricks on average. */
+  ExpectedTricks,
+  /** The best chance of taking at least this many tricks. */
+  AtLeast(u8),
+}
+
+/** What is known about one defender's holding in the suit before play starts. */
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Knowledge {
+  length: (u8, u8),
+  cards: Vec<Rank>,
+}
+
+impl Default for Knowledge {
+  fn default() -> Self {
+    Knowledge { length: (0, 13), cards: Vec::new() }
+  }
+}
+
+impl Knowledge {
+  fn allows(&self, cards: &[Rank]) -> bool {
+    let length = cards.len() as u8;
+    length >= self.length.0 && length <= self.length.1 && self.cards.iter().all(|rank| cards.contains(rank))
+  }
+}
+
+/** Finds the best way to play a suit combination in the manner of the suit-combination tables: every
+    layout of the missing cards is weighed by its a-priori probability, and the defenders, who see
+    all the cards, play their best against each of declarer's plays. Declarer only ever acts on the
+    cards seen so far.
+ */
+#[derive(Debug, Clone)]
+pub struct SuitAnalyser {
+  combination: SuitCombination,
+  vacant: VacantPlaces,
+  entries: [u8; 2],
+  lead: Option<OurHand>,
+  lho: Knowledge,
+  rho: Knowledge,
+  objective: Objective,
+  positions: RefCell<HashMap<Position, Vec<u8>>>,
+}
+
+/** The result of an analysis: the first card of the best line, and how many tricks it takes in each
+    layout of the missing cards.
+ */
+#[derive(Debug, Clone)]
+pub struct Analysis {
+  pub lead: (OurHand, Rank),
+  pub layouts: Vec<(Layout, f64, u8)>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Seat {
+  Ours(OurHand),
+  Theirs(Defender),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Lead {
+  Either,
+  Hand(OurHand),
+  Defenders,
+}
+
+#[derive(Debug, Clone)]
+struct World {
+  lho: Vec<Rank>,
+  rho: Vec<Rank>,
+  weight: f64,
+}
+
+impl World {
+  fn holding(&self, defender: Defender) -> &[Rank] {
+    match defender {
+      Defender::Lho => &self.lho,
+      Defender::Rho => &self.rho,
+    }
+  }
+
+  fn cards(&mut self, defender: Defender) -> &mut Vec<Rank> {
+    match defender {
+      Defender::Lho => &mut self.lho,
+      Defender::Rho => &mut self.rho,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+  ours: [Vec<Rank>; 2],
+  entries: [u8; 2],
+  lead: Lead,
+  trick: Vec<(Seat, Option<Rank>)>,
+  played: Vec<Rank>,
+  won: u8,
+}
+
+impl Node {
+  fn seat(&self, position: usize) -> Seat {
+    let leader = match self.trick.first() {
+      Some(&(Seat::Ours(leader), _)) => leader,
+      _ => unreachable!("declarer's side leads every round"),
+    };
+    match position {
+      0 => Seat::Ours(leader),
+      1 => Seat::Theirs(leader.left()),
+      2 => Seat::Ours(leader.other()),
+      _ => Seat::Theirs(leader.right()),
+    }
+  }
+
+  fn play(&self, seat: Seat, card: Option<Rank>) -> Node {
+    let mut node = self.clone();
+    if let (Seat::Ours(hand), Some(rank)) = (seat, card) {
+      node.ours[hand as usize].retain(|&r| r != rank);
+    }
+    node.trick.push((seat, card));
+    node
+  }
+}
+
+fn mask(cards: &[Rank]) -> u16 {
+  cards.iter().fold(0, |mask, &rank| mask | 1 << rank as u8)
+}
+
+/** A position between tricks, as far as the rest of the play is concerned. */
+type Position = (u16, u16, u16, [u8; 2], Lead, u8, Vec<(u16, u16)>);
+
+/** The lowest card of each group of cards in `hand` that are equal in play, since no card between
+    them is still out.
+ */
+fn equivalent_cards(hand: &[Rank], played: &[Rank]) -> Vec<Rank> {
+  let mut cards = hand.to_vec();
+  cards.sort_by(|a, b| b.cmp(a));
+  let mut choices: Vec<Rank> = Vec::new();
+  for (i, &rank) in cards.iter().enumerate() {
+    let touching = i > 0 && Rank::iter().all(|r| r >= cards[i - 1] || r <= rank || played.contains(&r));
+    if touching {
+      *choices.last_mut().unwrap() = rank;
+    } else {
+      choices.push(rank);
+    }
+  }
+  choices
+}
+
+impl SuitAnalyser {
+  pub fn new(combination: SuitCombination) -> Self {
+    SuitAnalyser {
+      combination,
+      vacant: VacantPlaces::default(),
+      entries: [13, 13],
+      lead: None,
+      lho: Knowledge::default(),
+      rho: Knowledge::default(),
+      objective: Objective::ExpectedTricks,
+      positions: RefCell::default(),
+    }
+  }
+
+  /** Limits how often each hand can be reached from outside the suit. Leading again from the hand
+      that won the last trick needs no entry.
+   */
+  pub fn with_entries(mut self, declarer: u8, dummy: u8) -> Self {
+    self.entries = [declarer, dummy];
+    self
+  }
+
+  /** Starts the play in one hand instead of letting the analyser choose. */
+  pub fn with_lead(mut self, hand: OurHand) -> Self {
+    self.lead = Some(hand);
+    self
+  }
+
+  pub fn with_vacant_places(mut self, vacant: VacantPlaces) -> Self {
+    self.vacant = vacant;
+    self
+  }
+
+  /** Restricts a defender's length in the suit, such as a singleton known from the bidding. */
+  pub fn with_length(mut self, defender: Defender, min: u8, max: u8) -> Self {
+    self.knowledge(defender).length = (min, max);
+    self
+  }
+
+  /** Places a missing card with a defender. */
+  pub fn with_card(mut self, defender: Defender, rank: Rank) -> Self {
+    self.knowledge(defender).cards.push(rank);
+    self
+  }
+
+  pub fn with_objective(mut self, objective: Objective) -> Self {
+    self.objective = objective;
+    self
+  }
+
+  fn knowledge(&mut self, defender: Defender) -> &mut Knowledge {
+    match defender {
+      Defender::Lho => &mut self.lho,
+      Defender::Rho => &mut self.rho,
+    }
+  }
+
+  /** The layouts that agree with what is known, with their probabilities given that knowledge. */
+  pub fn layouts(&self) -> Vec<(Layout, f64)> {
+    let mut layouts: Vec<(Layout, f64)> = layouts(&self.combination.missing(), self.vacant)
+        .into_iter()
+        .filter(|(layout, _)| self.lho.allows(&layout.lho) && self.rho.allows(&layout.rho))
+        .collect();
+    let total: f64 = layouts.iter().map(|(_, p)| p).sum();
+    for (_, p) in &mut layouts {
+      *p /= total;
+    }
+    layouts
+  }
+
+  /** Finds the best line for the objective. Returns `None` if declarer has no cards in the suit or
+      no layout agrees with what is known.
+   */
+  pub fn analyse(&self) -> Option<Analysis> {
+    self.positions.borrow_mut().clear();
+    let layouts = self.layouts();
+    if layouts.is_empty() {
+      return None;
+    }
+    let worlds: Vec<World> = layouts.iter().map(|(layout, p)| World {
+      lho: layout.lho.clone(),
+      rho: layout.rho.clone(),
+      weight: *p,
+    }).collect();
+    let root = Node {
+      ours: [self.combination.hand(OurHand::Declarer).to_vec(), self.combination.hand(OurHand::Dummy).to_vec()],
+      entries: self.entries,
+      lead: self.lead.map_or(Lead::Either, Lead::Hand),
+      trick: Vec::new(),
+      played: self.combination.played().to_vec(),
+      won: 0,
+    };
+    let (lead, tricks) = self.best(self.leads(&root), &worlds)?;
+    let lead = match lead.trick[0] {
+      (Seat::Ours(hand), Some(rank)) => (hand, rank),
+      _ => unreachable!("a lead is always one of our cards"),
+    };
+    let layouts = layouts.into_iter().zip(tricks).map(|((layout, p), tricks)| (layout, p, tricks)).collect();
+    Some(Analysis { lead, layouts })
+  }
+
+  fn leads(&self, node: &Node) -> Vec<Node> {
+    let mut leads = Vec::new();
+    for hand in [OurHand::Declarer, OurHand::Dummy] {
+      let free = match node.lead {
+        Lead::Either => true,
+        Lead::Hand(on_lead) => on_lead == hand,
+        Lead::Defenders => false,
+      };
+      if node.ours[hand as usize].is_empty() || (!free && node.entries[hand as usize] == 0) {
+        continue;
+      }
+      let mut from = node.clone();
+      if !free {
+        from.entries[hand as usize] -= 1;
+      }
+      for rank in equivalent_cards(&node.ours[hand as usize], &node.played) {
+        leads.push(from.play(Seat::Ours(hand), Some(rank)));
+      }
+    }
+    leads
+  }
+
+  /** The candidate that does best for the objective, with the tricks it takes in each world. */
+  fn best(&self, candidates: Vec<Node>, worlds: &[World]) -> Option<(Node, Vec<u8>)> {
+    let score = |node: &Node, tricks: &[u8]| {
+      let expected: f64 = worlds.iter().zip(tricks).map(|(world, &t)| world.weight * t as f64).sum();
+      let objective = match self.objective {
+        Objective::ExpectedTricks => expected,
+        Objective::AtLeast(target) => {
+          worlds.iter().zip(tricks).filter(|&(_, &t)| node.won + t >= target).map(|(world, _)| world.weight).sum()
+        }
+      };
+      (objective, expected)
+    };
+    let mut best: Option<(Node, Vec<u8>, (f64, f64))> = None;
+    for candidate in candidates {
+      let tricks = self.search(&candidate, worlds);
+      let value = score(&candidate, &tricks);
+      let better = best.as_ref().is_none_or(|(_, _, best)| {
+        value.0 > best.0 + 1e-12 || (value.0 > best.0 - 1e-12 && value.1 > best.1 + 1e-12)
+      });
+      if better {
+        best = Some((candidate, tricks, value));
+      }
+    }
+    best.map(|(node, tricks, _)| (node, tricks))
+  }
+
+  /** The tricks still to come in each world, with declarer playing for the objective. */
+  fn search(&self, node: &Node, worlds: &[World]) -> Vec<u8> {
+    if worlds.is_empty() {
+      return Vec::new();
+    }
+    match node.trick.len() {
+      0 => {
+        let position = (
+          mask(&node.ours[0]),
+          mask(&node.ours[1]),
+          mask(&node.played),
+          node.entries,
+          node.lead,
+          node.won,
+          worlds.iter().map(|world| (mask(&world.lho), mask(&world.rho))).collect(),
+        );
+        if let Some(tricks) = self.positions.borrow().get(&position) {
+          return tricks.clone();
+        }
+        let tricks = self.best(self.leads(node), worlds).map_or(vec![0; worlds.len()], |(_, tricks)| tricks);
+        self.positions.borrow_mut().insert(position, tricks.clone());
+        tricks
+      }
+      4 => self.finish_trick(node, worlds),
+      position => match node.seat(position) {
+        Seat::Ours(hand) => {
+          let cards = &node.ours[hand as usize];
+          let candidates = if cards.is_empty() {
+            vec![node.play(Seat::Ours(hand), None)]
+          } else {
+            equivalent_cards(cards, &node.played).into_iter().map(|rank| node.play(Seat::Ours(hand), Some(rank))).collect()
+          };
+          self.best(candidates, worlds).expect("there is always a card to play").1
+        }
+        Seat::Theirs(defender) => self.defend(node, defender, worlds),
+      },
+    }
+  }
+
+  /** Each defender plays whatever holds declarer to the fewest tricks in its own world. */
+  fn defend(&self, node: &Node, defender: Defender, worlds: &[World]) -> Vec<u8> {
+    let choices: Vec<Vec<Option<Rank>>> = worlds.iter().map(|world| {
+      let cards = world.holding(defender);
+      if cards.is_empty() {
+        vec![None]
+      } else {
+        equivalent_cards(cards, &node.played).into_iter().map(Some).collect()
+      }
+    }).collect();
+    let mut options: Vec<Option<Rank>> = choices.iter().flatten().copied().collect();
+    options.sort();
+    options.dedup();
+    let mut tricks = vec![u8::MAX; worlds.len()];
+    for option in options {
+      let (indices, subset): (Vec<usize>, Vec<World>) = worlds.iter().enumerate()
+          .filter(|&(i, _)| choices[i].contains(&option))
+          .map(|(i, world)| {
+            let mut world = world.clone();
+            world.cards(defender).retain(|&rank| Some(rank) != option);
+            (i, world)
+          })
+          .unzip();
+      let results = self.search(&node.play(Seat::Theirs(defender), option), &subset);
+      for (i, result) in indices.into_iter().zip(results) {
+        tricks[i] = tricks[i].min(result);
+      }
+    }
+    tricks
+  }
+
+  fn finish_trick(&self, node: &Node, worlds: &[World]) -> Vec<u8> {
+    let winner = node.trick.iter()
+        .filter_map(|&(seat, card)| card.map(|rank| (seat, rank)))
+        .max_by_key(|&(_, rank)| rank)
+        .map(|(seat, _)| seat);
+    let mut next = node.clone();
+    next.played.extend(node.trick.iter().filter_map(|&(_, card)| card));
+    next.trick.clear();
+    let won = match winner {
+      Some(Seat::Ours(hand)) => {
+        next.lead = Lead::Hand(hand);
+        next.won += 1;
+        1
+      }
+      _ => {
+        next.lead = Lead::Defenders;
+        0
+      }
+    };
+    self.search(&next, worlds).into_iter().map(|tricks| tricks + won).collect()
+  }
+}
+
+impl Analysis {
+  pub fn expected_tricks(&self) -> f64 {
+    self.layouts.iter().map(|&(_, p, tricks)| p * tricks as f64).sum()
+  }
+
+  /** The chance of taking at least `tricks` tricks. */
+  pub fn probability(&self, tricks: u8) -> f64 {
+    self.layouts.iter().filter(|&&(_, _, t)| t >= tricks).map(|&(_, p, _)| p).sum()
+  }
+
+  pub fn max_tricks(&self) -> u8 {
+    self.layouts.iter().map(|&(_, _, tricks)| tricks).max().unwrap_or(0)
+  }
+}
+
+impl Display for Analysis {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let (hand, rank) = self.lead;
+    writeln!(f, "lead the {} from {}: {:.2} tricks on average", rank, hand, self.expected_tricks())?;
+    for tricks in (1..=self.max_tricks()).rev() {
+      writeln!(f, "  {}+ tricks: {:.1}%", tricks, 100.0 * self.probability(tricks))?;
+    }
+    Ok(())
+  }
+}
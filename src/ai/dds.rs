@@ -1,11 +1,58 @@
 use strum::IntoEnumIterator;
+use std::fmt::{Display, Formatter};
 use std::os::raw::{c_int, c_uint};
-use crate::ai::dds_bindings::deal;
-use crate::game_model::cards::{PlayerHand, Rank, Suit};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use enum_map::{enum_map, EnumMap};
+use crate::ai::dds_bindings::{AnalyseAllPlaysBin, AnalysePlayBin, boards, CalcDDtable, ddTableDeal, ddTableResults, deal, futureTricks,
+                              MAXNOOFBOARDS, playTraceBin, playTracesBin, RETURN_NO_FAULT, SetResources, solvedPlay, solvedPlays,
+                              SolveBoard};
+use crate::game_model::cards::{Card, Rank, Suit};
 use crate::game_model::{Board, Seat, Vulnerability};
 use crate::game_model::bidding::Strain;
 use crate::game_model::play::Play;
 
+/** An error code returned by DDS.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DdsError(pub c_int);
+
+impl Display for DdsError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "DDS error {}", self.0)
+  }
+}
+
+/** The memory and threads DDS was last given, if it has been given any. Every call into DDS holds
+    this lock: DDS shares its thread pool and transposition tables between calls, so two threads
+    must never be inside it at once.
+ */
+static DDS: Mutex<Option<(usize, usize)>> = Mutex::new(None);
+
+/** Waits until no other thread is calling DDS. */
+pub(crate) fn lock_dds() -> MutexGuard<'static, Option<(usize, usize)>> {
+  DDS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/** Waits until no other thread is calling DDS, then gives DDS the memory, in megabytes, and the
+    threads to use if they differ from what it was last given. Zero lets DDS decide either.
+ */
+pub(crate) fn lock_dds_with(memory: usize, threads: usize) -> MutexGuard<'static, Option<(usize, usize)>> {
+  let mut resources = lock_dds();
+  if *resources != Some((memory, threads)) {
+    unsafe { SetResources(memory as c_int, threads as c_int) };
+    *resources = Some((memory, threads));
+  }
+  resources
+}
+
+pub fn dds_result(code: c_int) -> Result<(), DdsError> {
+  if code == RETURN_NO_FAULT as c_int {
+    Ok(())
+  } else {
+    Err(DdsError(code))
+  }
+}
+
 pub fn dds_hand(seat: Seat) -> c_int {
   match seat {
     Seat::North => 0,
@@ -62,10 +109,10 @@ pub fn dds_remain_cards(board: &Board) -> [[c_uint; 4usize]; 4usize] {
   let mut result = [[0u32; 4]; 4];
   for seat in Seat::iter() {
     let sorted = board.player_hand(seat).sort();
-    let mut result_hand = &mut result[dds_hand(seat) as usize];
-    for (suit, &ranks) in sorted.iter() {
-      let mut result_suit = &mut result_hand[dds_suit(suit) as usize];
-      for rank in ranks {
+    let result_hand = &mut result[dds_hand(seat) as usize];
+    for (suit, ranks) in sorted.iter() {
+      let result_suit = &mut result_hand[dds_suit(suit) as usize];
+      for &rank in ranks {
         *result_suit |= 1 << dds_rank(rank);
       }
     }
@@ -81,24 +128,168 @@ pub fn dds_deal(play: &Play, board: &Board) -> deal {
       t.winner(strain).unwrap_or(t.leader())
     })
   };
-  let (current_suit, current_rank) = play.tricks().last().map_or(([0; 3], [0; 3]), |t| {
+  let current_trick = play.tricks().last().filter(|t| t.winner(strain).is_none());
+  let (current_suit, current_rank) = current_trick.map_or(([0; 3], [0; 3]), |t| {
     let mut l = leader;
     let mut suits = [0; 3];
     let mut ranks = [0; 3];
-    while l.next_seat() != leader {
+    for idx in 0..3 {
       if let Some(card) = t[l] {
-        let idx = dds_hand(l) as usize;
         suits[idx] = dds_suit(card.suit());
         ranks[idx] = dds_rank(card.rank());
       }
+      l = l.next_seat();
     }
     (suits, ranks)
   });
+  let mut remain_cards = dds_remain_cards(board);
+  for trick in play.tricks() {
+    for seat in Seat::iter() {
+      if let Some(card) = trick[seat] {
+        let hand = &mut remain_cards[dds_hand(seat) as usize];
+        hand[dds_suit(card.suit()) as usize] &= !(1 << dds_rank(card.rank()));
+      }
+    }
+  }
   deal {
     trump: dds_strain(strain),
     first: dds_hand(leader),
     currentTrickSuit: current_suit,
     currentTrickRank: current_rank,
-    remainCards: dds_remain_cards(board),
+    remainCards: remain_cards,
+  }
+}
+
+pub fn empty_future_tricks() -> futureTricks {
+  futureTricks {
+    nodes: 0,
+    cards: 0,
+    suit: [0; 13],
+    rank: [0; 13],
+    equals: [0; 13],
+    score: [0; 13],
+  }
+}
+
+/** Solves the current position of a play double dummy. Returns the most tricks that the side due
+    to play next can take from the remaining tricks.
+ */
+pub fn double_dummy_tricks(play: &Play, board: &Board) -> Result<u8, DdsError> {
+  let mut fut = empty_future_tricks();
+  let _dds = lock_dds();
+  dds_result(unsafe { SolveBoard(dds_deal(play, board), -1, 1, 1, &mut fut, 0) })?;
+  Ok(fut.score[0] as u8)
+}
+
+fn suit_from_dds(suit: c_int) -> Suit {
+  Suit::iter().find(|&s| dds_suit(s) == suit).expect("DDS suits are 0 to 3")
+}
+
+fn rank_from_dds(rank: c_int) -> Rank {
+  Rank::iter().find(|&r| dds_rank(r) == rank).expect("DDS ranks are 2 to 14")
+}
+
+/** Solves every legal card of the side due to play next double dummy. Returns each card with the
+    most tricks the side can take from the remaining tricks after playing it. Cards that DDS treats
+    as equals are listed separately with the same score.
+ */
+pub fn double_dummy_plays(play: &Play, board: &Board) -> Result<Vec<(Card, u8)>, DdsError> {
+  let mut fut = empty_future_tricks();
+  let _dds = lock_dds();
+  dds_result(unsafe { SolveBoard(dds_deal(play, board), -1, 3, 1, &mut fut, 0) })?;
+  Ok(plays_from_future(&fut))
+}
+
+/** Lists the cards of a solution found with `solutions` set to 3, equals included. */
+pub(crate) fn plays_from_future(fut: &futureTricks) -> Vec<(Card, u8)> {
+  let mut plays = Vec::new();
+  for i in 0..fut.cards as usize {
+    let suit = suit_from_dds(fut.suit[i]);
+    let tricks = fut.score[i] as u8;
+    plays.push((Card::from(suit, rank_from_dds(fut.rank[i])), tricks));
+    for rank in Rank::iter().filter(|&rank| fut.equals[i] & (1 << dds_rank(rank)) != 0) {
+      plays.push((Card::from(suit, rank), tricks));
+    }
+  }
+  plays
+}
+
+/** The double-dummy tricks of a deal: for each strain, the tricks each seat takes as declarer.
+ */
+pub type DoubleDummyTable = EnumMap<Strain, EnumMap<Seat, u8>>;
+
+/** Solves a whole deal double dummy for every strain and declarer.
+ */
+pub fn double_dummy_table(board: &Board) -> Result<DoubleDummyTable, DdsError> {
+  let mut results = ddTableResults { resTable: [[0; 4]; 5] };
+  let table_deal = ddTableDeal { cards: dds_remain_cards(board) };
+  let _dds = lock_dds();
+  dds_result(unsafe { CalcDDtable(table_deal, &mut results) })?;
+  Ok(table_from_results(&results))
+}
+
+pub(crate) fn table_from_results(results: &ddTableResults) -> DoubleDummyTable {
+  enum_map! {
+    strain => enum_map! {
+      seat => results.resTable[dds_strain(strain) as usize][dds_hand(seat) as usize] as u8
+    }
+  }
+}
+
+fn play_trace(play: &Play) -> playTraceBin {
+  let mut trace = playTraceBin { number: 0, suit: [0; 52], rank: [0; 52] };
+  for (i, (_, card)) in play.tricks().iter().flat_map(|trick| trick.cards_in_order()).enumerate() {
+    trace.suit[i] = dds_suit(card.suit());
+    trace.rank[i] = dds_rank(card.rank());
+    trace.number = i as c_int + 1;
+  }
+  trace
+}
+
+fn trace_tricks(solved: &solvedPlay) -> Vec<u8> {
+  solved.tricks[..solved.number as usize].iter().map(|&tricks| tricks as u8).collect()
+}
+
+/** Replays a play double dummy. Returns the tricks declarer can take in the whole hand before the
+    first card and after each card played.
+ */
+pub fn double_dummy_trace(play: &Play, board: &Board) -> Result<Vec<u8>, DdsError> {
+  let mut solved = solvedPlay { number: 0, tricks: [0; 53] };
+  let start = dds_deal(&Play::new(play.contract()), board);
+  let _dds = lock_dds();
+  dds_result(unsafe { AnalysePlayBin(start, play_trace(play), &mut solved, 0) })?;
+  Ok(trace_tricks(&solved))
+}
+
+/** Replays many plays double dummy, as `double_dummy_trace` does for one, letting DDS spread them
+    over its threads.
+ */
+pub fn double_dummy_traces(plays: &[(&Play, &Board)]) -> Result<Vec<Vec<u8>>, DdsError> {
+  let mut traces = Vec::with_capacity(plays.len());
+  for chunk in plays.chunks(MAXNOOFBOARDS as usize) {
+    let empty_deal = dds_deal(&Play::new(chunk[0].0.contract()), chunk[0].1);
+    let mut bop = boards {
+      noOfBoards: chunk.len() as c_int,
+      deals: [empty_deal; MAXNOOFBOARDS as usize],
+      target: [-1; MAXNOOFBOARDS as usize],
+      solutions: [1; MAXNOOFBOARDS as usize],
+      mode: [1; MAXNOOFBOARDS as usize],
+    };
+    let mut plp = playTracesBin {
+      noOfBoards: chunk.len() as c_int,
+      plays: [playTraceBin { number: 0, suit: [0; 52], rank: [0; 52] }; MAXNOOFBOARDS as usize],
+    };
+    for (i, &(play, board)) in chunk.iter().enumerate() {
+      bop.deals[i] = dds_deal(&Play::new(play.contract()), board);
+      plp.plays[i] = play_trace(play);
+    }
+    let mut solved = solvedPlays {
+      noOfBoards: 0,
+      solved: [solvedPlay { number: 0, tricks: [0; 53] }; MAXNOOFBOARDS as usize],
+    };
+    let _dds = lock_dds();
+    dds_result(unsafe { AnalyseAllPlaysBin(&mut bop, &mut plp, &mut solved, 1) })?;
+    traces.extend(solved.solved[..chunk.len()].iter().map(trace_tricks));
   }
+  Ok(traces)
 }
@@ -92,7 +92,7 @@ fn bindgen_test_layout_futureTricks() {
         concat!("Alignment of ", stringify!(futureTricks))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<futureTricks>())).nodes as *const _ as usize },
+        ::std::mem::offset_of!(futureTricks, nodes),
         0usize,
         concat!(
             "Offset of field: ",
@@ -102,7 +102,7 @@ fn bindgen_test_layout_futureTricks() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<futureTricks>())).cards as *const _ as usize },
+        ::std::mem::offset_of!(futureTricks, cards),
         4usize,
         concat!(
             "Offset of field: ",
@@ -112,7 +112,7 @@ fn bindgen_test_layout_futureTricks() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<futureTricks>())).suit as *const _ as usize },
+        ::std::mem::offset_of!(futureTricks, suit),
         8usize,
         concat!(
             "Offset of field: ",
@@ -122,7 +122,7 @@ fn bindgen_test_layout_futureTricks() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<futureTricks>())).rank as *const _ as usize },
+        ::std::mem::offset_of!(futureTricks, rank),
         60usize,
         concat!(
             "Offset of field: ",
@@ -132,7 +132,7 @@ fn bindgen_test_layout_futureTricks() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<futureTricks>())).equals as *const _ as usize },
+        ::std::mem::offset_of!(futureTricks, equals),
         112usize,
         concat!(
             "Offset of field: ",
@@ -142,7 +142,7 @@ fn bindgen_test_layout_futureTricks() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<futureTricks>())).score as *const _ as usize },
+        ::std::mem::offset_of!(futureTricks, score),
         164usize,
         concat!(
             "Offset of field: ",
@@ -174,7 +174,7 @@ fn bindgen_test_layout_deal() {
         concat!("Alignment of ", stringify!(deal))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<deal>())).trump as *const _ as usize },
+        ::std::mem::offset_of!(deal, trump),
         0usize,
         concat!(
             "Offset of field: ",
@@ -184,7 +184,7 @@ fn bindgen_test_layout_deal() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<deal>())).first as *const _ as usize },
+        ::std::mem::offset_of!(deal, first),
         4usize,
         concat!(
             "Offset of field: ",
@@ -194,7 +194,7 @@ fn bindgen_test_layout_deal() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<deal>())).currentTrickSuit as *const _ as usize },
+        ::std::mem::offset_of!(deal, currentTrickSuit),
         8usize,
         concat!(
             "Offset of field: ",
@@ -204,7 +204,7 @@ fn bindgen_test_layout_deal() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<deal>())).currentTrickRank as *const _ as usize },
+        ::std::mem::offset_of!(deal, currentTrickRank),
         20usize,
         concat!(
             "Offset of field: ",
@@ -214,7 +214,7 @@ fn bindgen_test_layout_deal() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<deal>())).remainCards as *const _ as usize },
+        ::std::mem::offset_of!(deal, remainCards),
         32usize,
         concat!(
             "Offset of field: ",
@@ -246,7 +246,7 @@ fn bindgen_test_layout_dealPBN() {
         concat!("Alignment of ", stringify!(dealPBN))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<dealPBN>())).trump as *const _ as usize },
+        ::std::mem::offset_of!(dealPBN, trump),
         0usize,
         concat!(
             "Offset of field: ",
@@ -256,7 +256,7 @@ fn bindgen_test_layout_dealPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<dealPBN>())).first as *const _ as usize },
+        ::std::mem::offset_of!(dealPBN, first),
         4usize,
         concat!(
             "Offset of field: ",
@@ -266,7 +266,7 @@ fn bindgen_test_layout_dealPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<dealPBN>())).currentTrickSuit as *const _ as usize },
+        ::std::mem::offset_of!(dealPBN, currentTrickSuit),
         8usize,
         concat!(
             "Offset of field: ",
@@ -276,7 +276,7 @@ fn bindgen_test_layout_dealPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<dealPBN>())).currentTrickRank as *const _ as usize },
+        ::std::mem::offset_of!(dealPBN, currentTrickRank),
         20usize,
         concat!(
             "Offset of field: ",
@@ -286,7 +286,7 @@ fn bindgen_test_layout_dealPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<dealPBN>())).remainCards as *const _ as usize },
+        ::std::mem::offset_of!(dealPBN, remainCards),
         32usize,
         concat!(
             "Offset of field: ",
@@ -318,7 +318,7 @@ fn bindgen_test_layout_boards() {
         concat!("Alignment of ", stringify!(boards))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boards>())).noOfBoards as *const _ as usize },
+        ::std::mem::offset_of!(boards, noOfBoards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -328,7 +328,7 @@ fn bindgen_test_layout_boards() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boards>())).deals as *const _ as usize },
+        ::std::mem::offset_of!(boards, deals),
         4usize,
         concat!(
             "Offset of field: ",
@@ -338,7 +338,7 @@ fn bindgen_test_layout_boards() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boards>())).target as *const _ as usize },
+        ::std::mem::offset_of!(boards, target),
         19204usize,
         concat!(
             "Offset of field: ",
@@ -348,7 +348,7 @@ fn bindgen_test_layout_boards() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boards>())).solutions as *const _ as usize },
+        ::std::mem::offset_of!(boards, solutions),
         20004usize,
         concat!(
             "Offset of field: ",
@@ -358,7 +358,7 @@ fn bindgen_test_layout_boards() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boards>())).mode as *const _ as usize },
+        ::std::mem::offset_of!(boards, mode),
         20804usize,
         concat!(
             "Offset of field: ",
@@ -390,7 +390,7 @@ fn bindgen_test_layout_boardsPBN() {
         concat!("Alignment of ", stringify!(boardsPBN))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boardsPBN>())).noOfBoards as *const _ as usize },
+        ::std::mem::offset_of!(boardsPBN, noOfBoards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -400,7 +400,7 @@ fn bindgen_test_layout_boardsPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boardsPBN>())).deals as *const _ as usize },
+        ::std::mem::offset_of!(boardsPBN, deals),
         4usize,
         concat!(
             "Offset of field: ",
@@ -410,7 +410,7 @@ fn bindgen_test_layout_boardsPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boardsPBN>())).target as *const _ as usize },
+        ::std::mem::offset_of!(boardsPBN, target),
         22404usize,
         concat!(
             "Offset of field: ",
@@ -420,7 +420,7 @@ fn bindgen_test_layout_boardsPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boardsPBN>())).solutions as *const _ as usize },
+        ::std::mem::offset_of!(boardsPBN, solutions),
         23204usize,
         concat!(
             "Offset of field: ",
@@ -430,7 +430,7 @@ fn bindgen_test_layout_boardsPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<boardsPBN>())).mode as *const _ as usize },
+        ::std::mem::offset_of!(boardsPBN, mode),
         24004usize,
         concat!(
             "Offset of field: ",
@@ -459,7 +459,7 @@ fn bindgen_test_layout_solvedBoards() {
         concat!("Alignment of ", stringify!(solvedBoards))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<solvedBoards>())).noOfBoards as *const _ as usize },
+        ::std::mem::offset_of!(solvedBoards, noOfBoards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -469,7 +469,7 @@ fn bindgen_test_layout_solvedBoards() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<solvedBoards>())).solvedBoard as *const _ as usize },
+        ::std::mem::offset_of!(solvedBoards, solvedBoard),
         4usize,
         concat!(
             "Offset of field: ",
@@ -497,7 +497,7 @@ fn bindgen_test_layout_ddTableDeal() {
         concat!("Alignment of ", stringify!(ddTableDeal))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTableDeal>())).cards as *const _ as usize },
+        ::std::mem::offset_of!(ddTableDeal, cards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -526,7 +526,7 @@ fn bindgen_test_layout_ddTableDeals() {
         concat!("Alignment of ", stringify!(ddTableDeals))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTableDeals>())).noOfTables as *const _ as usize },
+        ::std::mem::offset_of!(ddTableDeals, noOfTables),
         0usize,
         concat!(
             "Offset of field: ",
@@ -536,7 +536,7 @@ fn bindgen_test_layout_ddTableDeals() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTableDeals>())).deals as *const _ as usize },
+        ::std::mem::offset_of!(ddTableDeals, deals),
         4usize,
         concat!(
             "Offset of field: ",
@@ -564,7 +564,7 @@ fn bindgen_test_layout_ddTableDealPBN() {
         concat!("Alignment of ", stringify!(ddTableDealPBN))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTableDealPBN>())).cards as *const _ as usize },
+        ::std::mem::offset_of!(ddTableDealPBN, cards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -593,7 +593,7 @@ fn bindgen_test_layout_ddTableDealsPBN() {
         concat!("Alignment of ", stringify!(ddTableDealsPBN))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTableDealsPBN>())).noOfTables as *const _ as usize },
+        ::std::mem::offset_of!(ddTableDealsPBN, noOfTables),
         0usize,
         concat!(
             "Offset of field: ",
@@ -603,7 +603,7 @@ fn bindgen_test_layout_ddTableDealsPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTableDealsPBN>())).deals as *const _ as usize },
+        ::std::mem::offset_of!(ddTableDealsPBN, deals),
         4usize,
         concat!(
             "Offset of field: ",
@@ -631,7 +631,7 @@ fn bindgen_test_layout_ddTableResults() {
         concat!("Alignment of ", stringify!(ddTableResults))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTableResults>())).resTable as *const _ as usize },
+        ::std::mem::offset_of!(ddTableResults, resTable),
         0usize,
         concat!(
             "Offset of field: ",
@@ -660,7 +660,7 @@ fn bindgen_test_layout_ddTablesRes() {
         concat!("Alignment of ", stringify!(ddTablesRes))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTablesRes>())).noOfBoards as *const _ as usize },
+        ::std::mem::offset_of!(ddTablesRes, noOfBoards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -670,7 +670,7 @@ fn bindgen_test_layout_ddTablesRes() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<ddTablesRes>())).results as *const _ as usize },
+        ::std::mem::offset_of!(ddTablesRes, results),
         4usize,
         concat!(
             "Offset of field: ",
@@ -699,7 +699,7 @@ fn bindgen_test_layout_parResults() {
         concat!("Alignment of ", stringify!(parResults))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResults>())).parScore as *const _ as usize },
+        ::std::mem::offset_of!(parResults, parScore),
         0usize,
         concat!(
             "Offset of field: ",
@@ -709,7 +709,7 @@ fn bindgen_test_layout_parResults() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResults>())).parContractsString as *const _ as usize },
+        ::std::mem::offset_of!(parResults, parContractsString),
         32usize,
         concat!(
             "Offset of field: ",
@@ -737,7 +737,7 @@ fn bindgen_test_layout_allParResults() {
         concat!("Alignment of ", stringify!(allParResults))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<allParResults>())).presults as *const _ as usize },
+        ::std::mem::offset_of!(allParResults, presults),
         0usize,
         concat!(
             "Offset of field: ",
@@ -767,7 +767,7 @@ fn bindgen_test_layout_parResultsDealer() {
         concat!("Alignment of ", stringify!(parResultsDealer))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResultsDealer>())).number as *const _ as usize },
+        ::std::mem::offset_of!(parResultsDealer, number),
         0usize,
         concat!(
             "Offset of field: ",
@@ -777,7 +777,7 @@ fn bindgen_test_layout_parResultsDealer() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResultsDealer>())).score as *const _ as usize },
+        ::std::mem::offset_of!(parResultsDealer, score),
         4usize,
         concat!(
             "Offset of field: ",
@@ -787,7 +787,7 @@ fn bindgen_test_layout_parResultsDealer() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResultsDealer>())).contracts as *const _ as usize },
+        ::std::mem::offset_of!(parResultsDealer, contracts),
         8usize,
         concat!(
             "Offset of field: ",
@@ -819,7 +819,7 @@ fn bindgen_test_layout_contractType() {
         concat!("Alignment of ", stringify!(contractType))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<contractType>())).underTricks as *const _ as usize },
+        ::std::mem::offset_of!(contractType, underTricks),
         0usize,
         concat!(
             "Offset of field: ",
@@ -829,7 +829,7 @@ fn bindgen_test_layout_contractType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<contractType>())).overTricks as *const _ as usize },
+        ::std::mem::offset_of!(contractType, overTricks),
         4usize,
         concat!(
             "Offset of field: ",
@@ -839,7 +839,7 @@ fn bindgen_test_layout_contractType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<contractType>())).level as *const _ as usize },
+        ::std::mem::offset_of!(contractType, level),
         8usize,
         concat!(
             "Offset of field: ",
@@ -849,7 +849,7 @@ fn bindgen_test_layout_contractType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<contractType>())).denom as *const _ as usize },
+        ::std::mem::offset_of!(contractType, denom),
         12usize,
         concat!(
             "Offset of field: ",
@@ -859,7 +859,7 @@ fn bindgen_test_layout_contractType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<contractType>())).seats as *const _ as usize },
+        ::std::mem::offset_of!(contractType, seats),
         16usize,
         concat!(
             "Offset of field: ",
@@ -889,7 +889,7 @@ fn bindgen_test_layout_parResultsMaster() {
         concat!("Alignment of ", stringify!(parResultsMaster))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResultsMaster>())).score as *const _ as usize },
+        ::std::mem::offset_of!(parResultsMaster, score),
         0usize,
         concat!(
             "Offset of field: ",
@@ -899,7 +899,7 @@ fn bindgen_test_layout_parResultsMaster() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResultsMaster>())).number as *const _ as usize },
+        ::std::mem::offset_of!(parResultsMaster, number),
         4usize,
         concat!(
             "Offset of field: ",
@@ -909,7 +909,7 @@ fn bindgen_test_layout_parResultsMaster() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parResultsMaster>())).contracts as *const _ as usize },
+        ::std::mem::offset_of!(parResultsMaster, contracts),
         8usize,
         concat!(
             "Offset of field: ",
@@ -938,7 +938,7 @@ fn bindgen_test_layout_parTextResults() {
         concat!("Alignment of ", stringify!(parTextResults))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parTextResults>())).parText as *const _ as usize },
+        ::std::mem::offset_of!(parTextResults, parText),
         0usize,
         concat!(
             "Offset of field: ",
@@ -948,7 +948,7 @@ fn bindgen_test_layout_parTextResults() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<parTextResults>())).equal as *const _ as usize },
+        ::std::mem::offset_of!(parTextResults, equal),
         256usize,
         concat!(
             "Offset of field: ",
@@ -978,7 +978,7 @@ fn bindgen_test_layout_playTraceBin() {
         concat!("Alignment of ", stringify!(playTraceBin))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTraceBin>())).number as *const _ as usize },
+        ::std::mem::offset_of!(playTraceBin, number),
         0usize,
         concat!(
             "Offset of field: ",
@@ -988,7 +988,7 @@ fn bindgen_test_layout_playTraceBin() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTraceBin>())).suit as *const _ as usize },
+        ::std::mem::offset_of!(playTraceBin, suit),
         4usize,
         concat!(
             "Offset of field: ",
@@ -998,7 +998,7 @@ fn bindgen_test_layout_playTraceBin() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTraceBin>())).rank as *const _ as usize },
+        ::std::mem::offset_of!(playTraceBin, rank),
         212usize,
         concat!(
             "Offset of field: ",
@@ -1027,7 +1027,7 @@ fn bindgen_test_layout_playTracePBN() {
         concat!("Alignment of ", stringify!(playTracePBN))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTracePBN>())).number as *const _ as usize },
+        ::std::mem::offset_of!(playTracePBN, number),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1037,7 +1037,7 @@ fn bindgen_test_layout_playTracePBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTracePBN>())).cards as *const _ as usize },
+        ::std::mem::offset_of!(playTracePBN, cards),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1066,7 +1066,7 @@ fn bindgen_test_layout_solvedPlay() {
         concat!("Alignment of ", stringify!(solvedPlay))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<solvedPlay>())).number as *const _ as usize },
+        ::std::mem::offset_of!(solvedPlay, number),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1076,7 +1076,7 @@ fn bindgen_test_layout_solvedPlay() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<solvedPlay>())).tricks as *const _ as usize },
+        ::std::mem::offset_of!(solvedPlay, tricks),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1105,7 +1105,7 @@ fn bindgen_test_layout_playTracesBin() {
         concat!("Alignment of ", stringify!(playTracesBin))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTracesBin>())).noOfBoards as *const _ as usize },
+        ::std::mem::offset_of!(playTracesBin, noOfBoards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1115,7 +1115,7 @@ fn bindgen_test_layout_playTracesBin() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTracesBin>())).plays as *const _ as usize },
+        ::std::mem::offset_of!(playTracesBin, plays),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1144,7 +1144,7 @@ fn bindgen_test_layout_playTracesPBN() {
         concat!("Alignment of ", stringify!(playTracesPBN))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTracesPBN>())).noOfBoards as *const _ as usize },
+        ::std::mem::offset_of!(playTracesPBN, noOfBoards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1154,7 +1154,7 @@ fn bindgen_test_layout_playTracesPBN() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<playTracesPBN>())).plays as *const _ as usize },
+        ::std::mem::offset_of!(playTracesPBN, plays),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1183,7 +1183,7 @@ fn bindgen_test_layout_solvedPlays() {
         concat!("Alignment of ", stringify!(solvedPlays))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<solvedPlays>())).noOfBoards as *const _ as usize },
+        ::std::mem::offset_of!(solvedPlays, noOfBoards),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1193,7 +1193,7 @@ fn bindgen_test_layout_solvedPlays() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<solvedPlays>())).solved as *const _ as usize },
+        ::std::mem::offset_of!(solvedPlays, solved),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1233,7 +1233,7 @@ fn bindgen_test_layout_DDSInfo() {
         concat!("Alignment of ", stringify!(DDSInfo))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).major as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, major),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1243,7 +1243,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).minor as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, minor),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1253,7 +1253,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).patch as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, patch),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1263,7 +1263,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).versionString as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, versionString),
         12usize,
         concat!(
             "Offset of field: ",
@@ -1273,7 +1273,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).system as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, system),
         24usize,
         concat!(
             "Offset of field: ",
@@ -1283,7 +1283,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).numBits as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, numBits),
         28usize,
         concat!(
             "Offset of field: ",
@@ -1293,7 +1293,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).compiler as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, compiler),
         32usize,
         concat!(
             "Offset of field: ",
@@ -1303,7 +1303,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).constructor as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, constructor),
         36usize,
         concat!(
             "Offset of field: ",
@@ -1313,7 +1313,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).numCores as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, numCores),
         40usize,
         concat!(
             "Offset of field: ",
@@ -1323,7 +1323,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).threading as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, threading),
         44usize,
         concat!(
             "Offset of field: ",
@@ -1333,7 +1333,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).noOfThreads as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, noOfThreads),
         48usize,
         concat!(
             "Offset of field: ",
@@ -1343,7 +1343,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).threadSizes as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, threadSizes),
         52usize,
         concat!(
             "Offset of field: ",
@@ -1353,7 +1353,7 @@ fn bindgen_test_layout_DDSInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<DDSInfo>())).systemString as *const _ as usize },
+        ::std::mem::offset_of!(DDSInfo, systemString),
         180usize,
         concat!(
             "Offset of field: ",
@@ -0,0 +1,509 @@
+use std::fmt::{Display, Formatter};
+use strum::IntoEnumIterator;
+use crate::ai::trace::{DecisionTrace, PlayTrace};
+use crate::game_model::{Board, Vulnerability};
+use crate::game_model::bidding::{Auction, Call, CallAnnotation, Strain};
+use crate::game_model::cards::{Card, PlayerHand, Rank, Suit};
+use crate::game_model::play::{Claim, Play};
+use crate::interface::{Deadline, Player};
+
+/** One of the two hands of the declaring side. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OurHand {
+  Declarer,
+  Dummy,
+}
+
+/** A defender, named from declarer's point of view. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Defender {
+  Lho,
+  Rho,
+}
+
+impl OurHand {
+  pub fn other(self) -> Self {
+    match self {
+      OurHand::Declarer => OurHand::Dummy,
+      OurHand::Dummy => OurHand::Declarer,
+    }
+  }
+
+  /** The defender who plays just before this hand when the other hand leads. */
+  pub fn right(self) -> Defender {
+    match self {
+      OurHand::Declarer => Defender::Rho,
+      OurHand::Dummy => Defender::Lho,
+    }
+  }
+
+  /** The defender who plays just after this hand when it leads. */
+  pub fn left(self) -> Defender {
+    match self {
+      OurHand::Declarer => Defender::Lho,
+      OurHand::Dummy => Defender::Rho,
+    }
+  }
+}
+
+impl Display for OurHand {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OurHand::Declarer => write!(f, "hand"),
+      OurHand::Dummy => write!(f, "dummy"),
+    }
+  }
+}
+
+fn binomial(n: u32, k: u32) -> f64 {
+  if k > n {
+    return 0.0;
+  }
+  (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/** The cards of one suit held by declarer and dummy, each highest first, along with the cards of the
+    suit that have already been played. Every other card of the suit is missing.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SuitCombination {
+  declarer: Vec<Rank>,
+  dummy: Vec<Rank>,
+  played: Vec<Rank>,
+}
+
+impl SuitCombination {
+  /** Returns `None` if a card is given twice. */
+  pub fn new(declarer: &[Rank], dummy: &[Rank]) -> Option<Self> {
+    let mut combination = SuitCombination { declarer: declarer.to_vec(), dummy: dummy.to_vec(), played: Vec::new() };
+    combination.declarer.sort_by(|a, b| b.cmp(a));
+    combination.dummy.sort_by(|a, b| b.cmp(a));
+    let mut all: Vec<Rank> = combination.declarer.iter().chain(&combination.dummy).copied().collect();
+    all.sort();
+    all.dedup();
+    (all.len() == declarer.len() + dummy.len()).then_some(combination)
+  }
+
+  /** Reads a combination such as `"AQ32"` and `"654"`, with `"-"` for a void. */
+  pub fn parse(declarer: &str, dummy: &str) -> Option<Self> {
+    let ranks = |text: &str| -> Option<Vec<Rank>> {
+      text.chars().filter(|&c| c != '-').map(|c| Rank::try_from(c).ok()).collect()
+    };
+    SuitCombination::new(&ranks(declarer)?, &ranks(dummy)?)
+  }
+
+  /** The combination in `suit` at the current point of a play. */
+  pub fn from_play(play: &Play, suit: Suit, declarer: &PlayerHand, dummy: &PlayerHand) -> Self {
+    let played: Vec<Card> = play.tricks().iter().flat_map(|trick| trick.cards_in_order()).map(|(_, card)| card).collect();
+    let remaining = |hand: &PlayerHand| -> Vec<Rank> {
+      hand.cards().iter().filter(|&&card| card.suit() == suit && !played.contains(&card)).map(Card::rank).collect()
+    };
+    let mut combination = SuitCombination::new(&remaining(declarer), &remaining(dummy)).expect("hands share no cards");
+    combination.played = played.iter().filter(|card| card.suit() == suit).map(Card::rank).collect();
+    combination
+  }
+
+  pub fn hand(&self, hand: OurHand) -> &[Rank] {
+    match hand {
+      OurHand::Declarer => &self.declarer,
+      OurHand::Dummy => &self.dummy,
+    }
+  }
+
+  /** The cards of the suit already played, which neither side holds. */
+  pub fn played(&self) -> &[Rank] {
+    &self.played
+  }
+
+  /** The cards of the suit the defenders may hold, highest first. */
+  pub fn missing(&self) -> Vec<Rank> {
+    Rank::iter()
+        .rev()
+        .filter(|rank| !self.declarer.contains(rank) && !self.dummy.contains(rank) && !self.played.contains(rank))
+        .collect()
+  }
+
+  fn holder(&self, rank: Rank) -> Option<OurHand> {
+    if self.declarer.contains(&rank) {
+      Some(OurHand::Declarer)
+    } else if self.dummy.contains(&rank) {
+      Some(OurHand::Dummy)
+    } else {
+      None
+    }
+  }
+
+  /** Our cards that rank above `rank`. */
+  fn above(&self, rank: Rank) -> usize {
+    self.declarer.iter().chain(&self.dummy).filter(|&&r| r > rank).count()
+  }
+
+  /** Our highest card below `rank`, and whether the next of ours below it follows with no missing
+      card between them.
+   */
+  fn below(&self, rank: Rank) -> Option<(Rank, Option<Rank>)> {
+    let missing = self.missing();
+    let mut ours: Vec<Rank> = self.declarer.iter().chain(&self.dummy).copied().filter(|&r| r < rank).collect();
+    ours.sort_by(|a, b| b.cmp(a));
+    let first = *ours.first()?;
+    let touching = ours.get(1).copied().filter(|&next| !missing.iter().any(|&m| m < first && m > next));
+    Some((first, touching))
+  }
+
+  /** Our cards above the second highest missing card, which win tricks once the highest has been
+      finessed.
+   */
+  fn capturing(&self) -> usize {
+    match self.missing().get(1) {
+      Some(&second) => self.above(second),
+      None => self.declarer.len() + self.dummy.len(),
+    }
+  }
+
+  /** The tricks that can be cashed from the top without losing the lead. */
+  pub fn sure_tricks(&self) -> u8 {
+    let top = self.missing().first().copied();
+    let winners = top.map_or(self.declarer.len() + self.dummy.len(), |top| self.above(top));
+    winners.min(self.declarer.len().max(self.dummy.len())) as u8
+  }
+}
+
+/** How many unknown cards each defender holds, which sets the odds of the layouts of a suit. At the
+    start of play both hold thirteen.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VacantPlaces {
+  pub lho: u8,
+  pub rho: u8,
+}
+
+impl Default for VacantPlaces {
+  fn default() -> Self {
+    VacantPlaces { lho: 13, rho: 13 }
+  }
+}
+
+/** One way the missing cards of a suit can lie between the defenders. */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Layout {
+  pub lho: Vec<Rank>,
+  pub rho: Vec<Rank>,
+}
+
+impl Layout {
+  pub fn cards(&self, defender: Defender) -> &[Rank] {
+    match defender {
+      Defender::Lho => &self.lho,
+      Defender::Rho => &self.rho,
+    }
+  }
+
+  pub fn holder(&self, rank: Rank) -> Option<Defender> {
+    if self.lho.contains(&rank) {
+      Some(Defender::Lho)
+    } else if self.rho.contains(&rank) {
+      Some(Defender::Rho)
+    } else {
+      None
+    }
+  }
+}
+
+/** Every layout of the missing cards with its a-priori probability. A particular layout with `k`
+    of the `n` missing cards on the left is as likely as the other cards are to fill the remaining
+    vacant places.
+ */
+pub fn layouts(missing: &[Rank], vacant: VacantPlaces) -> Vec<(Layout, f64)> {
+  let n = missing.len() as u32;
+  let (lho, rho) = (vacant.lho as u32, vacant.rho as u32);
+  let total = binomial(lho + rho, lho);
+  (0u32..1 << n).filter_map(|mask| {
+    let k = mask.count_ones();
+    if k > lho || n - k > rho {
+      return None;
+    }
+    let on_left = |i: usize| mask & (1 << i) != 0;
+    let layout = Layout {
+      lho: missing.iter().enumerate().filter(|&(i, _)| on_left(i)).map(|(_, &rank)| rank).collect(),
+      rho: missing.iter().enumerate().filter(|&(i, _)| !on_left(i)).map(|(_, &rank)| rank).collect(),
+    };
+    Some((layout, binomial(lho + rho - n, lho - k) / total))
+  }).collect()
+}
+
+/** Whether the missing card outlasts the `rounds` rounds in which we play our top cards and then
+    wins a trick that our next card would otherwise have won. The defenders follow with their lowest
+    cards meanwhile.
+ */
+fn costs_trick(combination: &SuitCombination, layout: &Layout, against: Rank, rounds: usize) -> bool {
+  let longest = combination.declarer.len().max(combination.dummy.len());
+  let holder = match layout.holder(against) {
+    Some(holder) => layout.cards(holder),
+    None => return false,
+  };
+  if holder.len().min(longest) <= rounds {
+    return false;
+  }
+  let mut ours: Vec<Rank> = combination.declarer.iter().chain(&combination.dummy).copied().collect();
+  ours.sort_by(|a, b| b.cmp(a));
+  let next = match ours.get(rounds) {
+    Some(&next) => next,
+    None => return false,
+  };
+  let outstanding = |cards: &[Rank]| cards[..cards.len().saturating_sub(rounds)].to_vec();
+  let mut left = outstanding(&layout.lho);
+  left.extend(outstanding(&layout.rho));
+  !left.iter().any(|&rank| rank != against && rank > next)
+}
+
+/** A standard way of playing a suit to avoid losing a trick to the highest missing card. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Technique {
+  /** Cash the top cards and hope the missing card falls under them. */
+  Drop(Rank),
+  /** Lead towards the hand holding the tenace, playing its lower card if the missing card does not
+      appear.
+   */
+  Finesse { against: Rank, tenace: OurHand },
+  /** The safety play of cashing a top card before finessing, which also wins against a singleton
+      honour on the wrong side.
+   */
+  SafetyFinesse { against: Rank, tenace: OurHand },
+  /** Lead a card of a sequence through the missing card and ruff if it covers, with the other hand
+      void.
+   */
+  RuffingFinesse { against: Rank, sequence: OurHand },
+}
+
+impl Display for Technique {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Technique::Drop(rank) => write!(f, "play for the drop of the {}", rank),
+      Technique::Finesse { against, tenace } => write!(f, "finesse against the {} into {}", against, tenace),
+      Technique::SafetyFinesse { against, tenace } => {
+        write!(f, "cash a top card, then finesse against the {} into {}", against, tenace)
+      }
+      Technique::RuffingFinesse { against, sequence } => {
+        write!(f, "ruffing finesse against the {} from {}", against, sequence)
+      }
+    }
+  }
+}
+
+impl Technique {
+  /** Returns whether the technique avoids losing a trick to the missing card in a layout. A trick the
+      missing card takes after we have run out of winners does not count against the line.
+   */
+  pub fn succeeds(&self, combination: &SuitCombination, layout: &Layout) -> bool {
+    let singleton = |against: Rank| layout.holder(against).is_some_and(|defender| layout.cards(defender).len() == 1);
+    let onside = |against: Rank, tenace: OurHand| layout.holder(against) == Some(tenace.right());
+    match *self {
+      Technique::Drop(against) => !costs_trick(combination, layout, against, combination.above(against)),
+      Technique::Finesse { against, tenace } => {
+        onside(against, tenace) && !costs_trick(combination, layout, against, combination.capturing())
+      }
+      Technique::SafetyFinesse { against, tenace } => {
+        singleton(against) || (onside(against, tenace) && !costs_trick(combination, layout, against, combination.capturing()))
+      }
+      Technique::RuffingFinesse { against, sequence } => {
+        let cash_first = combination.hand(sequence).first().is_some_and(|&top| top > against);
+        layout.holder(against) == Some(sequence.left()) || (cash_first && singleton(against))
+      }
+    }
+  }
+
+  /** The card to start the technique with from the hand on lead, or `None` if it must be started
+      from the other hand.
+   */
+  pub fn first_card(&self, combination: &SuitCombination, on_lead: OurHand) -> Option<Rank> {
+    let cards = combination.hand(on_lead);
+    match *self {
+      Technique::Drop(against) => cards.first().copied().filter(|&top| top > against).or(cards.last().copied()),
+      Technique::Finesse { against, tenace } if on_lead == tenace.other() => {
+        let (next, _) = combination.below(against)?;
+        if combination.holder(next) == Some(on_lead) { Some(next) } else { cards.last().copied() }
+      }
+      Technique::SafetyFinesse { against, tenace } => {
+        let top = cards.first().copied().filter(|&top| top > against)?;
+        let keeps_tenace = on_lead != tenace || cards.iter().filter(|&&rank| rank > against).count() >= 2;
+        keeps_tenace.then_some(top)
+      }
+      Technique::RuffingFinesse { against, sequence } if on_lead == sequence => {
+        combination.below(against).map(|(next, _)| next)
+      }
+      _ => None,
+    }
+  }
+}
+
+/** The techniques that apply to a combination. Ruffing finesses need a trump contract in another
+    suit, which `ruffing` says.
+ */
+pub fn techniques(combination: &SuitCombination, ruffing: bool) -> Vec<Technique> {
+  let mut found = Vec::new();
+  let against = match combination.missing().first() {
+    Some(&against) => against,
+    None => return found,
+  };
+  let tops = combination.above(against);
+  if tops >= 1 {
+    found.push(Technique::Drop(against));
+  }
+  let below = combination.below(against);
+  for tenace in [OurHand::Declarer, OurHand::Dummy] {
+    let lead = tenace.other();
+    let has_top = combination.hand(tenace).first().is_some_and(|&top| top > against);
+    let finesse = match below {
+      Some((next, _)) if combination.holder(next) == Some(tenace) => !combination.hand(lead).is_empty(),
+      Some((next, touching)) => {
+        combination.holder(next) == Some(lead) && touching.is_some_and(|t| combination.holder(t) == Some(lead))
+      }
+      None => false,
+    };
+    if has_top && finesse {
+      found.push(Technique::Finesse { against, tenace });
+      if tops >= 2 {
+        found.push(Technique::SafetyFinesse { against, tenace });
+      }
+    }
+  }
+  if ruffing {
+    for sequence in [OurHand::Declarer, OurHand::Dummy] {
+      let sequence_held = match below {
+        Some((next, Some(touching))) => {
+          combination.holder(next) == Some(sequence) && combination.holder(touching) == Some(sequence)
+        }
+        _ => false,
+      };
+      if sequence_held && combination.hand(sequence.other()).is_empty() {
+        found.push(Technique::RuffingFinesse { against, sequence });
+      }
+    }
+  }
+  found
+}
+
+/** A technique with its chance of success. */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Line {
+  pub technique: Technique,
+  pub probability: f64,
+}
+
+/** The techniques that apply to a combination with their a-priori chances of success, best first.
+ */
+pub fn lines(combination: &SuitCombination, ruffing: bool, vacant: VacantPlaces) -> Vec<Line> {
+  let layouts = layouts(&combination.missing(), vacant);
+  let mut lines: Vec<Line> = techniques(combination, ruffing).into_iter().map(|technique| Line {
+    technique,
+    probability: layouts.iter()
+        .filter(|(layout, _)| technique.succeeds(combination, layout))
+        .map(|(_, p)| p)
+        .sum(),
+  }).collect();
+  lines.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+  lines
+}
+
+fn remaining(hand: &PlayerHand, play: &Play) -> PlayerHand {
+  let mut hand = hand.clone();
+  for (_, card) in play.tricks().iter().flat_map(|trick| trick.cards_in_order()) {
+    hand.take_card(card);
+  }
+  hand
+}
+
+/** The lead the techniques suggest for the declaring side when it is on lead, with the chance of
+    the best line it starts. The suit whose best line is most likely to succeed is chosen.
+ */
+pub fn suggested_lead(play: &Play, declarer: &PlayerHand, dummy: &PlayerHand) -> Option<(Card, f64)> {
+  let seat = play.next_player();
+  if seat.is_opponent(play.declarer()) || play.tricks().last().is_some_and(|trick| trick.card_count() < 4) {
+    return None;
+  }
+  let on_lead = if seat == play.declarer() { OurHand::Declarer } else { OurHand::Dummy };
+  let played_by = |seat| play.tricks().iter().filter(|trick| trick[seat].is_some()).count() as u8;
+  let vacant = VacantPlaces {
+    lho: 13 - played_by(play.declarer().next_seat()),
+    rho: 13 - played_by(play.declarer().prev_seat()),
+  };
+  let strain = play.contract().strain();
+  let (declarer, dummy) = (remaining(declarer, play), remaining(dummy, play));
+  Suit::iter().filter_map(|suit| {
+    let combination = SuitCombination::from_play(play, suit, &declarer, &dummy);
+    let ruffing = matches!(strain, Strain::Trump(trump) if trump != suit);
+    lines(&combination, ruffing, vacant).into_iter().find_map(|line| {
+      let rank = line.technique.first_card(&combination, on_lead)?;
+      Some((Card::from(suit, rank), line.probability))
+    })
+  }).max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/** Orders candidate cards for a search, putting the lead suggested by the techniques first. */
+pub fn order_moves(play: &Play, declarer: &PlayerHand, dummy: &PlayerHand, moves: &mut [Card]) {
+  if let Some((lead, _)) = suggested_lead(play, declarer, dummy) {
+    moves.sort_by_key(|&card| (card != lead, card.suit() != lead.suit()));
+  }
+}
+
+/** A fast declarer that leads by the techniques when its side is on lead and leaves every other
+    decision to another player.
+ */
+pub struct TechniqueDeclarer<P: Player> {
+  inner: P,
+  trace: Option<DecisionTrace>,
+}
+
+impl<P: Player> Player for TechniqueDeclarer<P> {
+  fn new(hand: &PlayerHand) -> Self {
+    TechniqueDeclarer { inner: P::new(hand), trace: None }
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    self.inner.get_call(auction)
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: Deadline) -> Card {
+    let declarer = board.player_hand(play.declarer());
+    let dummy = board.player_hand(play.declarer().partner());
+    match suggested_lead(play, declarer, dummy) {
+      Some((card, probability)) => {
+        let reason = format!("starts the best line in its suit, which succeeds {:.0}% of the time", probability * 100.0);
+        self.trace = Some(DecisionTrace::Play(PlayTrace::new(play.next_player(), card, &reason)));
+        card
+      }
+      None => {
+        self.trace = None;
+        self.inner.get_play(play, board, deadline)
+      }
+    }
+  }
+
+  fn notify_dummy(&mut self, dummy: &PlayerHand) {
+    self.inner.notify_dummy(dummy)
+  }
+
+  fn notify_board(&mut self, number: u32, vulnerability: Vulnerability) {
+    self.inner.notify_board(number, vulnerability)
+  }
+
+  fn notify_auction(&mut self, auction: &Auction) {
+    self.inner.notify_auction(auction)
+  }
+
+  fn explain_call(&self, auction: &Auction, call: Call) -> Option<CallAnnotation> {
+    self.inner.explain_call(auction, call)
+  }
+
+  fn offer_claim(&mut self, play: &Play, board: &Board) -> Option<(u8, String)> {
+    self.inner.offer_claim(play, board)
+  }
+
+  fn respond_to_claim(&mut self, play: &Play, board: &Board, claim: &Claim) -> bool {
+    self.inner.respond_to_claim(play, board, claim)
+  }
+
+  fn last_decision(&self) -> Option<DecisionTrace> {
+    self.trace.clone().or_else(|| self.inner.last_decision())
+  }
+}
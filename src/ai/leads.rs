@@ -0,0 +1,264 @@
+use std::fmt::{Display, Formatter};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use crate::ai::dds::{DdsError, double_dummy_plays};
+use crate::ai::sampling::{DealSampler, infer_constraints};
+use crate::ai::trace::{CardScore, DecisionTrace, PlayTrace};
+use crate::game_model::{Board, HandResult, Vulnerability};
+use crate::game_model::bidding::{Auction, Call, CallAnnotation, Contract};
+use crate::game_model::cards::{Card, PlayerHand};
+use crate::game_model::play::Play;
+use crate::game_model::scoring::{datum, imps};
+use crate::interface::{Deadline, Player};
+
+/** The simulated value of one opening lead, averaged over the sampled deals. Tricks are the
+    defenders' tricks. IMPs are measured against the average score of all the leads on each deal,
+    and matchpoints are the fraction of the other leads beaten, with ties counting half.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LeadAnalysis {
+  pub card: Card,
+  pub average_tricks: f64,
+  pub set_probability: f64,
+  pub imps: f64,
+  pub matchpoints: f64,
+}
+
+/** The measure used to pick the best lead.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LeadMetric {
+  Tricks,
+  SetProbability,
+  Imps,
+  Matchpoints,
+}
+
+impl LeadAnalysis {
+  pub fn value(&self, metric: LeadMetric) -> f64 {
+    match metric {
+      LeadMetric::Tricks => self.average_tricks,
+      LeadMetric::SetProbability => self.set_probability,
+      LeadMetric::Imps => self.imps,
+      LeadMetric::Matchpoints => self.matchpoints,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeadReport {
+  contract: Contract,
+  samples: usize,
+  leads: Vec<LeadAnalysis>,
+}
+
+impl LeadReport {
+  /** Builds a report from double-dummy results, where `tricks[s][l]` is the number of tricks the
+      defenders take on sample `s` after leading `leads[l]`.
+   */
+  pub fn from_tricks(contract: Contract, vul: Vulnerability, leads: &[Card], tricks: &[Vec<u8>]) -> Self {
+    let samples = tricks.len();
+    let mut analyses: Vec<LeadAnalysis> = leads.iter()
+        .map(|&card| LeadAnalysis {
+          card,
+          average_tricks: 0.0,
+          set_probability: 0.0,
+          imps: 0.0,
+          matchpoints: 0.0,
+        })
+        .collect();
+    let target = contract.level() as i8 + 6;
+    for sample in tricks {
+      let scores: Vec<i32> = sample.iter()
+          .map(|&t| -HandResult::Played(contract, 13 - t as i8 - target).score(vul))
+          .collect();
+      let datum = datum(&scores);
+      for (i, analysis) in analyses.iter_mut().enumerate() {
+        analysis.average_tricks += sample[i] as f64;
+        if 13 - (sample[i] as i8) < target {
+          analysis.set_probability += 1.0;
+        }
+        analysis.imps += imps(scores[i] - datum) as f64;
+        let others = scores.len() - 1;
+        analysis.matchpoints += if others == 0 {
+          0.5
+        } else {
+          let beaten = scores.iter().filter(|&&s| s < scores[i]).count();
+          let tied = scores.iter().filter(|&&s| s == scores[i]).count() - 1;
+          (beaten as f64 + tied as f64 / 2.0) / others as f64
+        };
+      }
+    }
+    if samples > 0 {
+      for analysis in &mut analyses {
+        analysis.average_tricks /= samples as f64;
+        analysis.set_probability /= samples as f64;
+        analysis.imps /= samples as f64;
+        analysis.matchpoints /= samples as f64;
+      }
+    }
+    LeadReport { contract, samples, leads: analyses }
+  }
+
+  pub fn contract(&self) -> Contract {
+    self.contract
+  }
+
+  pub fn samples(&self) -> usize {
+    self.samples
+  }
+
+  pub fn leads(&self) -> &[LeadAnalysis] {
+    &self.leads
+  }
+
+  pub fn best(&self, metric: LeadMetric) -> Option<&LeadAnalysis> {
+    self.leads.iter().max_by(|a, b| a.value(metric).total_cmp(&b.value(metric)))
+  }
+}
+
+impl Display for LeadReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "Leads against {}{} ({} deals)", self.contract.level(), self.contract.strain(), self.samples)?;
+    let mut leads = self.leads.clone();
+    leads.sort_by(|a, b| b.imps.total_cmp(&a.imps));
+    for lead in leads {
+      writeln!(f, "{:>4}  tricks {:5.2}  set {:5.1}%  imps {:+6.2}  mp {:5.1}%", lead.card.to_string(),
+               lead.average_tricks, lead.set_probability * 100.0, lead.imps, lead.matchpoints * 100.0)?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LeadError {
+  /** The auction is not over, or was passed out. */
+  NoContract,
+  /** No deal consistent with the auction was found. */
+  NoSamples,
+  Dds(DdsError),
+}
+
+impl From<DdsError> for LeadError {
+  fn from(err: DdsError) -> Self {
+    LeadError::Dds(err)
+  }
+}
+
+/** Simulates every opening lead from `hand` against the contract reached in `auction`. The other
+    three hands are dealt at random to fit the auction, and each deal is solved double dummy for
+    all thirteen leads.
+ */
+pub fn analyse_leads<R: Rng + ?Sized>(auction: &Auction, hand: &PlayerHand, vul: Vulnerability,
+                                      samples: usize, rng: &mut R) -> Result<LeadReport, LeadError> {
+  let play = auction.play().ok_or(LeadError::NoContract)?;
+  let leader = play.next_player();
+  let deals = DealSampler::new()
+      .with_all_constraints(infer_constraints(auction))
+      .with_known_hand(leader, hand)
+      .samples(samples, rng);
+  if deals.is_empty() {
+    return Err(LeadError::NoSamples);
+  }
+  let leads = hand.cards().to_vec();
+  let mut tricks = Vec::with_capacity(deals.len());
+  for hands in deals {
+    let board = Board::from_hands(1, hands);
+    let solved = double_dummy_plays(&play, &board)?;
+    tricks.push(leads.iter()
+        .map(|&lead| solved.iter().find(|&&(card, _)| card == lead).map_or(0, |&(_, t)| t))
+        .collect());
+  }
+  Ok(LeadReport::from_tricks(play.contract(), vul, &leads, &tricks))
+}
+
+/** A player that chooses its opening leads by simulation and leaves every other decision to
+    another player.
+ */
+pub struct SimulatedLeadPlayer<P: Player> {
+  inner: P,
+  hand: PlayerHand,
+  auction: Option<Auction>,
+  samples: usize,
+  metric: LeadMetric,
+  rng: StdRng,
+  trace: Option<DecisionTrace>,
+}
+
+impl<P: Player> SimulatedLeadPlayer<P> {
+  pub fn with_samples(mut self, samples: usize) -> Self {
+    self.samples = samples;
+    self
+  }
+
+  pub fn with_metric(mut self, metric: LeadMetric) -> Self {
+    self.metric = metric;
+    self
+  }
+
+  pub fn with_seed(mut self, seed: u64) -> Self {
+    self.rng = StdRng::seed_from_u64(seed);
+    self
+  }
+}
+
+impl<P: Player> Player for SimulatedLeadPlayer<P> {
+  fn new(hand: &PlayerHand) -> Self {
+    SimulatedLeadPlayer {
+      inner: P::new(hand),
+      hand: hand.clone(),
+      auction: None,
+      samples: 100,
+      metric: LeadMetric::Imps,
+      rng: StdRng::from_entropy(),
+      trace: None,
+    }
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    self.inner.get_call(auction)
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: Deadline) -> Card {
+    if play.cards_played() == 0 {
+      if let Some(auction) = &self.auction {
+        let report = analyse_leads(auction, &self.hand, board.vulnerability(), self.samples, &mut self.rng);
+        if let Some(report) = report.ok().filter(|report| report.best(self.metric).is_some()) {
+          let lead = report.best(self.metric).unwrap().card;
+          let reason = format!("the best lead by {:?} over {} deals", self.metric, report.samples());
+          let mut trace = PlayTrace::new(play.next_player(), lead, &reason);
+          trace.scores = report.leads().iter().map(|lead| CardScore {
+            card: lead.card,
+            successes: (lead.set_probability * report.samples() as f64).round() as u32,
+            average_tricks: lead.average_tricks,
+          }).collect();
+          self.trace = Some(DecisionTrace::Play(trace));
+          return lead;
+        }
+      }
+    }
+    self.trace = None;
+    self.inner.get_play(play, board, deadline)
+  }
+
+  fn notify_dummy(&mut self, dummy: &PlayerHand) {
+    self.inner.notify_dummy(dummy)
+  }
+
+  fn notify_board(&mut self, number: u32, vulnerability: Vulnerability) {
+    self.inner.notify_board(number, vulnerability)
+  }
+
+  fn notify_auction(&mut self, auction: &Auction) {
+    self.auction = Some(auction.clone());
+    self.inner.notify_auction(auction)
+  }
+
+  fn explain_call(&self, auction: &Auction, call: Call) -> Option<CallAnnotation> {
+    self.inner.explain_call(auction, call)
+  }
+
+  fn last_decision(&self) -> Option<DecisionTrace> {
+    self.trace.clone().or_else(|| self.inner.last_decision())
+  }
+}
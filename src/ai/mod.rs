@@ -1,3 +1,15 @@
-mod alpha_mu;
-mod dds;
+pub mod alpha_mu;
+pub mod baseline;
+pub mod batch;
+pub mod bidding;
+pub mod claims;
+pub mod combinations;
+pub mod dds;
+pub mod declarer;
+pub mod leads;
+pub mod postmortem;
+pub mod sampling;
+pub mod search;
+pub mod signals;
+pub mod trace;
 mod dds_bindings;
@@ -0,0 +1,156 @@
+use std::fmt::{Display, Formatter};
+use enum_map::EnumMap;
+use crate::ai::dds::{double_dummy_trace, double_dummy_traces, DdsError};
+use crate::game_model::{Board, Seat, Side};
+use crate::game_model::cards::Card;
+use crate::game_model::play::Play;
+
+/** The double-dummy verdict on one card: the tricks declarer could take in the whole hand before
+    and after it was played, and how many of them the card gave away to the other side.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CardAnalysis {
+  pub seat: Seat,
+  pub card: Card,
+  pub before: u8,
+  pub after: u8,
+  pub cost: u8,
+}
+
+impl CardAnalysis {
+  pub fn is_costly(&self) -> bool {
+    self.cost > 0
+  }
+}
+
+/** The errors a side made: how many costly cards it played and the tricks they cost in all. */
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct ErrorSummary {
+  pub errors: u32,
+  pub tricks: u32,
+}
+
+impl ErrorSummary {
+  fn add(&mut self, other: ErrorSummary) {
+    self.errors += other.errors;
+    self.tricks += other.tricks;
+  }
+}
+
+/** A completed play checked card by card against double-dummy play. */
+#[derive(Debug, Clone)]
+pub struct PlayAnalysis {
+  declarer: Seat,
+  cards: Vec<CardAnalysis>,
+}
+
+impl PlayAnalysis {
+  /** Solves the play with DDS. */
+  pub fn analyse(play: &Play, board: &Board) -> Result<Self, DdsError> {
+    let tricks = double_dummy_trace(play, board)?;
+    Ok(PlayAnalysis::from_trace(play, &tricks).expect("DDS traces every card"))
+  }
+
+  /** Builds the analysis from declarer's double-dummy tricks before the first card and after each
+      card. Returns `None` if there is not one count more than there are cards.
+   */
+  pub fn from_trace(play: &Play, tricks: &[u8]) -> Option<Self> {
+    let declarer = play.declarer();
+    let played: Vec<(Seat, Card)> = play.tricks().iter().flat_map(|trick| trick.cards_in_order()).collect();
+    if tricks.len() != played.len() + 1 {
+      return None;
+    }
+    let cards = played.into_iter().zip(tricks.windows(2)).map(|((seat, card), pair)| {
+      let (before, after) = (pair[0], pair[1]);
+      let cost = if seat.is_opponent(declarer) { after.saturating_sub(before) } else { before.saturating_sub(after) };
+      CardAnalysis { seat, card, before, after, cost }
+    }).collect();
+    Some(PlayAnalysis { declarer, cards })
+  }
+
+  pub fn declarer(&self) -> Seat {
+    self.declarer
+  }
+
+  pub fn cards(&self) -> &[CardAnalysis] {
+    &self.cards
+  }
+
+  pub fn costly_cards(&self) -> impl Iterator<Item=&CardAnalysis> + '_ {
+    self.cards.iter().filter(|card| card.is_costly())
+  }
+
+  pub fn errors(&self, side: Side) -> ErrorSummary {
+    self.costly_cards()
+        .filter(|card| card.seat.side() == side)
+        .fold(ErrorSummary::default(), |summary, card| ErrorSummary {
+          errors: summary.errors + 1,
+          tricks: summary.tricks + card.cost as u32,
+        })
+  }
+}
+
+impl Display for PlayAnalysis {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    for (i, card) in self.cards.iter().enumerate().filter(|(_, card)| card.is_costly()) {
+      let tricks = if card.cost == 1 { "trick" } else { "tricks" };
+      writeln!(f, "trick {}: {:?} played {}, costing {} {} ({} to {} for declarer)",
+               i / 4 + 1, card.seat, card.card, card.cost, tricks, card.before, card.after)?;
+    }
+    let (ours, theirs) = (self.declarer.side(), self.declarer.side().opponents());
+    let (declaring, defending) = (self.errors(ours), self.errors(theirs));
+    write!(f, "declarer: {} errors costing {} tricks; defence: {} errors costing {} tricks",
+           declaring.errors, declaring.tricks, defending.errors, defending.tricks)
+  }
+}
+
+/** The post-mortems of a session of hands, with each side's errors added up across it. */
+#[derive(Debug, Clone, Default)]
+pub struct SessionAnalysis {
+  hands: Vec<PlayAnalysis>,
+}
+
+impl SessionAnalysis {
+  pub fn new() -> Self {
+    SessionAnalysis::default()
+  }
+
+  /** Solves every play with DDS, which shares the work between its threads. */
+  pub fn analyse(plays: &[(Play, Board)]) -> Result<Self, DdsError> {
+    let pairs: Vec<(&Play, &Board)> = plays.iter().map(|(play, board)| (play, board)).collect();
+    let traces = double_dummy_traces(&pairs)?;
+    let mut session = SessionAnalysis::new();
+    for ((play, _), tricks) in plays.iter().zip(traces) {
+      session.push(PlayAnalysis::from_trace(play, &tricks).expect("DDS traces every card"));
+    }
+    Ok(session)
+  }
+
+  pub fn push(&mut self, hand: PlayAnalysis) {
+    self.hands.push(hand);
+  }
+
+  pub fn hands(&self) -> &[PlayAnalysis] {
+    &self.hands
+  }
+
+  pub fn errors(&self) -> EnumMap<Side, ErrorSummary> {
+    let mut errors: EnumMap<Side, ErrorSummary> = EnumMap::default();
+    for hand in &self.hands {
+      for (side, summary) in errors.iter_mut() {
+        summary.add(hand.errors(side));
+      }
+    }
+    errors
+  }
+}
+
+impl Display for SessionAnalysis {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} hands", self.hands.len())?;
+    for (side, summary) in self.errors() {
+      write!(f, "; {:?}: {} errors costing {} tricks", side, summary.errors, summary.tricks)?;
+    }
+    Ok(())
+  }
+}
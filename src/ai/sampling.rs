@@ -0,0 +1,228 @@
+use enum_map::{enum_map, EnumMap};
+use itertools::Itertools;
+use rand::prelude::*;
+use strum::IntoEnumIterator;
+use crate::ai::signals::SignalReading;
+use crate::game_model::Seat;
+use crate::game_model::bidding::{Auction, Call, CallMeaning, Strain};
+use crate::game_model::cards::{Card, PlayerHand, Rank, Suit};
+
+/** Bounds on a hand that is not seen: a range of high-card points and a range of lengths in each
+    suit, all inclusive.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HandConstraints {
+  hcp: (u8, u8),
+  lengths: EnumMap<Suit, (u8, u8)>,
+}
+
+impl Default for HandConstraints {
+  fn default() -> Self {
+    HandConstraints { hcp: (0, 37), lengths: enum_map! { _ => (0, 13) } }
+  }
+}
+
+impl HandConstraints {
+  pub fn hcp(&self) -> (u8, u8) {
+    self.hcp
+  }
+
+  pub fn length(&self, suit: Suit) -> (u8, u8) {
+    self.lengths[suit]
+  }
+
+  /** Narrows the range of high-card points. Bounds that conflict with earlier ones leave a
+      constraint no hand can meet.
+   */
+  pub fn with_hcp(mut self, min: u8, max: u8) -> Self {
+    self.hcp = (self.hcp.0.max(min), self.hcp.1.min(max));
+    self
+  }
+
+  pub fn with_length(mut self, suit: Suit, min: u8, max: u8) -> Self {
+    let (old_min, old_max) = self.lengths[suit];
+    self.lengths[suit] = (old_min.max(min), old_max.min(max));
+    self
+  }
+
+  /** Limits every suit to between two and five cards, which takes in the balanced shapes. */
+  pub fn balanced(self) -> Self {
+    Suit::iter().fold(self, |c, suit| c.with_length(suit, 2, 5))
+  }
+
+  /** Narrows the constraints to the ranges a call was explained to show. */
+  pub fn with_meaning(self, meaning: &CallMeaning) -> Self {
+    Suit::iter().fold(self.with_hcp(meaning.hcp.0, meaning.hcp.1), |c, suit| {
+      let (min, max) = meaning.lengths[suit];
+      c.with_length(suit, min, max)
+    })
+  }
+
+  pub fn accepts(&self, hand: &PlayerHand) -> bool {
+    let hcp = hand.hcp();
+    (self.hcp.0..=self.hcp.1).contains(&hcp) && Suit::iter().all(|suit| {
+      let (min, max) = self.lengths[suit];
+      (min..=max).contains(&hand.suit_length(suit))
+    })
+  }
+}
+
+/** Reads rough constraints on each seat's hand from a natural auction. Only the first bid of each
+    player and passes before anyone has bid are taken into account; conventional bids are read as
+    natural, unless the auction carries the meaning of the call, which is used instead.
+ */
+pub fn infer_constraints(auction: &Auction) -> EnumMap<Seat, HandConstraints> {
+  let mut constraints: EnumMap<Seat, HandConstraints> = EnumMap::default();
+  let mut has_bid: EnumMap<Seat, bool> = EnumMap::default();
+  let mut opener = None;
+  let mut seat = auction.dealer();
+  for (i, &call) in auction.calls().enumerate() {
+    let c = constraints[seat];
+    if let Some(meaning) = auction.annotation(i).and_then(|annotation| annotation.meaning) {
+      constraints[seat] = c.with_meaning(&meaning);
+      if let Call::Bid(_) = call {
+        has_bid[seat] = true;
+        opener.get_or_insert(seat);
+      }
+      seat = seat.next_seat();
+      continue;
+    }
+    match call {
+      Call::Pass if opener.is_none() => constraints[seat] = c.with_hcp(0, 11),
+      Call::Bid(bid) if !has_bid[seat] => {
+        constraints[seat] = match (opener, bid.strain()) {
+          (None, Strain::Notrump) => match bid.level() {
+            1 => c.with_hcp(15, 17).balanced(),
+            2 => c.with_hcp(20, 21).balanced(),
+            _ => c.with_hcp(25, 27).balanced(),
+          },
+          (None, Strain::Trump(suit)) => match bid.level() {
+            1 if suit >= Suit::Hearts => c.with_hcp(11, 21).with_length(suit, 5, 13),
+            1 => c.with_hcp(11, 21).with_length(suit, 3, 13),
+            2 if suit == Suit::Clubs => c.with_hcp(22, 37),
+            2 => c.with_hcp(5, 10).with_length(suit, 6, 6),
+            _ => c.with_hcp(5, 10).with_length(suit, 7, 13),
+          },
+          (Some(opener), strain) if opener == seat.partner() => match strain {
+            Strain::Notrump => c.with_hcp(6, 19),
+            Strain::Trump(suit) => c.with_hcp(6, 37).with_length(suit, 4, 13),
+          },
+          (Some(_), Strain::Notrump) => c.with_hcp(15, 18),
+          (Some(_), Strain::Trump(suit)) => c.with_hcp(8, 17).with_length(suit, 5, 13),
+        };
+        has_bid[seat] = true;
+        opener.get_or_insert(seat);
+      }
+      _ => {}
+    }
+    seat = seat.next_seat();
+  }
+  constraints
+}
+
+/** Deals random hands consistent with what is known: cards already seen in each hand and
+    constraints on the hands as they were dealt. Deals are found by rejection, so constraints that
+    are rarely met can exhaust the attempts.
+ */
+#[derive(Debug, Clone)]
+pub struct DealSampler {
+  known: EnumMap<Seat, PlayerHand>,
+  constraints: EnumMap<Seat, HandConstraints>,
+  signals: Vec<SignalReading>,
+  max_attempts: usize,
+}
+
+impl Default for DealSampler {
+  fn default() -> Self {
+    DealSampler::new()
+  }
+}
+
+impl DealSampler {
+  pub fn new() -> Self {
+    DealSampler {
+      known: EnumMap::default(),
+      constraints: EnumMap::default(),
+      signals: Vec::new(),
+      max_attempts: 10_000,
+    }
+  }
+
+  /** Fixes some of the cards of a seat's hand. A full hand fixes the hand. */
+  pub fn with_known_cards(mut self, seat: Seat, cards: &[Card]) -> Self {
+    for &card in cards {
+      self.known[seat].add_card(card);
+    }
+    self
+  }
+
+  /** Fixes a seat's whole hand. Constraints already set on the seat are dropped: the hand is known,
+      and what its calls were taken to show need not fit it.
+   */
+  pub fn with_known_hand(mut self, seat: Seat, hand: &PlayerHand) -> Self {
+    self.constraints[seat] = HandConstraints::default();
+    self.with_known_cards(seat, hand.cards())
+  }
+
+  pub fn with_constraints(mut self, seat: Seat, constraints: HandConstraints) -> Self {
+    self.constraints[seat] = constraints;
+    self
+  }
+
+  pub fn with_all_constraints(mut self, constraints: EnumMap<Seat, HandConstraints>) -> Self {
+    self.constraints = constraints;
+    self
+  }
+
+  /** Takes defenders' signals into account. Signals are soft constraints: a deal that contradicts
+      them is kept only some of the time, so that deals agreeing with them are more likely.
+   */
+  pub fn with_signals(mut self, signals: Vec<SignalReading>) -> Self {
+    self.signals = signals;
+    self
+  }
+
+  /** Sets how many random deals are tried for each sample before giving up. */
+  pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+    self.max_attempts = max_attempts;
+    self
+  }
+
+  /** Deals a single sample, or returns `None` if no consistent deal was found. */
+  pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<EnumMap<Seat, PlayerHand>> {
+    let mut unseen: Vec<Card> = Suit::iter()
+        .cartesian_product(Rank::iter())
+        .map(|(suit, rank)| Card::from(suit, rank))
+        .filter(|&card| self.known.values().all(|hand| !hand.has_card(card)))
+        .collect();
+    let missing: EnumMap<Seat, usize> = enum_map! { seat => 13usize.saturating_sub(self.known[seat].len()) };
+    if missing.values().sum::<usize>() != unseen.len() {
+      return None;
+    }
+    for _ in 0..self.max_attempts {
+      unseen.shuffle(rng);
+      let mut cards = unseen.iter();
+      let hands = enum_map! {
+        seat => {
+          let mut hand = self.known[seat].clone();
+          for &card in cards.by_ref().take(missing[seat]) {
+            hand.add_card(card);
+          }
+          hand
+        }
+      };
+      if Seat::iter().all(|seat| self.constraints[seat].accepts(&hands[seat])) {
+        let weight: f64 = self.signals.iter().map(|signal| signal.weight(&hands[signal.seat])).product();
+        if weight >= 1.0 || rng.gen::<f64>() < weight {
+          return Some(hands);
+        }
+      }
+    }
+    None
+  }
+
+  /** Deals up to `count` samples, stopping early if a sample cannot be found. */
+  pub fn samples<R: Rng + ?Sized>(&self, count: usize, rng: &mut R) -> Vec<EnumMap<Seat, PlayerHand>> {
+    (0..count).map_while(|_| self.sample(rng)).collect()
+  }
+}
@@ -0,0 +1,270 @@
+use std::time::Instant;
+use enum_map::{enum_map, EnumMap};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::ai::batch::BatchSolver;
+use crate::ai::dds::DdsError;
+use crate::game_model::{Board, Seat};
+use crate::game_model::bidding::Strain;
+use crate::game_model::cards::Card;
+use crate::game_model::play::Play;
+use crate::interface::Deadline;
+
+fn card_index(card: Card) -> usize {
+  card.suit() as usize * 13 + card.rank() as usize
+}
+
+/** Random keys for hashing positions the Zobrist way: a position's hash is the exclusive or of the
+    keys of everything in it, so positions reached by playing the same cards in another order hash
+    alike. The keys come from a fixed seed, which keeps hashes the same from run to run.
+ */
+pub struct ZobristKeys {
+  cards: EnumMap<Seat, [u64; 52]>,
+  leader: EnumMap<Seat, u64>,
+  trick: [[u64; 52]; 3],
+  strain: EnumMap<Strain, u64>,
+}
+
+impl Default for ZobristKeys {
+  fn default() -> Self {
+    ZobristKeys::new()
+  }
+}
+
+impl ZobristKeys {
+  pub fn new() -> Self {
+    let mut rng = StdRng::seed_from_u64(0x5eed_b41d);
+    let mut keys = || -> [u64; 52] { std::array::from_fn(|_| rng.gen()) };
+    let cards = enum_map! { _ => keys() };
+    let trick = [keys(), keys(), keys()];
+    ZobristKeys {
+      cards,
+      trick,
+      leader: enum_map! { _ => rng.gen() },
+      strain: enum_map! { _ => rng.gen() },
+    }
+  }
+
+  /** The hash of a position: the cards each seat still holds, who led to the current trick or is
+      to lead the next, the cards played to the current trick so far and the strain.
+   */
+  pub fn hash(&self, play: &Play, board: &Board) -> u64 {
+    let strain = play.contract().strain();
+    let mut hash = self.strain[strain];
+    let mut remaining = enum_map! { seat => board.player_hand(seat).cards().to_vec() };
+    for (seat, card) in play.tricks().iter().flat_map(|trick| trick.cards_in_order()) {
+      remaining[seat].retain(|&c| c != card);
+    }
+    for (seat, cards) in &remaining {
+      for &card in cards {
+        hash ^= self.cards[seat][card_index(card)];
+      }
+    }
+    let current = play.tricks().last().filter(|trick| trick.card_count() < 4);
+    match current {
+      Some(trick) => {
+        hash ^= self.leader[trick.leader()];
+        for (i, (_, card)) in trick.cards_in_order().enumerate() {
+          hash ^= self.trick[i][card_index(card)];
+        }
+      }
+      None => hash ^= self.leader[play.next_player()],
+    }
+    hash
+  }
+}
+
+/** How well a cache is doing. */
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct CacheStats {
+  pub hits: u64,
+  pub misses: u64,
+  /** Entries overwritten by a different position. */
+  pub evictions: u64,
+}
+
+impl CacheStats {
+  pub fn hit_rate(&self) -> f64 {
+    let lookups = self.hits + self.misses;
+    if lookups == 0 { 0.0 } else { self.hits as f64 / lookups as f64 }
+  }
+}
+
+/** A cache of solved positions with a fixed number of slots. Each position has one slot, chosen by
+    its hash; a new position takes the slot over from whatever was there.
+ */
+pub struct TranspositionTable<V> {
+  slots: Vec<Option<(u64, V)>>,
+  stats: CacheStats,
+}
+
+impl<V: Clone> TranspositionTable<V> {
+  pub fn new(capacity: usize) -> Self {
+    TranspositionTable { slots: vec![None; capacity.max(1)], stats: CacheStats::default() }
+  }
+
+  fn slot(&self, hash: u64) -> usize {
+    (hash % self.slots.len() as u64) as usize
+  }
+
+  pub fn get(&mut self, hash: u64) -> Option<&V> {
+    let slot = self.slot(hash);
+    match &self.slots[slot] {
+      Some((stored, value)) if *stored == hash => {
+        self.stats.hits += 1;
+        Some(value)
+      }
+      _ => {
+        self.stats.misses += 1;
+        None
+      }
+    }
+  }
+
+  pub fn insert(&mut self, hash: u64, value: V) {
+    let slot = self.slot(hash);
+    if self.slots[slot].as_ref().is_some_and(|&(stored, _)| stored != hash) {
+      self.stats.evictions += 1;
+    }
+    self.slots[slot] = Some((hash, value));
+  }
+
+  pub fn len(&self) -> usize {
+    self.slots.iter().filter(|slot| slot.is_some()).count()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn capacity(&self) -> usize {
+    self.slots.len()
+  }
+
+  pub fn stats(&self) -> CacheStats {
+    self.stats
+  }
+
+  pub fn clear(&mut self) {
+    self.slots.iter_mut().for_each(|slot| *slot = None);
+  }
+}
+
+/** The number of positions a search context remembers unless told otherwise. */
+pub const DEFAULT_CACHE_SIZE: usize = 1 << 16;
+
+/** What a player has already solved during a hand. Later decisions in the hand reach many of the
+    same positions in the same worlds, and only the new ones are sent to DDS.
+ */
+pub struct SearchContext {
+  keys: ZobristKeys,
+  tricks: TranspositionTable<u8>,
+  plays: TranspositionTable<Vec<(Card, u8)>>,
+}
+
+impl Default for SearchContext {
+  fn default() -> Self {
+    SearchContext::new(DEFAULT_CACHE_SIZE)
+  }
+}
+
+impl SearchContext {
+  /** A context remembering up to `capacity` positions of each kind. */
+  pub fn new(capacity: usize) -> Self {
+    SearchContext {
+      keys: ZobristKeys::new(),
+      tricks: TranspositionTable::new(capacity),
+      plays: TranspositionTable::new(capacity),
+    }
+  }
+
+  pub fn hash(&self, play: &Play, board: &Board) -> u64 {
+    self.keys.hash(play, board)
+  }
+
+  /** Looks every position up, solves the ones missing together and remembers them. */
+  fn solve_cached<V: Clone>(
+    keys: &ZobristKeys,
+    table: &mut TranspositionTable<V>,
+    positions: &[(&Play, &Board)],
+    solve: impl FnOnce(&[(&Play, &Board)]) -> Result<Vec<V>, DdsError>,
+  ) -> Result<Vec<V>, DdsError> {
+    let hashes: Vec<u64> = positions.iter().map(|&(play, board)| keys.hash(play, board)).collect();
+    let mut values: Vec<Option<V>> = hashes.iter().map(|&hash| table.get(hash).cloned()).collect();
+    let missing: Vec<usize> = (0..positions.len()).filter(|&i| values[i].is_none()).collect();
+    if !missing.is_empty() {
+      let unsolved: Vec<(&Play, &Board)> = missing.iter().map(|&i| positions[i]).collect();
+      for (i, value) in missing.into_iter().zip(solve(&unsolved)?) {
+        table.insert(hashes[i], value.clone());
+        values[i] = Some(value);
+      }
+    }
+    Ok(values.into_iter().map(|value| value.expect("every position was solved")).collect())
+  }
+
+  /** As `BatchSolver::solve_tricks`, reusing positions solved before. */
+  pub fn solve_tricks(&mut self, positions: &[(&Play, &Board)]) -> Result<Vec<u8>, DdsError> {
+    SearchContext::solve_cached(&self.keys, &mut self.tricks, positions, |unsolved| {
+      BatchSolver::new().solve_tricks(unsolved)
+    })
+  }
+
+  /** As `BatchSolver::solve_plays`, reusing positions solved before. */
+  pub fn solve_plays(&mut self, positions: &[(&Play, &Board)]) -> Result<Vec<Vec<(Card, u8)>>, DdsError> {
+    SearchContext::solve_cached(&self.keys, &mut self.plays, positions, |unsolved| {
+      BatchSolver::new().solve_plays(unsolved)
+    })
+  }
+
+  /** The statistics of both caches together. */
+  pub fn stats(&self) -> CacheStats {
+    let (tricks, plays) = (self.tricks.stats(), self.plays.stats());
+    CacheStats {
+      hits: tricks.hits + plays.hits,
+      misses: tricks.misses + plays.misses,
+      evictions: tricks.evictions + plays.evictions,
+    }
+  }
+
+  /** Forgets every position, as at the start of a new hand. */
+  pub fn clear(&mut self) {
+    self.tricks.clear();
+    self.plays.clear();
+  }
+}
+
+/** Keeps a search to its deadline, counting the deals it solves. */
+#[derive(Debug, Copy, Clone)]
+pub struct SearchClock {
+  deadline: Deadline,
+  nodes: u64,
+}
+
+impl SearchClock {
+  pub fn start(deadline: Deadline) -> Self {
+    SearchClock { deadline, nodes: 0 }
+  }
+
+  pub fn count(&mut self, nodes: u64) {
+    self.nodes += nodes;
+  }
+
+  pub fn nodes(&self) -> u64 {
+    self.nodes
+  }
+
+  /** The deals still to be solved when the deadline is a number of them. */
+  pub fn remaining_nodes(&self) -> Option<u64> {
+    match self.deadline {
+      Deadline::At(_) => None,
+      Deadline::Nodes(nodes) => Some(nodes.saturating_sub(self.nodes)),
+    }
+  }
+
+  pub fn is_expired(&self) -> bool {
+    match self.deadline {
+      Deadline::At(at) => Instant::now() >= at,
+      Deadline::Nodes(nodes) => self.nodes >= nodes,
+    }
+  }
+}
@@ -0,0 +1,363 @@
+use strum::IntoEnumIterator;
+use crate::ai::trace::{DecisionTrace, PlayTrace};
+use crate::game_model::{Board, Seat, Vulnerability};
+use crate::game_model::bidding::{Auction, Call, CallAnnotation, Strain};
+use crate::game_model::cards::{Card, PlayerHand, Rank, Suit};
+use crate::game_model::play::{Claim, Play, Trick};
+use crate::interface::{Deadline, Player};
+
+/** Whether a high card or a low card carries the positive message: encouragement, an even number
+    of cards or a preference for the higher suit.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SignalStyle {
+  Standard,
+  UpsideDown,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SignalKind {
+  Attitude,
+  Count,
+  SuitPreference,
+}
+
+/** Which small card is led from a suit without a sequence. Sequences are always led from the top.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LeadConvention {
+  FourthBest,
+  ThirdAndFifth,
+}
+
+/** A partnership's carding agreements: the lead convention, the style of signals and the kind of
+    signal given when following to partner's lead, when following to declarer's lead and when
+    discarding.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Carding {
+  pub style: SignalStyle,
+  pub leads: LeadConvention,
+  pub partner_leads: SignalKind,
+  pub declarer_leads: SignalKind,
+  pub discards: SignalKind,
+}
+
+impl Default for Carding {
+  fn default() -> Self {
+    Carding {
+      style: SignalStyle::Standard,
+      leads: LeadConvention::FourthBest,
+      partner_leads: SignalKind::Attitude,
+      declarer_leads: SignalKind::Count,
+      discards: SignalKind::Attitude,
+    }
+  }
+}
+
+/** The message of a signal in a suit. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Signal {
+  Encourage(bool),
+  EvenCount(bool),
+  PreferHigher(bool),
+}
+
+impl Signal {
+  /** Returns whether the signal is given with a high card in the given style. */
+  pub fn is_high(&self, style: SignalStyle) -> bool {
+    let positive = match *self {
+      Signal::Encourage(yes) | Signal::EvenCount(yes) | Signal::PreferHigher(yes) => yes,
+    };
+    positive == (style == SignalStyle::Standard)
+  }
+
+  fn read(kind: SignalKind, high: bool, style: SignalStyle) -> Self {
+    let positive = high == (style == SignalStyle::Standard);
+    match kind {
+      SignalKind::Attitude => Signal::Encourage(positive),
+      SignalKind::Count => Signal::EvenCount(positive),
+      SignalKind::SuitPreference => Signal::PreferHigher(positive),
+    }
+  }
+}
+
+fn has_honour(hand: &PlayerHand, suit: Suit) -> bool {
+  [Rank::Ace, Rank::King, Rank::Queen].iter().any(|&rank| hand.has_card(Card::from(suit, rank)))
+}
+
+/** The two suits a suit-preference signal in `suit` chooses between, lower first: the suits other
+    than the signal suit and trumps. There is no choice if more than two suits are left.
+ */
+fn preference_suits(suit: Suit, trump: Strain) -> Option<(Suit, Suit)> {
+  let others: Vec<Suit> = Suit::iter()
+      .filter(|&other| other != suit && Strain::Trump(other) != trump)
+      .collect();
+  match others[..] {
+    [lower, higher] => Some((lower, higher)),
+    _ => None,
+  }
+}
+
+/** Whether a hand prefers the higher of the two suits a suit-preference signal in `suit` chooses
+    between, holding more high-card points there than in the lower. `None` if there is no choice.
+ */
+fn prefers_higher(hand: &PlayerHand, suit: Suit, trump: Strain) -> Option<bool> {
+  let points = |suit: Suit| -> u8 {
+    hand.cards().iter().filter(|card| card.suit() == suit).map(|card| card.rank().hcp()).sum()
+  };
+  preference_suits(suit, trump).map(|(lower, higher)| points(higher) > points(lower))
+}
+
+fn current_trick(play: &Play) -> Option<&Trick> {
+  play.tricks().last().filter(|trick| trick.card_count() < 4)
+}
+
+/** The kind of signal the defender due to play gives with a card of `suit`, or `None` if the card
+    is a lead or the seat is not a defender.
+ */
+pub fn signal_kind(play: &Play, seat: Seat, suit: Suit, carding: &Carding) -> Option<SignalKind> {
+  if !seat.is_opponent(play.declarer()) {
+    return None;
+  }
+  let (leader, led) = current_trick(play)?.cards_in_order().next()?;
+  Some(if led.suit() != suit {
+    carding.discards
+  } else if leader == seat.partner() {
+    carding.partner_leads
+  } else {
+    carding.declarer_leads
+  })
+}
+
+/** The signal a hand gives in `suit`: encouragement with an ace, king or queen in it, the parity
+    of its length, or a preference for the side suit with more high-card points. `hand` is what the
+    player holds before playing.
+ */
+pub fn intended_signal(kind: SignalKind, suit: Suit, hand: &PlayerHand, trump: Strain) -> Signal {
+  match kind {
+    SignalKind::Attitude => Signal::Encourage(has_honour(hand, suit)),
+    SignalKind::Count => Signal::EvenCount(hand.suit_length(suit).is_multiple_of(2)),
+    SignalKind::SuitPreference => Signal::PreferHigher(prefers_higher(hand, suit, trump) == Some(true)),
+  }
+}
+
+/** Chooses the card to lead from a suit, given its ranks from the top down: the top of a sequence
+    of two or more honours, the top of a doubleton or of three small cards, and otherwise the small
+    card the lead convention asks for.
+ */
+pub fn lead_from(holding: &[Rank], convention: LeadConvention) -> Option<Rank> {
+  let top = *holding.first()?;
+  let sequence = holding.len() >= 2 && top >= Rank::Ten && holding[1] as u8 + 1 == top as u8;
+  if sequence || holding.len() <= 2 || (holding.len() == 3 && top < Rank::Ten) {
+    return Some(top);
+  }
+  let position = match (convention, holding.len()) {
+    (LeadConvention::FourthBest, _) => 3,
+    (LeadConvention::ThirdAndFifth, 3 | 4) => 2,
+    (LeadConvention::ThirdAndFifth, _) => 4,
+  };
+  Some(holding[position.min(holding.len() - 1)])
+}
+
+/** Chooses among cards that are equally good, such as the cards of a sequence or small cards that
+    make no difference to the tricks. A defender's lead follows the lead convention and other cards
+    signal according to the carding agreements; otherwise the lowest card is played. `hand` is the
+    whole of what the player holds, and `equals` must be cards of one suit.
+ */
+pub fn choose_equal_card(equals: &[Card], play: &Play, hand: &PlayerHand, carding: &Carding) -> Option<Card> {
+  let suit = equals.first()?.suit();
+  let seat = play.next_player();
+  let mut ranks: Vec<Rank> = equals.iter().map(Card::rank).collect();
+  ranks.sort_by(|a, b| b.cmp(a));
+  let leading = current_trick(play).is_none();
+  if leading && seat.is_opponent(play.declarer()) {
+    let mut holding: Vec<Rank> = hand.cards().iter().filter(|card| card.suit() == suit).map(Card::rank).collect();
+    holding.sort_by(|a, b| b.cmp(a));
+    let choice = lead_from(&holding, carding.leads).filter(|rank| ranks.contains(rank));
+    return Some(Card::from(suit, choice.unwrap_or(ranks[ranks.len() - 1])));
+  }
+  let high = match signal_kind(play, seat, suit, carding) {
+    Some(kind) => intended_signal(kind, suit, hand, play.contract().strain()).is_high(carding.style),
+    None => false,
+  };
+  // a signal is given with the highest card that is not an honour, to avoid wasting one
+  let rank = if high {
+    ranks.iter().copied().find(|&rank| rank < Rank::Ten).unwrap_or(ranks[ranks.len() - 1])
+  } else {
+    ranks[ranks.len() - 1]
+  };
+  Some(Card::from(suit, rank))
+}
+
+/** The weight given to a deal in which a hand contradicts its signal. Signals are only taken as
+    likely, since a defender may have had no free choice of card.
+ */
+pub const CONTRADICTED_SIGNAL_WEIGHT: f64 = 0.25;
+
+/** A signal read from a card, as a soft constraint on the original hand of the seat that gave it.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SignalReading {
+  pub seat: Seat,
+  pub suit: Suit,
+  pub signal: Signal,
+  trump: Strain,
+}
+
+impl SignalReading {
+  /** Returns whether an original hand agrees with the signal. */
+  pub fn agrees_with(&self, hand: &PlayerHand) -> bool {
+    match self.signal {
+      Signal::Encourage(yes) => has_honour(hand, self.suit) == yes,
+      Signal::EvenCount(even) => hand.suit_length(self.suit).is_multiple_of(2) == even,
+      Signal::PreferHigher(higher) => prefers_higher(hand, self.suit, self.trump).is_none_or(|prefers| prefers == higher),
+    }
+  }
+
+  pub fn weight(&self, hand: &PlayerHand) -> f64 {
+    if self.agrees_with(hand) { 1.0 } else { CONTRADICTED_SIGNAL_WEIGHT }
+  }
+}
+
+/** Reads the signals `seat` has given so far, assuming the carding agreements. The first card a
+    defender plays in a suit that someone else led, or discards, is read as a signal if it is a
+    clearly high spot card (seven to nine) or a clearly low one (five or lower).
+ */
+pub fn read_signals(play: &Play, seat: Seat, carding: &Carding) -> Vec<SignalReading> {
+  let mut readings = Vec::new();
+  if !seat.is_opponent(play.declarer()) {
+    return readings;
+  }
+  let mut before = Play::new(play.contract());
+  let mut played: Vec<Card> = Vec::new();
+  for (player, card) in play.tricks().iter().flat_map(|trick| trick.cards_in_order()) {
+    if player == seat && !played.iter().any(|c| c.suit() == card.suit()) {
+      let high = match card.rank() {
+        Rank::Seven | Rank::Eight | Rank::Nine => Some(true),
+        rank if rank <= Rank::Five => Some(false),
+        _ => None,
+      };
+      let kind = signal_kind(&before, seat, card.suit(), carding);
+      if let (Some(high), Some(kind)) = (high, kind) {
+        readings.push(SignalReading {
+          seat,
+          suit: card.suit(),
+          signal: Signal::read(kind, high, carding.style),
+          trump: play.contract().strain(),
+        });
+      }
+    }
+    if player == seat {
+      played.push(card);
+    }
+    before.make_play(player, card);
+  }
+  readings
+}
+
+/** The cards of `hand` in the suit of `card` that are equal to it as far as their holder can see:
+    every card of the suit ranking between them is also in `hand` or has been played.
+ */
+fn equal_cards(card: Card, hand: &PlayerHand, play: &Play) -> Vec<Card> {
+  let played = |other: Card| play.tricks().iter().flat_map(|trick| trick.cards_in_order()).any(|(_, c)| c == other);
+  let seen = |rank: Rank| {
+    let other = Card::from(card.suit(), rank);
+    hand.has_card(other) || played(other)
+  };
+  hand.cards().iter()
+      .filter(|other| other.suit() == card.suit())
+      .filter(|other| {
+        let (low, high) = (other.rank().min(card.rank()), other.rank().max(card.rank()));
+        Rank::iter().filter(|&rank| low < rank && rank < high).all(seen)
+      })
+      .copied()
+      .collect()
+}
+
+/** A defender that signals. The inner player chooses each card; when other cards of the same suit
+    in the defender's hand are equal to it, with nothing between them left to play, the card is
+    chosen from them by the carding agreements instead.
+ */
+pub struct SignallingDefender<P: Player> {
+  inner: P,
+  hand: PlayerHand,
+  carding: Carding,
+  trace: Option<DecisionTrace>,
+}
+
+impl<P: Player> SignallingDefender<P> {
+  pub fn with_carding(mut self, carding: Carding) -> Self {
+    self.carding = carding;
+    self
+  }
+
+  fn remaining_hand(&self, play: &Play) -> PlayerHand {
+    let mut hand = self.hand.clone();
+    for trick in play.tricks() {
+      for (_, card) in trick.cards_in_order() {
+        hand.take_card(card);
+      }
+    }
+    hand
+  }
+}
+
+impl<P: Player> Player for SignallingDefender<P> {
+  fn new(hand: &PlayerHand) -> Self {
+    SignallingDefender { inner: P::new(hand), hand: hand.clone(), carding: Carding::default(), trace: None }
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    self.inner.get_call(auction)
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: Deadline) -> Card {
+    self.trace = None;
+    let card = self.inner.get_play(play, board, deadline);
+    if !play.next_player().is_opponent(play.declarer()) {
+      return card;
+    }
+    let hand = self.remaining_hand(play);
+    let equals = equal_cards(card, &hand, play);
+    let signal = choose_equal_card(&equals, play, &hand, &self.carding).unwrap_or(card);
+    if signal != card {
+      let mut trace = match self.inner.last_decision() {
+        Some(DecisionTrace::Play(trace)) => trace,
+        _ => PlayTrace::new(play.next_player(), card, "chosen by the inner player"),
+      };
+      trace.reason = format!("{}; {} signals with an equal card", trace.reason, signal);
+      trace.chosen = signal;
+      self.trace = Some(DecisionTrace::Play(trace));
+    }
+    signal
+  }
+
+  fn notify_dummy(&mut self, dummy: &PlayerHand) {
+    self.inner.notify_dummy(dummy)
+  }
+
+  fn notify_board(&mut self, number: u32, vulnerability: Vulnerability) {
+    self.inner.notify_board(number, vulnerability)
+  }
+
+  fn notify_auction(&mut self, auction: &Auction) {
+    self.inner.notify_auction(auction)
+  }
+
+  fn explain_call(&self, auction: &Auction, call: Call) -> Option<CallAnnotation> {
+    self.inner.explain_call(auction, call)
+  }
+
+  fn offer_claim(&mut self, play: &Play, board: &Board) -> Option<(u8, String)> {
+    self.inner.offer_claim(play, board)
+  }
+
+  fn respond_to_claim(&mut self, play: &Play, board: &Board, claim: &Claim) -> bool {
+    self.inner.respond_to_claim(play, board, claim)
+  }
+
+  fn last_decision(&self) -> Option<DecisionTrace> {
+    self.trace.clone().or_else(|| self.inner.last_decision())
+  }
+}
@@ -0,0 +1,135 @@
+use std::fmt::{Display, Formatter};
+use enum_map::EnumMap;
+use crate::ai::bidding::simulation::CallEvaluation;
+use crate::game_model::Seat;
+use crate::game_model::bidding::Call;
+use crate::game_model::cards::{Card, PlayerHand};
+use crate::game_model::notation::Notation;
+
+/** Why a player made its last decision. */
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum DecisionTrace {
+  Call(CallTrace),
+  Play(PlayTrace),
+}
+
+impl DecisionTrace {
+  #[cfg(feature = "serde")]
+  pub fn to_json(&self) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(self)
+  }
+
+  #[cfg(feature = "serde")]
+  pub fn from_json(json: &str) -> serde_json::Result<Self> {
+    serde_json::from_str(json)
+  }
+}
+
+impl Display for DecisionTrace {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DecisionTrace::Call(trace) => trace.fmt(f),
+      DecisionTrace::Play(trace) => trace.fmt(f),
+    }
+  }
+}
+
+/** A bidding rule that applied at a decision, and whether the hand met its constraint. */
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleTrace {
+  pub rule: String,
+  pub accepted: bool,
+}
+
+/** A call: the rules considered in order, any simulation of the alternatives and the call made. */
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallTrace {
+  pub chosen: Call,
+  pub reason: String,
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  pub rules: Vec<RuleTrace>,
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  pub simulation: Vec<CallEvaluation>,
+}
+
+impl Display for CallTrace {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "called {}: {}", self.chosen.notation(), self.reason)?;
+    for rule in &self.rules {
+      writeln!(f, "  {} {}", if rule.accepted { "+" } else { "-" }, rule.rule)?;
+    }
+    for evaluation in &self.simulation {
+      writeln!(f, "  {:>4}: {:+.2} IMPs, {:.0}% matchpoints, {:+.0} points",
+               evaluation.call.notation(), evaluation.imps, evaluation.matchpoints * 100.0, evaluation.average_score)?;
+    }
+    Ok(())
+  }
+}
+
+/** How a candidate card fared over the deals searched: the deals in which it reached the side's
+    target and the tricks it took on average.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CardScore {
+  pub card: Card,
+  pub successes: u32,
+  pub average_tricks: f64,
+}
+
+/** One deal the search looked at, with the tricks the side to play takes after each candidate, in
+    the order of the trace's scores.
+ */
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorldTrace {
+  pub hands: EnumMap<Seat, PlayerHand>,
+  pub tricks: Vec<u8>,
+}
+
+/** Hands are equal when they hold the same cards, in any order. */
+impl PartialEq for WorldTrace {
+  fn eq(&self, other: &Self) -> bool {
+    self.tricks == other.tricks && self.hands.iter().all(|(seat, hand)| hand.sort() == other.hands[seat].sort())
+  }
+}
+
+/** A card: the candidates with their scores, the deals behind them and the card played. */
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayTrace {
+  pub seat: Seat,
+  pub chosen: Card,
+  pub reason: String,
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  pub scores: Vec<CardScore>,
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  pub worlds: Vec<WorldTrace>,
+}
+
+impl PlayTrace {
+  /** A card chosen without weighing the alternatives. */
+  pub fn new(seat: Seat, chosen: Card, reason: &str) -> Self {
+    PlayTrace { seat, chosen, reason: reason.to_string(), scores: Vec::new(), worlds: Vec::new() }
+  }
+}
+
+impl Display for PlayTrace {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "{:?} played {}: {}", self.seat, self.chosen, self.reason)?;
+    let deals = self.worlds.len();
+    for score in &self.scores {
+      let marker = if score.card == self.chosen { '>' } else { ' ' };
+      write!(f, "{} {}: {:.2} tricks", marker, score.card, score.average_tricks)?;
+      if deals > 0 {
+        write!(f, ", target reached in {} of {} deals", score.successes, deals)?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,640 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::rc::Rc;
+use enum_map::{enum_map, EnumMap};
+use strum::IntoEnumIterator;
+use crate::game_model::{Board, BridgeGame, HandResult, Seat, Side, Vulnerability};
+use crate::game_model::bidding::{Auction, Bid, Call};
+use crate::game_model::cards::{Card, PlayerHand, Rank, Suit};
+use crate::game_model::notation::Notation;
+use crate::game_model::play::Play;
+use crate::interface::{Deadline, Player, TimeControl};
+use crate::tournament::{play_board_with_claims, TableError};
+
+/** The version of the table-manager protocol spoken, sent when a player connects. */
+pub const PROTOCOL_VERSION: u32 = 18;
+
+/** A line of the Blue Chip Bridge table-manager protocol, as used in computer bridge matches. The
+    table manager deals and relays; each player, connected over TCP, says when it is ready for the
+    next thing it needs to know and sends its own calls and cards. Declarer sends dummy's cards.
+ */
+#[derive(Debug, Clone)]
+pub enum Message {
+  Connecting { team: String, seat: Seat },
+  Seated { seat: Seat, team: String },
+  ReadyForTeams(Seat),
+  Teams { north_south: String, east_west: String },
+  ReadyToStart(Seat),
+  StartOfBoard,
+  ReadyForDeal(Seat),
+  Deal { board: u32, dealer: Seat, vulnerability: Vulnerability },
+  ReadyForCards(Seat),
+  Cards(Seat, PlayerHand),
+  /** The first seat is waiting for the second to call. */
+  ReadyForCall(Seat, Seat),
+  Call(Seat, Call),
+  /** `seat` is waiting for the card of `from`, or of dummy if that is `None`. Tricks count from one. */
+  ReadyForCard { seat: Seat, from: Option<Seat>, trick: usize },
+  ReadyForDummy(Seat),
+  Dummy(PlayerHand),
+  Play(Seat, Card),
+  EndOfSession,
+}
+
+fn seat_name(seat: Seat) -> String {
+  format!("{:?}", seat)
+}
+
+fn read_seat(word: &str) -> Option<Seat> {
+  Seat::iter().find(|&seat| seat_name(seat).eq_ignore_ascii_case(word))
+}
+
+fn vulnerability_text(vulnerability: Vulnerability) -> &'static str {
+  match vulnerability {
+    Vulnerability::Neither => "Neither",
+    Vulnerability::NS => "N/S",
+    Vulnerability::EW => "E/W",
+    Vulnerability::Both => "Both",
+  }
+}
+
+fn read_vulnerability(text: &str) -> Option<Vulnerability> {
+  [Vulnerability::Neither, Vulnerability::NS, Vulnerability::EW, Vulnerability::Both].into_iter()
+      .find(|&vulnerability| vulnerability_text(vulnerability).eq_ignore_ascii_case(text))
+}
+
+/** A hand by suits from spades down, each ended by a full stop: `"S A K 3. H -. D T 8 5 2. ..."`. */
+fn hand_text(hand: &PlayerHand) -> String {
+  let sorted = hand.sort();
+  let suits: Vec<String> = Suit::iter().rev().map(|suit| {
+    let ranks: Vec<String> = sorted[suit].iter().map(|rank| rank.rank_char().to_string()).collect();
+    format!("{} {}.", suit.letter(), if ranks.is_empty() { "-".to_string() } else { ranks.join(" ") })
+  }).collect();
+  suits.join(" ")
+}
+
+fn read_hand(text: &str) -> Option<PlayerHand> {
+  let mut hand = PlayerHand::default();
+  for holding in text.split('.').map(str::trim).filter(|holding| !holding.is_empty()) {
+    let mut words = holding.split_whitespace();
+    let suit = Suit::from_notation(words.next()?)?;
+    for word in words.filter(|&word| word != "-") {
+      let rank = if word == "10" { Rank::Ten } else { Rank::from_notation(word)? };
+      let card = Card::from(suit, rank);
+      if hand.has_card(card) {
+        return None;
+      }
+      hand.add_card(card);
+    }
+  }
+  Some(hand)
+}
+
+impl Message {
+  /** Reads a line of the protocol, ignoring the line ending. A call may be followed by an alert,
+      which is ignored.
+   */
+  pub fn parse(line: &str) -> Option<Message> {
+    let line = line.trim();
+    if line.eq_ignore_ascii_case("Start of board") {
+      return Some(Message::StartOfBoard);
+    }
+    if line.eq_ignore_ascii_case("End of session") {
+      return Some(Message::EndOfSession);
+    }
+    if let Some(rest) = line.strip_prefix("Connecting ") {
+      let (team, rest) = rest.rsplit_once(" as ")?;
+      let seat = read_seat(rest.split_whitespace().next()?)?;
+      return Some(Message::Connecting { team: team.trim().trim_matches('"').to_string(), seat });
+    }
+    if let Some(rest) = line.strip_prefix("Teams") {
+      let parts: Vec<&str> = rest.split('"').collect();
+      return match parts[..] {
+        [_, north_south, _, east_west, _] => {
+          Some(Message::Teams { north_south: north_south.to_string(), east_west: east_west.to_string() })
+        }
+        _ => None,
+      };
+    }
+    if let Some(rest) = line.strip_prefix("Board number ") {
+      let parts: Vec<&str> = rest.split('.').map(str::trim).collect();
+      return Some(Message::Deal {
+        board: parts.first()?.parse().ok()?,
+        dealer: read_seat(parts.get(1)?.strip_prefix("Dealer ")?)?,
+        vulnerability: read_vulnerability(parts.get(2)?.strip_suffix(" vulnerable")?)?,
+      });
+    }
+    let (first, rest) = line.split_once(' ')?;
+    let rest = rest.trim();
+    if let Some(cards) = rest.strip_prefix("cards :") {
+      let hand = read_hand(cards)?;
+      if first.eq_ignore_ascii_case("Dummy's") {
+        return Some(Message::Dummy(hand));
+      }
+      return Some(Message::Cards(read_seat(first.strip_suffix("'s")?)?, hand));
+    }
+    let seat = read_seat(first)?;
+    if let Some(team) = rest.strip_suffix(" seated") {
+      let team = team.trim_start_matches('(').trim_end_matches(')').trim_matches('"');
+      return Some(Message::Seated { seat, team: team.to_string() });
+    }
+    if let Some(wanted) = rest.strip_prefix("ready ") {
+      return match wanted {
+        "for teams" => Some(Message::ReadyForTeams(seat)),
+        "to start" => Some(Message::ReadyToStart(seat)),
+        "for deal" => Some(Message::ReadyForDeal(seat)),
+        "for cards" => Some(Message::ReadyForCards(seat)),
+        "for dummy" => Some(Message::ReadyForDummy(seat)),
+        _ => {
+          let (whose, what) = wanted.strip_prefix("for ")?.split_once(' ')?;
+          let whose = whose.strip_suffix("'s")?;
+          if what == "bid" {
+            return Some(Message::ReadyForCall(seat, read_seat(whose)?));
+          }
+          let trick = what.strip_prefix("card to trick ")?.parse().ok()?;
+          let from = if whose.eq_ignore_ascii_case("dummy") { None } else { Some(read_seat(whose)?) };
+          Some(Message::ReadyForCard { seat, from, trick })
+        }
+      };
+    }
+    let mut words = rest.split_whitespace();
+    match words.next()? {
+      "passes" => Some(Message::Call(seat, Call::Pass)),
+      "doubles" => Some(Message::Call(seat, Call::Double)),
+      "redoubles" => Some(Message::Call(seat, Call::Redouble)),
+      "bids" => Some(Message::Call(seat, Call::Bid(Bid::from_notation(words.next()?)?))),
+      "plays" => Some(Message::Play(seat, Card::from_notation(&words.next()?.replace("10", "T"))?)),
+      _ => None,
+    }
+  }
+}
+
+impl Display for Message {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Message::Connecting { team, seat } => {
+        write!(f, "Connecting \"{}\" as {} using protocol version {}", team, seat_name(*seat), PROTOCOL_VERSION)
+      }
+      Message::Seated { seat, team } => write!(f, "{} (\"{}\") seated", seat_name(*seat), team),
+      Message::ReadyForTeams(seat) => write!(f, "{} ready for teams", seat_name(*seat)),
+      Message::Teams { north_south, east_west } => {
+        write!(f, "Teams : N/S : \"{}\" E/W : \"{}\"", north_south, east_west)
+      }
+      Message::ReadyToStart(seat) => write!(f, "{} ready to start", seat_name(*seat)),
+      Message::StartOfBoard => write!(f, "Start of board"),
+      Message::ReadyForDeal(seat) => write!(f, "{} ready for deal", seat_name(*seat)),
+      Message::Deal { board, dealer, vulnerability } => {
+        write!(f, "Board number {}. Dealer {}. {} vulnerable.", board, seat_name(*dealer), vulnerability_text(*vulnerability))
+      }
+      Message::ReadyForCards(seat) => write!(f, "{} ready for cards", seat_name(*seat)),
+      Message::Cards(seat, hand) => write!(f, "{}'s cards : {}", seat_name(*seat), hand_text(hand)),
+      Message::ReadyForCall(seat, bidder) => write!(f, "{} ready for {}'s bid", seat_name(*seat), seat_name(*bidder)),
+      Message::Call(seat, call) => match call {
+        Call::Pass => write!(f, "{} passes", seat_name(*seat)),
+        Call::Double => write!(f, "{} doubles", seat_name(*seat)),
+        Call::Redouble => write!(f, "{} redoubles", seat_name(*seat)),
+        Call::Bid(bid) => write!(f, "{} bids {}", seat_name(*seat), bid.notation()),
+      },
+      Message::ReadyForCard { seat, from, trick } => {
+        let from = from.map_or("dummy".to_string(), seat_name);
+        write!(f, "{} ready for {}'s card to trick {}", seat_name(*seat), from, trick)
+      }
+      Message::ReadyForDummy(seat) => write!(f, "{} ready for dummy", seat_name(*seat)),
+      Message::Dummy(hand) => write!(f, "Dummy's cards : {}", hand_text(hand)),
+      Message::Play(seat, card) => write!(f, "{} plays {}", seat_name(*seat), card.notation()),
+      Message::EndOfSession => write!(f, "End of session"),
+    }
+  }
+}
+
+/** Something went wrong talking to the other end of a table connection. */
+#[derive(Debug)]
+pub enum ProtocolError {
+  Io(std::io::Error),
+  /** The other end closed the connection. */
+  Closed,
+  /** A line that was not the message expected at that point. */
+  Unexpected(String),
+  /** A player broke the rules of the game. */
+  Table(TableError),
+}
+
+impl Display for ProtocolError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ProtocolError::Io(err) => write!(f, "connection: {}", err),
+      ProtocolError::Closed => write!(f, "the connection was closed"),
+      ProtocolError::Unexpected(line) => write!(f, "unexpected message: {}", line),
+      ProtocolError::Table(err) => err.fmt(f),
+    }
+  }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<std::io::Error> for ProtocolError {
+  fn from(err: std::io::Error) -> Self {
+    ProtocolError::Io(err)
+  }
+}
+
+impl From<TableError> for ProtocolError {
+  fn from(err: TableError) -> Self {
+    ProtocolError::Table(err)
+  }
+}
+
+fn unexpected(message: Message) -> ProtocolError {
+  ProtocolError::Unexpected(message.to_string())
+}
+
+/** One end of a table connection, sending and receiving whole lines. */
+struct Connection {
+  reader: BufReader<TcpStream>,
+  writer: TcpStream,
+}
+
+impl Connection {
+  fn new(stream: TcpStream) -> Result<Self, ProtocolError> {
+    stream.set_nodelay(true)?;
+    Ok(Connection { reader: BufReader::new(stream.try_clone()?), writer: stream })
+  }
+
+  fn send(&mut self, message: &Message) -> Result<(), ProtocolError> {
+    write!(self.writer, "{}\r\n", message)?;
+    self.writer.flush()?;
+    Ok(())
+  }
+
+  fn receive(&mut self) -> Result<Message, ProtocolError> {
+    let mut line = String::new();
+    if self.reader.read_line(&mut line)? == 0 {
+      return Err(ProtocolError::Closed);
+    }
+    Message::parse(&line).ok_or_else(|| ProtocolError::Unexpected(line.trim().to_string()))
+  }
+
+  fn expect(&mut self, expected: &Message) -> Result<(), ProtocolError> {
+    let message = self.receive()?;
+    if message.to_string() == expected.to_string() { Ok(()) } else { Err(unexpected(message)) }
+  }
+}
+
+/** A player connected to the table manager, with how much of the board it has been told. Calls
+    and cards are passed on only when the player next has to act, or when the board ends.
+ */
+struct Remote {
+  seat: Seat,
+  team: String,
+  connection: Connection,
+  calls: usize,
+  cards: usize,
+  error: Option<ProtocolError>,
+}
+
+impl Remote {
+  fn relay_calls(&mut self, auction: &Auction) -> Result<(), ProtocolError> {
+    for (i, &call) in auction.calls().enumerate().skip(self.calls) {
+      let bidder = auction.bidder(i);
+      if bidder != self.seat {
+        self.connection.expect(&Message::ReadyForCall(self.seat, bidder))?;
+        self.connection.send(&Message::Call(bidder, call))?;
+      }
+      self.calls = i + 1;
+    }
+    Ok(())
+  }
+
+  fn relay_cards(&mut self, play: &Play, board: &Board) -> Result<(), ProtocolError> {
+    let declarer = play.declarer();
+    let dummy = declarer.partner();
+    let cards: Vec<(Seat, Card)> = play.tricks().iter().flat_map(|trick| trick.cards_in_order()).collect();
+    for (i, &(seat, card)) in cards.iter().enumerate().skip(self.cards) {
+      let controller = if seat == dummy { declarer } else { seat };
+      if controller != self.seat {
+        let from = Some(seat).filter(|&seat| seat != dummy);
+        self.connection.expect(&Message::ReadyForCard { seat: self.seat, from, trick: i / 4 + 1 })?;
+        self.connection.send(&Message::Play(seat, card))?;
+      }
+      if i == 0 && self.seat != dummy {
+        self.connection.expect(&Message::ReadyForDummy(self.seat))?;
+        self.connection.send(&Message::Dummy(board.player_hand(dummy).clone()))?;
+      }
+      self.cards = i + 1;
+    }
+    Ok(())
+  }
+}
+
+/** A seat played by someone connected to a `TableManager`. It is made by `TableManager::deal` and
+    used only by `TableManager::play_board`, since it cannot be made from a hand as other players
+    are. The remote player keeps its own time, so deadlines are not passed on. If the connection
+    fails, the seat passes and plays its lowest legal card to the end of the board, and the table
+    manager reports the failure when the board is finished.
+ */
+struct RemoteSeat {
+  remote: Rc<RefCell<Remote>>,
+}
+
+impl RemoteSeat {
+  fn attempt<T>(&self, action: impl FnOnce(&mut Remote) -> Result<T, ProtocolError>) -> Option<T> {
+    let mut remote = self.remote.borrow_mut();
+    if remote.error.is_some() {
+      return None;
+    }
+    action(&mut remote).map_err(|err| remote.error = Some(err)).ok()
+  }
+}
+
+impl Player for RemoteSeat {
+  fn new(_hand: &PlayerHand) -> Self {
+    unreachable!("a remote seat is made by TableManager::deal for a connected player")
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    self.attempt(|remote| {
+      remote.relay_calls(auction)?;
+      match remote.connection.receive()? {
+        Message::Call(seat, call) if seat == remote.seat => Ok(call),
+        message => Err(unexpected(message)),
+      }
+    }).unwrap_or(Call::Pass)
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, _deadline: Deadline) -> Card {
+    let seat = play.next_player();
+    self.attempt(|remote| {
+      remote.relay_cards(play, board)?;
+      match remote.connection.receive()? {
+        Message::Play(player, card) if player == seat => Ok(card),
+        message => Err(unexpected(message)),
+      }
+    }).unwrap_or_else(|| {
+      play.legal_plays(board.player_hand(seat)).into_iter()
+          .min_by_key(|card| (card.rank(), card.suit()))
+          .expect("the player to play holds a card")
+    })
+  }
+
+  fn notify_dummy(&mut self, _dummy: &PlayerHand) {}
+
+  fn notify_auction(&mut self, auction: &Auction) {
+    self.attempt(|remote| remote.relay_calls(auction));
+  }
+}
+
+/** A table manager: players connect to it over TCP to take seats, and it deals them boards and
+    passes on the calls and cards made at the table. Seats no one has connected to are filled by
+    local players.
+ */
+pub struct TableManager {
+  listener: TcpListener,
+  remotes: EnumMap<Seat, Option<Rc<RefCell<Remote>>>>,
+  control: TimeControl,
+}
+
+impl TableManager {
+  pub fn bind(address: impl ToSocketAddrs) -> Result<Self, ProtocolError> {
+    Ok(TableManager { listener: TcpListener::bind(address)?, remotes: EnumMap::default(), control: TimeControl::default() })
+  }
+
+  pub fn local_addr(&self) -> Result<SocketAddr, ProtocolError> {
+    Ok(self.listener.local_addr()?)
+  }
+
+  /** Sets how much the local players may think in a hand. */
+  pub fn with_time_control(mut self, control: TimeControl) -> Self {
+    self.control = control;
+    self
+  }
+
+  /** Waits for a player to connect to each of `seats`, then tells them the teams. A connection
+      asking for a seat that is not wanted or already taken is closed. A side with no one connected
+      is named "bridge-ai".
+   */
+  pub fn seat_players(&mut self, seats: &[Seat]) -> Result<(), ProtocolError> {
+    while seats.iter().any(|&seat| self.remotes[seat].is_none()) {
+      let mut connection = Connection::new(self.listener.accept()?.0)?;
+      let (team, seat) = match connection.receive() {
+        Ok(Message::Connecting { team, seat }) if seats.contains(&seat) && self.remotes[seat].is_none() => (team, seat),
+        _ => continue,
+      };
+      connection.send(&Message::Seated { seat, team: team.clone() })?;
+      let remote = Remote { seat, team, connection, calls: 0, cards: 0, error: None };
+      self.remotes[seat] = Some(Rc::new(RefCell::new(remote)));
+    }
+    let team = |side: Side| {
+      Seat::iter().filter(|seat| seat.side() == side)
+          .find_map(|seat| self.remotes[seat].as_ref().map(|remote| remote.borrow().team.clone()))
+          .unwrap_or_else(|| "bridge-ai".to_string())
+    };
+    let teams = Message::Teams { north_south: team(Side::NorthSouth), east_west: team(Side::EastWest) };
+    for &seat in seats {
+      let mut remote = self.remotes[seat].as_ref().expect("the seat was just taken").borrow_mut();
+      remote.connection.expect(&Message::ReadyForTeams(seat))?;
+      remote.connection.send(&teams)?;
+      remote.connection.expect(&Message::ReadyToStart(seat))?;
+    }
+    Ok(())
+  }
+
+  /** Starts a board: tells each connected player the deal and their cards, and returns the seats
+      they play.
+   */
+  fn deal(&mut self, board: &Board) -> Result<EnumMap<Seat, Option<RemoteSeat>>, ProtocolError> {
+    for (seat, remote) in self.remotes.iter() {
+      let mut remote = match remote {
+        Some(remote) => remote.borrow_mut(),
+        None => continue,
+      };
+      remote.calls = 0;
+      remote.cards = 0;
+      remote.error = None;
+      let connection = &mut remote.connection;
+      connection.send(&Message::StartOfBoard)?;
+      connection.expect(&Message::ReadyForDeal(seat))?;
+      connection.send(&Message::Deal { board: board.number(), dealer: board.dealer(), vulnerability: board.vulnerability() })?;
+      connection.expect(&Message::ReadyForCards(seat))?;
+      connection.send(&Message::Cards(seat, board.player_hand(seat).clone()))?;
+    }
+    Ok(enum_map! { seat => self.remotes[seat].as_ref().map(|remote| RemoteSeat { remote: remote.clone() }) })
+  }
+
+  /** The first failure of a connected player during the board, if any. */
+  fn take_error(&mut self) -> Option<ProtocolError> {
+    self.remotes.values().flatten().find_map(|remote| remote.borrow_mut().error.take())
+  }
+
+  /** Ends a board started with `deal`, passing on the calls and cards each connected player has not
+      been told yet. Reports a player whose connection failed during the board.
+   */
+  fn finish_board(&mut self, game: &BridgeGame) -> Result<(), ProtocolError> {
+    if let Some(err) = self.take_error() {
+      return Err(err);
+    }
+    for remote in self.remotes.values().flatten() {
+      let mut remote = remote.borrow_mut();
+      remote.relay_calls(game.auction())?;
+      if let Some(play) = game.play() {
+        remote.relay_cards(play, game.board())?;
+      }
+    }
+    Ok(())
+  }
+
+  /** Plays a board with the connected players in their seats and players made by `local` in the
+      others. The protocol has no claims, so every card is played.
+   */
+  pub fn play_board(&mut self, board: &Board, mut local: impl FnMut(Seat, &PlayerHand) -> Box<dyn Player>)
+                    -> Result<BridgeGame, ProtocolError> {
+    let mut remote = self.deal(board)?;
+    let mut players: EnumMap<Seat, Box<dyn Player>> = enum_map! { seat => match remote[seat].take() {
+      Some(remote) => Box::new(remote) as Box<dyn Player>,
+      None => local(seat, board.player_hand(seat)),
+    } };
+    let game = play_board_with_claims(board, &mut players, self.control, false);
+    match game {
+      Ok(game) => self.finish_board(&game).map(|_| game),
+      Err(err) => Err(self.take_error().unwrap_or(ProtocolError::Table(err))),
+    }
+  }
+
+  /** Tells the connected players that the session is over and closes their connections. */
+  pub fn end_session(&mut self) -> Result<(), ProtocolError> {
+    for remote in self.remotes.values_mut().filter_map(Option::take) {
+      remote.borrow_mut().connection.send(&Message::EndOfSession)?;
+    }
+    Ok(())
+  }
+}
+
+/** Plays one seat for a local player at a table run by a table manager elsewhere, such as in a
+    computer bridge match.
+ */
+pub struct TableClient {
+  seat: Seat,
+  connection: Connection,
+  control: TimeControl,
+}
+
+impl TableClient {
+  /** Connects to the table manager at `address` and takes `seat` for `team`, returning once the
+      table manager has named the teams.
+   */
+  pub fn connect(address: impl ToSocketAddrs, seat: Seat, team: &str) -> Result<Self, ProtocolError> {
+    let mut connection = Connection::new(TcpStream::connect(address)?)?;
+    connection.send(&Message::Connecting { team: team.to_string(), seat })?;
+    match connection.receive()? {
+      Message::Seated { seat: seated, .. } if seated == seat => {}
+      message => return Err(unexpected(message)),
+    }
+    connection.send(&Message::ReadyForTeams(seat))?;
+    match connection.receive()? {
+      Message::Teams { .. } => {}
+      message => return Err(unexpected(message)),
+    }
+    connection.send(&Message::ReadyToStart(seat))?;
+    Ok(TableClient { seat, connection, control: TimeControl::default() })
+  }
+
+  /** Sets how much the player may think in a hand. */
+  pub fn with_time_control(mut self, control: TimeControl) -> Self {
+    self.control = control;
+    self
+  }
+
+  /** Plays boards, with a new `P` for each hand, until the table manager ends the session. Returns
+      the result of each board.
+   */
+  pub fn play_session<P: Player>(&mut self) -> Result<Vec<HandResult>, ProtocolError> {
+    let mut results = Vec::new();
+    loop {
+      match self.connection.receive()? {
+        Message::StartOfBoard => results.push(self.play_board::<P>()?),
+        Message::EndOfSession => return Ok(results),
+        message => return Err(unexpected(message)),
+      }
+    }
+  }
+
+  fn play_board<P: Player>(&mut self) -> Result<HandResult, ProtocolError> {
+    let me = self.seat;
+    self.connection.send(&Message::ReadyForDeal(me))?;
+    let (number, dealer, vulnerability) = match self.connection.receive()? {
+      Message::Deal { board, dealer, vulnerability } => (board, dealer, vulnerability),
+      message => return Err(unexpected(message)),
+    };
+    self.connection.send(&Message::ReadyForCards(me))?;
+    let hand = match self.connection.receive()? {
+      Message::Cards(seat, hand) if seat == me => hand,
+      message => return Err(unexpected(message)),
+    };
+    let mut hands: EnumMap<Seat, PlayerHand> = EnumMap::default();
+    hands[me] = hand.clone();
+    let mut board = Board::from_hands(number, hands.clone());
+    let mut player = P::new(&hand);
+    player.notify_board(number, vulnerability);
+
+    let mut auction = Auction::new(dealer);
+    while !auction.is_complete() {
+      let bidder = auction.current_bidder();
+      if bidder == me {
+        let call = player.get_call(&auction);
+        if !auction.make_call(call) {
+          return Err(TableError::IllegalCall(me, call).into());
+        }
+        self.connection.send(&Message::Call(me, call))?;
+      } else {
+        self.connection.send(&Message::ReadyForCall(me, bidder))?;
+        let call = match self.connection.receive()? {
+          Message::Call(seat, call) if seat == bidder => call,
+          message => return Err(unexpected(message)),
+        };
+        if !auction.make_call(call) {
+          return Err(TableError::IllegalCall(bidder, call).into());
+        }
+      }
+    }
+    let mut play = match auction.play() {
+      Some(play) => play,
+      None => return Ok(HandResult::Passout),
+    };
+    player.notify_auction(&auction);
+
+    let declarer = play.declarer();
+    let dummy = declarer.partner();
+    while !play.is_complete() {
+      let seat = play.next_player();
+      let controller = if seat == dummy { declarer } else { seat };
+      if controller == me {
+        let card = player.get_play(&play, &board, self.control.deadline(&play));
+        if !play.legal_plays(board.player_hand(seat)).contains(&card) || !play.make_play(seat, card) {
+          return Err(TableError::IllegalPlay(seat, card).into());
+        }
+        self.connection.send(&Message::Play(seat, card))?;
+      } else {
+        let from = Some(seat).filter(|&seat| seat != dummy);
+        self.connection.send(&Message::ReadyForCard { seat: me, from, trick: play.cards_played() / 4 + 1 })?;
+        let card = match self.connection.receive()? {
+          Message::Play(player, card) if player == seat => card,
+          message => return Err(unexpected(message)),
+        };
+        if !play.make_play(seat, card) {
+          return Err(TableError::IllegalPlay(seat, card).into());
+        }
+      }
+      if play.cards_played() == 1 && me != dummy {
+        self.connection.send(&Message::ReadyForDummy(me))?;
+        hands[dummy] = match self.connection.receive()? {
+          Message::Dummy(hand) => hand,
+          message => return Err(unexpected(message)),
+        };
+        board = Board::from_hands(number, hands.clone());
+        player.notify_dummy(&hands[dummy]);
+      }
+    }
+    Ok(play.result().expect("every card was played"))
+  }
+}
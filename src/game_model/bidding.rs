@@ -1,14 +1,19 @@
 use std::cmp::Ordering;
 use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
-use enum_map::{Enum, EnumMap};
+use enum_map::{enum_map, Enum, EnumMap};
 use crate::game_model::{Seat, Vulnerability};
 use crate::game_model::cards::Suit;
+use crate::game_model::notation::Notation;
 use crate::game_model::play::Play;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::game_model::serialization::AuctionRecord"))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::game_model::serialization::AuctionRecord"))]
 pub struct Auction {
   calls: VecDeque<Call>,
+  annotations: VecDeque<Option<CallAnnotation>>,
   dealer: Seat,
   ns_declarers: EnumMap<Strain, Option<Seat>>,
   ew_declarers: EnumMap<Strain, Option<Seat>>,
@@ -24,6 +29,7 @@ impl Auction {
   pub fn new(dealer: Seat) -> Auction {
     Auction {
       calls: VecDeque::new(),
+      annotations: VecDeque::new(),
       dealer,
       ns_declarers: EnumMap::default(),
       ew_declarers: EnumMap::default(),
@@ -39,6 +45,12 @@ impl Auction {
         is modified and the function returns `false`.
    */
   pub fn make_call(&mut self, call: Call) -> bool {
+    self.make_annotated_call(call, None)
+  }
+
+  /** Adds a call as `make_call` does, along with what its side discloses about it.
+   */
+  pub fn make_annotated_call(&mut self, call: Call, annotation: Option<CallAnnotation>) -> bool {
     let success = match call {
       Call::Bid(bid) => if let Some((high_bid, _)) = self.highest_bid {
         if bid > high_bid {
@@ -76,6 +88,7 @@ impl Auction {
         if !self.current_bidder.is_opponent(bid_seat)
             && self.doubled == DoubleLevel::Doubled {
           self.doubled = DoubleLevel::Redoubled;
+          self.passes = 0;
           true
         } else {
           false // can only redouble if your side's contract is doubled
@@ -86,6 +99,7 @@ impl Auction {
     };
     if success {
       self.calls.push_back(call);
+      self.annotations.push_back(annotation);
       self.current_bidder = self.current_bidder.next_seat();
     }
     success
@@ -101,16 +115,103 @@ impl Auction {
     }
   }
 
+  /** Takes back the last call, returning it. The derived state of the auction is rebuilt by
+      replaying the remaining calls.
+   */
+  pub fn undo(&mut self) -> Option<Call> {
+    let last = self.calls.pop_back()?;
+    self.annotations.pop_back();
+    let calls = std::mem::take(&mut self.calls);
+    let annotations = std::mem::take(&mut self.annotations);
+    *self = Auction::new(self.dealer);
+    for (call, annotation) in calls.into_iter().zip(annotations) {
+      self.make_annotated_call(call, annotation);
+    }
+    Some(last)
+  }
+
+  /** Sets the annotation of the call at `index`, as when a call is explained after it was made.
+      Returns `false` if there is no such call.
+   */
+  pub fn annotate(&mut self, index: usize, annotation: CallAnnotation) -> bool {
+    match self.annotations.get_mut(index) {
+      Some(slot) => {
+        *slot = Some(annotation);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn annotation(&self, index: usize) -> Option<&CallAnnotation> {
+    self.annotations.get(index)?.as_ref()
+  }
+
+  pub fn is_alerted(&self, index: usize) -> bool {
+    self.annotation(index).is_some_and(|annotation| annotation.alert)
+  }
+
+  pub fn explanation(&self, index: usize) -> Option<&str> {
+    self.annotation(index)?.explanation.as_deref()
+  }
+
+  /** Returns the seat that made the call at `index`.
+   */
+  pub fn bidder(&self, index: usize) -> Seat {
+    (0..index % 4).fold(self.dealer, |seat, _| seat.next_seat())
+  }
+
+  /** Returns the auction as `seat` may see it. Explanations are given to the opponents, so the
+      annotations of the calls made by the seat's partner are removed.
+   */
+  pub fn disclosed_to(&self, seat: Seat) -> Auction {
+    let mut auction = self.clone();
+    for (i, annotation) in auction.annotations.iter_mut().enumerate() {
+      if self.bidder(i) == seat.partner() {
+        *annotation = None;
+      }
+    }
+    auction
+  }
+
+  pub fn calls(&self) -> impl Iterator<Item=&Call> {
+    self.calls.iter()
+  }
+
+  pub fn dealer(&self) -> Seat {
+    self.dealer
+  }
+
   pub fn current_bidder(&self) -> Seat {
     self.current_bidder
   }
 
+  /** Returns every call the current bidder may make, pass first and then the bids from the
+      cheapest, or no calls once the auction is complete.
+   */
+  pub fn legal_calls(&self) -> Vec<Call> {
+    if self.is_complete() {
+      return Vec::new();
+    }
+    let strains = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades].map(Strain::Trump).into_iter()
+        .chain([Strain::Notrump]);
+    let bids = (1..=7).flat_map(|level| strains.clone().map(move |strain| Call::Bid(Bid::from(level, strain))));
+    [Call::Pass, Call::Double, Call::Redouble].into_iter()
+        .chain(bids)
+        .filter(|&call| self.clone().make_call(call))
+        .collect()
+  }
+
   pub fn len(&self) -> usize {
     self.calls.len()
   }
 
+  pub fn is_empty(&self) -> bool {
+    self.calls.is_empty()
+  }
+
   pub fn is_complete(&self) -> bool {
-    if let Some(_) = self.highest_bid {
+    if self.highest_bid.is_some() {
       self.passes == 3 // three passes end the auction if a bid has been made
     } else {
       self.passes == 4 // four passes end the auction if no bid has been made
@@ -143,13 +244,16 @@ impl Display for Auction {
       write!(f, "        ")?;
       current_seat = current_seat.next_seat();
     }
-    for call in self.calls.iter() {
-      let call_str = match call {
+    for (i, call) in self.calls.iter().enumerate() {
+      let mut call_str = match call {
         Call::Bid(bid) => bid.to_string(),
         Call::Pass => "Pass".to_string(),
         Call::Double => "X".to_string(),
         Call::Redouble => "XX".to_string(),
       };
+      if self.is_alerted(i) {
+        call_str.push('!');
+      }
       if current_seat == Seat::West {
         writeln!(f, "{}", call_str)?;
       } else {
@@ -164,6 +268,83 @@ impl Display for Auction {
   }
 }
 
+/** The hands a call shows, as inclusive ranges of high-card points and of the length of each suit.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CallMeaning {
+  pub hcp: (u8, u8),
+  pub lengths: EnumMap<Suit, (u8, u8)>,
+}
+
+impl Default for CallMeaning {
+  fn default() -> Self {
+    CallMeaning { hcp: (0, 37), lengths: enum_map! { _ => (0, 13) } }
+  }
+}
+
+/** What a side discloses about one of its calls: whether it was alerted, an explanation in words
+    and the hands it shows.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallAnnotation {
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub alert: bool,
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+  pub explanation: Option<String>,
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+  pub meaning: Option<CallMeaning>,
+}
+
+impl CallAnnotation {
+  pub fn explained(explanation: &str) -> Self {
+    CallAnnotation { explanation: Some(explanation.to_string()), ..CallAnnotation::default() }
+  }
+
+  pub fn alerted(mut self) -> Self {
+    self.alert = true;
+    self
+  }
+
+  pub fn with_meaning(mut self, meaning: CallMeaning) -> Self {
+    self.meaning = Some(meaning);
+    self
+  }
+
+  /** Returns the text of the note that explains the call in PBN and LIN files: the explanation,
+      followed by the meaning in braces.
+   */
+  pub(crate) fn note(&self) -> Option<String> {
+    let meaning = self.meaning.map(|meaning| format!("{{{}}}", meaning.notation()));
+    match (&self.explanation, meaning) {
+      (Some(explanation), Some(meaning)) => Some(format!("{} {}", explanation, meaning)),
+      (Some(explanation), None) => Some(explanation.clone()),
+      (None, meaning) => meaning,
+    }
+  }
+
+  /** Reads an annotation back from an alert flag and the text of a note. */
+  pub(crate) fn from_note(alert: bool, note: Option<&str>) -> Option<Self> {
+    let mut annotation = CallAnnotation { alert, ..CallAnnotation::default() };
+    if let Some(note) = note.map(str::trim) {
+      let braced = note.strip_suffix('}')
+          .and_then(|rest| rest.rsplit_once('{'))
+          .and_then(|(explanation, meaning)| Some((explanation, CallMeaning::from_notation(meaning)?)));
+      let explanation = match braced {
+        Some((explanation, meaning)) => {
+          annotation.meaning = Some(meaning);
+          explanation.trim()
+        }
+        None => note,
+      };
+      if !explanation.is_empty() {
+        annotation.explanation = Some(explanation.to_string());
+      }
+    }
+    (annotation != CallAnnotation::default()).then_some(annotation)
+  }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
 pub enum Strain {
   Trump(Suit),
@@ -171,23 +352,29 @@ pub enum Strain {
 }
 
 impl Strain {
-  fn trick_score(&self, tricks: i32) -> i32 {
-    let per_trick = self.score_per_trick();
+  /** Returns the undoubled trick values of the strain, as the value of the first trick over book and
+      the value of each later trick.
+   */
+  fn trick_values(&self) -> (i32, i32) {
     match self {
-      Strain::Notrump => per_trick * tricks + 10,
-      _ => per_trick * tricks
+      Strain::Trump(Suit::Clubs) | Strain::Trump(Suit::Diamonds) => (20, 20),
+      Strain::Trump(Suit::Hearts) | Strain::Trump(Suit::Spades) => (30, 30),
+      Strain::Notrump => (40, 30),
     }
   }
 
-  fn score_per_trick(&self) -> i32 {
-    match self {
-      Strain::Trump(suit) => match suit {
-        Suit::Clubs | Suit::Diamonds => 20,
-        Suit::Hearts | Suit::Spades => 30,
-      },
-      Strain::Notrump => 30,
+  fn trick_score(&self, tricks: i32) -> i32 {
+    let (first, later) = self.trick_values();
+    if tricks <= 0 {
+      0
+    } else {
+      first + later * (tricks - 1)
     }
   }
+
+  fn score_per_trick(&self) -> i32 {
+    self.trick_values().1
+  }
 }
 
 impl PartialOrd for Strain {
@@ -203,7 +390,7 @@ impl Ord for Strain {
     } else {
       if let Strain::Trump(trump) = self {
         if let Strain::Trump(other_trump) = other { // compare the suits
-          trump.cmp(&other_trump)
+          trump.cmp(other_trump)
         } else { // other is notrump, so it's higher
           Ordering::Less
         }
@@ -236,6 +423,14 @@ impl Bid {
   pub fn from(level: u8, strain: Strain) -> Self {
     Bid { level, strain }
   }
+
+  pub fn level(&self) -> u8 {
+    self.level
+  }
+
+  pub fn strain(&self) -> Strain {
+    self.strain
+  }
 }
 
 impl Display for Bid {
@@ -245,7 +440,7 @@ impl Display for Bid {
   }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Call {
   Bid(Bid),
   Pass,
@@ -261,28 +456,42 @@ pub enum DoubleLevel {
 }
 
 impl DoubleLevel {
-  pub fn score_for_set(&self, set_by: i32, vul: bool) -> i32 {
-    if set_by <= 0 {
-      return 0;
+  fn multiplier(&self) -> i32 {
+    match self {
+      DoubleLevel::Undoubled => 1,
+      DoubleLevel::Doubled => 2,
+      DoubleLevel::Redoubled => 4,
     }
+  }
+
+  /** Returns the penalty for the first undertrick, for each of the second and third, and for each
+      undertrick after the third.
+   */
+  fn undertrick_values(&self, vul: bool) -> [i32; 3] {
     use DoubleLevel::*;
     match (self, vul) {
-      (Undoubled, false) => -50 * set_by,
-      (Undoubled, true) => -100 * set_by,
-      (Doubled, false) => match set_by {
-        1 => -100,
-        2 => -300,
-        _ => -300 * set_by + 400,
-      },
-      (Doubled, true) => -300 * set_by + 100,
-      (Redoubled, false) => match set_by {
-        1 => -200,
-        2 => -600,
-        _ => -600 * set_by + 800,
-      }
-      (Redoubled, true) => -600 * set_by + 200
+      (Undoubled, false) => [50, 50, 50],
+      (Undoubled, true) => [100, 100, 100],
+      (Doubled, false) => [100, 200, 300],
+      (Doubled, true) => [200, 300, 300],
+      (Redoubled, false) => [200, 400, 600],
+      (Redoubled, true) => [400, 600, 600],
     }
   }
+
+  /** Returns the (negative) score for going down by `set_by` tricks.
+   */
+  pub fn score_for_set(&self, set_by: i32, vul: bool) -> i32 {
+    let [first, second_and_third, later] = self.undertrick_values(vul);
+    let penalty: i32 = (1..=set_by)
+        .map(|undertrick| match undertrick {
+          1 => first,
+          2 | 3 => second_and_third,
+          _ => later,
+        })
+        .sum();
+    -penalty
+  }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -290,7 +499,7 @@ pub struct Contract(Bid, DoubleLevel, Seat);
 
 impl Contract {
   pub fn new(bid: Bid, doubled: DoubleLevel, declarer: Seat) -> Self {
-    Contract { 0: bid, 1: doubled, 2: declarer }
+    Contract(bid, doubled, declarer)
   }
 
   pub fn level(&self) -> u8 {
@@ -309,50 +518,73 @@ impl Contract {
     self.2
   }
 
+  /** Computes the duplicate score of the contract for the declaring side, where `diff` is the
+      number of tricks taken over (or, if negative, under) the contract. A making score is the sum
+      of the contracted trick score, the game or part-score bonus, the slam bonus, the bonus for
+      making a doubled contract and the overtricks.
+   */
   pub fn score(&self, diff: i8, vulnerable: Vulnerability) -> i32 {
-    let vul = match vulnerable {
-      Vulnerability::Neither => false,
-      Vulnerability::NS => self.declarer().is_opponent(Seat::East),
-      Vulnerability::EW => self.declarer().is_opponent(Seat::North),
-      Vulnerability::Both => true,
-    };
+    let vul = vulnerable.is_vulnerable(self.declarer());
     if diff < 0 {
-      self.doubled().score_for_set(-diff as i32, vul)
-    } else {
-      let trick_score = self.trick_score(self.level() as i32 + diff as i32);
-      let game_bonus = if vul { 500 } else { 300 };
-      let slam_bonus = if vul { 750 } else { 500 };
-      let grand_bonus = if vul { 1500 } else { 1000 };
-      let double_bonus = match self.doubled() {
-        DoubleLevel::Undoubled => 0,
-        DoubleLevel::Doubled => 50,
-        DoubleLevel::Redoubled => 100,
-      };
-      let bonus = match self.level() {
-        7 => grand_bonus + game_bonus,
-        6 => slam_bonus + game_bonus,
-        5 => game_bonus,
-        4 => match self.strain() {
-          Strain::Trump(Suit::Clubs) | Strain::Trump(Suit::Diamonds) => 50,
-          _ => game_bonus,
-        },
-        3 => match self.strain() {
-          Strain::Notrump => game_bonus,
-          _ => 50
-        },
-        _ => trick_score + 50,
-      };
-      bonus + trick_score + double_bonus
+      return self.doubled().score_for_set(-diff as i32, vul);
     }
+    self.contract_points()
+        + self.level_bonus(vul)
+        + self.slam_bonus(vul)
+        + self.insult_bonus()
+        + self.overtrick_points(diff as i32, vul)
   }
 
-  fn trick_score(&self, tricks: i32) -> i32 {
-    let base = self.strain().trick_score(tricks);
-    let multiplier = match self.doubled() {
-      DoubleLevel::Undoubled => 1,
-      DoubleLevel::Doubled => 2,
-      DoubleLevel::Redoubled => 4,
+  /** Returns the duplicate game bonus if the contracted trick score reaches 100, and the part-score
+      bonus otherwise.
+   */
+  pub fn level_bonus(&self, vul: bool) -> i32 {
+    match (self.contract_points() >= 100, vul) {
+      (false, _) => 50,
+      (true, false) => 300,
+      (true, true) => 500,
+    }
+  }
+
+  /** Returns the trick score of the contracted tricks. This is the part of a making score that
+      counts towards game.
+   */
+  pub fn contract_points(&self) -> i32 {
+    self.trick_score(self.level() as i32)
+  }
+
+  pub fn overtrick_points(&self, overtricks: i32, vul: bool) -> i32 {
+    let per_trick = match (self.doubled(), vul) {
+      (DoubleLevel::Undoubled, _) => self.strain().score_per_trick(),
+      (DoubleLevel::Doubled, false) => 100,
+      (DoubleLevel::Doubled, true) => 200,
+      (DoubleLevel::Redoubled, false) => 200,
+      (DoubleLevel::Redoubled, true) => 400,
     };
-    base * multiplier
+    per_trick * overtricks
+  }
+
+  /** Returns the bonus for making a doubled or redoubled contract.
+   */
+  pub fn insult_bonus(&self) -> i32 {
+    match self.doubled() {
+      DoubleLevel::Undoubled => 0,
+      DoubleLevel::Doubled => 50,
+      DoubleLevel::Redoubled => 100,
+    }
+  }
+
+  pub fn slam_bonus(&self, vul: bool) -> i32 {
+    match (self.level(), vul) {
+      (6, false) => 500,
+      (6, true) => 750,
+      (7, false) => 1000,
+      (7, true) => 1500,
+      _ => 0,
+    }
+  }
+
+  fn trick_score(&self, tricks: i32) -> i32 {
+    self.strain().trick_score(tricks) * self.doubled().multiplier()
   }
 }
@@ -20,6 +20,15 @@ impl Deck {
     Suit::iter().cartesian_product(Rank::iter()).map(|(suit, rank)| Card { suit, rank }).collect()
   }
 
+  /** Creates a deck shuffled with the given random number generator, so that a seeded generator
+      always gives the same deal.
+   */
+  pub fn shuffled<R: Rng + ?Sized>(rng: &mut R) -> Deck {
+    let mut cards: Vec<Card> = Suit::iter().cartesian_product(Rank::iter()).map(|(suit, rank)| Card { suit, rank }).collect();
+    cards.shuffle(rng);
+    Deck { cards }
+  }
+
   /** Deals a deck into four hands. This operation consumes the deck.
    */
   pub fn deal_hands(mut self) -> EnumMap<Seat, PlayerHand> {
@@ -33,6 +42,12 @@ impl Deck {
   }
 }
 
+impl Default for Deck {
+  fn default() -> Self {
+    Deck::new()
+  }
+}
+
 impl FromIterator<Card> for Deck {
   /** Collects an iterator of cards into an array, shuffles the array, then converts the array to a
                  deck. Panics if the iterator does not contain exactly 52 cards.
@@ -62,6 +77,30 @@ impl PlayerHand {
     sorted_hand
   }
 
+  pub fn len(&self) -> usize {
+    self.cards.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.cards.is_empty()
+  }
+
+  /** Returns the cards in the hand, in no particular order.
+   */
+  pub fn cards(&self) -> &[Card] {
+    &self.cards
+  }
+
+  /** Counts high-card points: 4 for an ace, 3 for a king, 2 for a queen and 1 for a jack.
+   */
+  pub fn hcp(&self) -> u8 {
+    self.cards.iter().map(|card| card.rank.hcp()).sum()
+  }
+
+  pub fn suit_length(&self, suit: Suit) -> u8 {
+    self.cards.iter().filter(|card| card.suit == suit).count() as u8
+  }
+
   pub fn has_any(&self, suit: Suit) -> bool {
     self.cards.iter().any(|card| card.suit == suit)
   }
@@ -87,7 +126,7 @@ impl PlayerHand {
 impl Display for PlayerHand {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     let sorted = self.sort();
-    let suits = vec![Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+    let suits = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
     for suit in suits.iter() {
       write!(f, "{} ", &suit.to_string())?;
       let suit_cards = sorted[*suit].iter().fold(String::new(), |mut acc, rk| {
@@ -143,17 +182,14 @@ impl Card {
   }
 }
 
+/** Cards are only ordered within a suit; cards of different suits are incomparable.
+ */
 impl PartialOrd for Card {
   fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-    let rank_order = self.rank.cmp(&other.rank);
-    if rank_order == Ordering::Equal {
-      if self == other {
-        Some(Ordering::Equal)
-      } else {
-        None
-      }
+    if self.suit == other.suit {
+      Some(self.rank.cmp(&other.rank))
     } else {
-      Some(rank_order)
+      None
     }
   }
 }
@@ -173,6 +209,33 @@ pub enum Suit {
   Spades,
 }
 
+impl Suit {
+  /** Returns the letter used for the suit in text formats such as PBN.
+   */
+  pub fn letter(&self) -> char {
+    match self {
+      Suit::Clubs => 'C',
+      Suit::Diamonds => 'D',
+      Suit::Hearts => 'H',
+      Suit::Spades => 'S',
+    }
+  }
+}
+
+impl TryFrom<char> for Suit {
+  type Error = ();
+
+  fn try_from(value: char) -> Result<Self, Self::Error> {
+    match value {
+      'C' | 'c' | '♣' => Ok(Suit::Clubs),
+      'D' | 'd' | '♦' => Ok(Suit::Diamonds),
+      'H' | 'h' | '♥' => Ok(Suit::Hearts),
+      'S' | 's' | '♠' => Ok(Suit::Spades),
+      _ => Err(())
+    }
+  }
+}
+
 impl Display for Suit {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     let suit_str = match self {
@@ -204,6 +267,16 @@ pub enum Rank {
 }
 
 impl Rank {
+  pub fn hcp(&self) -> u8 {
+    match self {
+      Rank::Ace => 4,
+      Rank::King => 3,
+      Rank::Queen => 2,
+      Rank::Jack => 1,
+      _ => 0,
+    }
+  }
+
   pub fn rank_char(&self) -> char {
     match self {
       Rank::Two => '2',
@@ -0,0 +1,165 @@
+use crate::game_model::{BridgeGame, Seat};
+use crate::game_model::bidding::{Auction, Call};
+use crate::game_model::cards::Card;
+use crate::game_model::play::Play;
+
+/** A single step of a game: a call in the auction or a card in the play.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GameEvent {
+  Call(Call),
+  Card(Seat, Card),
+}
+
+/** A recorded sequence of calls and cards with a cursor that can be moved to any point of it. The
+    auction and play always reflect the events before the cursor; events after it are kept so the
+    cursor can move forward again, until a new event is recorded in their place.
+ */
+#[derive(Debug, Clone)]
+pub struct GameHistory {
+  auction: Auction,
+  play: Option<Play>,
+  events: Vec<GameEvent>,
+  cursor: usize,
+}
+
+impl GameHistory {
+  pub fn new(dealer: Seat) -> Self {
+    GameHistory {
+      auction: Auction::new(dealer),
+      play: None,
+      events: Vec::new(),
+      cursor: 0,
+    }
+  }
+
+  /** Records every call and card of a game, leaving the cursor at the end. Claims are not part of
+      the history.
+   */
+  pub fn from_game(game: &BridgeGame) -> Self {
+    let mut history = GameHistory::new(game.auction().dealer());
+    for &call in game.auction().calls() {
+      history.record(GameEvent::Call(call));
+    }
+    if let Some(play) = game.play() {
+      for (seat, card) in play.tricks().iter().flat_map(|trick| trick.cards_in_order()) {
+        history.record(GameEvent::Card(seat, card));
+      }
+    }
+    history
+  }
+
+  pub fn auction(&self) -> &Auction {
+    &self.auction
+  }
+
+  pub fn play(&self) -> Option<&Play> {
+    self.play.as_ref()
+  }
+
+  pub fn events(&self) -> &[GameEvent] {
+    &self.events
+  }
+
+  /** Returns the number of events before the cursor.
+   */
+  pub fn cursor(&self) -> usize {
+    self.cursor
+  }
+
+  pub fn len(&self) -> usize {
+    self.events.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.events.is_empty()
+  }
+
+  /** Applies an event at the cursor. If it is legal, any events after the cursor are discarded and
+      the cursor moves past the new event.
+   */
+  pub fn record(&mut self, event: GameEvent) -> bool {
+    if !self.apply(event) {
+      return false;
+    }
+    self.events.truncate(self.cursor);
+    self.events.push(event);
+    self.cursor += 1;
+    true
+  }
+
+  /** Moves the cursor back one event. Returns `false` at the start of the game.
+   */
+  pub fn step_back(&mut self) -> bool {
+    if self.cursor == 0 {
+      return false;
+    }
+    match self.events[self.cursor - 1] {
+      GameEvent::Call(_) => {
+        self.play = None;
+        self.auction.undo();
+      }
+      GameEvent::Card(_, _) => {
+        if let Some(play) = &mut self.play {
+          play.undo();
+        }
+      }
+    }
+    self.cursor -= 1;
+    true
+  }
+
+  /** Moves the cursor forward one event. Returns `false` at the end of the recorded events.
+   */
+  pub fn step_forward(&mut self) -> bool {
+    let event = match self.events.get(self.cursor) {
+      Some(&event) => event,
+      None => return false,
+    };
+    if self.apply(event) {
+      self.cursor += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  /** Moves the cursor so that exactly `position` events have been applied. Positions past the end
+      stop at the end.
+   */
+  pub fn seek(&mut self, position: usize) {
+    while self.cursor > position && self.step_back() {}
+    while self.cursor < position && self.step_forward() {}
+  }
+
+  /** Moves the cursor to just before the `index`th call, counting from zero.
+   */
+  pub fn seek_call(&mut self, index: usize) {
+    self.seek(index);
+  }
+
+  /** Moves the cursor to just before the `index`th card played, counting from zero.
+   */
+  pub fn seek_card(&mut self, index: usize) {
+    let calls = self.events.iter().take_while(|e| matches!(e, GameEvent::Call(_))).count();
+    self.seek(calls + index);
+  }
+
+  fn apply(&mut self, event: GameEvent) -> bool {
+    match event {
+      GameEvent::Call(call) => {
+        if self.auction.is_complete() || !self.auction.make_call(call) {
+          return false;
+        }
+        if self.auction.is_complete() {
+          self.play = self.auction.play();
+        }
+        true
+      }
+      GameEvent::Card(seat, card) => match &mut self.play {
+        Some(play) => play.make_play(seat, card),
+        None => false,
+      }
+    }
+  }
+}
@@ -0,0 +1,58 @@
+use crate::game_model::Seat;
+use crate::game_model::bidding::{Auction, Call, CallAnnotation};
+use crate::game_model::notation::Notation;
+
+fn call_text(call: Call) -> String {
+  match call {
+    Call::Pass => "p".to_string(),
+    Call::Double => "d".to_string(),
+    Call::Redouble => "r".to_string(),
+    Call::Bid(bid) => bid.notation().replace("NT", "N"),
+  }
+}
+
+/** Writes the calls of an auction as LIN `mb` items, each followed by an `an` item if it is
+    explained. Alerts are marked with `!`. The dealer is not written; in LIN it is part of the deal.
+ */
+pub fn write_auction(auction: &Auction) -> String {
+  let mut text = String::new();
+  for (i, &call) in auction.calls().enumerate() {
+    let alert = if auction.is_alerted(i) { "!" } else { "" };
+    text.push_str(&format!("mb|{}{}|", call_text(call), alert));
+    if let Some(note) = auction.annotation(i).and_then(CallAnnotation::note) {
+      text.push_str(&format!("an|{}|", note.replace('|', "/")));
+    }
+  }
+  text
+}
+
+/** Reads the `mb` and `an` items of a LIN file into an auction with the given dealer. Other items
+    are skipped. Returns `None` if a call is illegal.
+ */
+pub fn read_auction(text: &str, dealer: Seat) -> Option<Auction> {
+  let mut auction = Auction::new(dealer);
+  let mut items = text.split('|').map(str::trim);
+  let mut alerts = Vec::new();
+  let mut notes = Vec::new();
+  while let Some(key) = items.next() {
+    let value = items.next().unwrap_or("");
+    match key.to_ascii_lowercase().as_str() {
+      "mb" => {
+        let call = value.trim_end_matches('!');
+        if auction.is_complete() || !auction.make_call(Call::from_notation(call)?) {
+          return None;
+        }
+        alerts.push(call.len() < value.len());
+        notes.push(None);
+      }
+      "an" => *notes.last_mut()? = Some(value),
+      _ => {}
+    }
+  }
+  for (index, (&alert, note)) in alerts.iter().zip(notes).enumerate() {
+    if let Some(annotation) = CallAnnotation::from_note(alert, note) {
+      auction.annotate(index, annotation);
+    }
+  }
+  Some(auction)
+}
@@ -1,12 +1,23 @@
 use enum_map::{Enum, EnumMap};
+use rand::Rng;
 use strum::EnumIter;
-use crate::game_model::bidding::{Auction, Contract};
-use crate::game_model::cards::{Deck, PlayerHand};
+use crate::game_model::bidding::{Auction, Call, CallAnnotation, Contract};
+use crate::game_model::cards::{Card, Deck, PlayerHand};
 use crate::game_model::play::Play;
 
 pub mod cards;
 pub mod bidding;
 pub mod play;
+pub mod scoring;
+pub mod rubber;
+pub mod history;
+pub mod notation;
+pub mod pbn;
+pub mod lin;
+#[cfg(feature = "serde")]
+pub mod record;
+#[cfg(feature = "serde")]
+mod serialization;
 
 pub struct BridgeGame {
   board: Board,
@@ -17,8 +28,11 @@ pub struct BridgeGame {
 
 impl BridgeGame {
   pub fn new(board_num: u32) -> Self {
-    let board = Board::new(board_num);
-    let auction = Auction::new(dealer(board_num));
+    BridgeGame::from_board(Board::new(board_num))
+  }
+
+  pub fn from_board(board: Board) -> Self {
+    let auction = Auction::new(board.dealer());
     BridgeGame {
       board,
       auction,
@@ -27,6 +41,78 @@ impl BridgeGame {
     }
   }
 
+  /** Attempts to add a call to the auction. When the call completes the auction, the game moves on
+      to the play, or is finished if the board was passed out. Returns `false` if the call is
+      illegal or the auction is already over.
+   */
+  pub fn make_call(&mut self, call: Call) -> bool {
+    self.make_annotated_call(call, None)
+  }
+
+  /** Adds a call as `make_call` does, along with what its side discloses about it. Players should
+      be shown the auction through `Auction::disclosed_to`.
+   */
+  pub fn make_annotated_call(&mut self, call: Call, annotation: Option<CallAnnotation>) -> bool {
+    if self.auction.is_complete() || !self.auction.make_annotated_call(call, annotation) {
+      return false;
+    }
+    if self.auction.is_complete() {
+      self.play = self.auction.play();
+      if self.play.is_none() {
+        self.result = Some(HandResult::Passout);
+      }
+    }
+    true
+  }
+
+  /** Attempts to play a card. The card must come from the seat's hand, must not have been played
+      already and must follow suit if the seat can. When the last card is played, the result of the
+      hand is recorded.
+   */
+  pub fn make_play(&mut self, seat: Seat, card: Card) -> bool {
+    let legal = match self.play() {
+      Some(play) => play.legal_plays(self.board.player_hand(seat)).contains(&card),
+      None => false,
+    };
+    legal && self.update_play(|play| play.make_play(seat, card))
+  }
+
+  pub fn claim(&mut self, seat: Seat, tricks: u8, statement: &str) -> bool {
+    self.update_play(|play| play.claim(seat, tricks, statement))
+  }
+
+  pub fn concede(&mut self, seat: Seat, tricks: u8) -> bool {
+    self.update_play(|play| play.concede(seat, tricks))
+  }
+
+  pub fn accept_claim(&mut self, seat: Seat) -> bool {
+    self.update_play(|play| play.accept_claim(seat))
+  }
+
+  pub fn dispute_claim(&mut self, seat: Seat) -> bool {
+    self.update_play(|play| play.dispute_claim(seat))
+  }
+
+  /** Applies an action to the play, recording the result of the hand if the action ends it.
+   */
+  fn update_play(&mut self, action: impl FnOnce(&mut Play) -> bool) -> bool {
+    let play = match &mut self.play {
+      Some(play) => play,
+      None => return false,
+    };
+    if !action(play) {
+      return false;
+    }
+    if play.is_complete() {
+      self.result = play.result();
+    }
+    true
+  }
+
+  pub fn is_complete(&self) -> bool {
+    self.result.is_some()
+  }
+
   pub fn board(&self) -> &Board {
     &self.board
   }
@@ -34,24 +120,67 @@ impl BridgeGame {
   pub fn player_hand(&self, seat: Seat) -> &PlayerHand {
     self.board().player_hand(seat)
   }
+
+  pub fn auction(&self) -> &Auction {
+    &self.auction
+  }
+
+  pub fn play(&self) -> Option<&Play> {
+    self.play.as_ref()
+  }
+
+  pub fn result(&self) -> Option<&HandResult> {
+    self.result.as_ref()
+  }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialization::BoardRecord"))]
 pub struct Board {
   hands: EnumMap<Seat, PlayerHand>,
   number: u32,
+  dealer: Seat,
+  vulnerability: Vulnerability,
 }
 
 impl Board {
   pub fn new(number: u32) -> Self {
     let deck = Deck::new();
-    let hands = deck.deal_hands();
-    Board { hands, number }
+    Board::from_hands(number, deck.deal_hands())
+  }
+
+  /** Creates a board dealt with the given random number generator. */
+  pub fn random<R: Rng + ?Sized>(number: u32, rng: &mut R) -> Self {
+    Board::from_hands(number, Deck::shuffled(rng).deal_hands())
+  }
+
+  /** Creates a board with a prearranged deal.
+   */
+  pub fn from_hands(number: u32, hands: EnumMap<Seat, PlayerHand>) -> Self {
+    Board {
+      hands,
+      number,
+      dealer: dealer(number),
+      vulnerability: vulnerability(number),
+    }
   }
 
   pub fn player_hand(&self, seat: Seat) -> &PlayerHand {
     &self.hands[seat]
   }
+
+  pub fn number(&self) -> u32 {
+    self.number
+  }
+
+  pub fn dealer(&self) -> Seat {
+    self.dealer
+  }
+
+  pub fn vulnerability(&self) -> Vulnerability {
+    self.vulnerability
+  }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Enum, EnumIter)]
@@ -73,6 +202,13 @@ impl Seat {
     }
   }
 
+  pub fn side(&self) -> Side {
+    match self {
+      Seat::North | Seat::South => Side::NorthSouth,
+      Seat::East | Seat::West => Side::EastWest,
+    }
+  }
+
   pub fn is_opponent(&self, other: Seat) -> bool {
     use Seat::*;
     match self {
@@ -105,7 +241,22 @@ impl Seat {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum, EnumIter)]
+pub enum Side {
+  NorthSouth,
+  EastWest,
+}
+
+impl Side {
+  pub fn opponents(&self) -> Side {
+    match self {
+      Side::NorthSouth => Side::EastWest,
+      Side::EastWest => Side::NorthSouth,
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum HandResult {
   Passout,
   Played(Contract, i8),
@@ -118,9 +269,22 @@ impl HandResult {
       HandResult::Played(contract, diff) => contract.score(*diff, vul)
     }
   }
+
+  /** Returns the score of this result from North-South's point of view.
+   */
+  pub fn ns_score(&self, vul: Vulnerability) -> i32 {
+    match self {
+      HandResult::Passout => 0,
+      HandResult::Played(contract, _) => if contract.declarer().is_opponent(Seat::North) {
+        -self.score(vul)
+      } else {
+        self.score(vul)
+      }
+    }
+  }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Vulnerability {
   Neither,
   NS,
@@ -128,6 +292,19 @@ pub enum Vulnerability {
   Both
 }
 
+impl Vulnerability {
+  /** Returns whether the partnership containing `seat` is vulnerable.
+   */
+  pub fn is_vulnerable(&self, seat: Seat) -> bool {
+    match self {
+      Vulnerability::Neither => false,
+      Vulnerability::NS => matches!(seat, Seat::North | Seat::South),
+      Vulnerability::EW => matches!(seat, Seat::East | Seat::West),
+      Vulnerability::Both => true,
+    }
+  }
+}
+
 pub fn dealer(board_num: u32) -> Seat {
   match board_num % 4 {
     0 => Seat::West,
@@ -137,3 +314,17 @@ pub fn dealer(board_num: u32) -> Seat {
     _ => unreachable!()
   }
 }
+
+/** Returns the vulnerability of a duplicate board. The standard cycle repeats every 16 boards, and
+    each group of four boards rotates the first four vulnerabilities one step further.
+ */
+pub fn vulnerability(board_num: u32) -> Vulnerability {
+  use Vulnerability::*;
+  const CYCLE: [Vulnerability; 16] = [
+    Neither, NS, EW, Both,
+    NS, EW, Both, Neither,
+    EW, Both, Neither, NS,
+    Both, Neither, NS, EW,
+  ];
+  CYCLE[((board_num + 15) % 16) as usize]
+}
@@ -0,0 +1,348 @@
+use strum::IntoEnumIterator;
+use crate::game_model::{HandResult, Seat, Vulnerability};
+use crate::game_model::bidding::{Bid, Call, CallMeaning, Contract, DoubleLevel, Strain};
+use crate::game_model::cards::{Card, PlayerHand, Rank, Suit};
+
+/** Compact ASCII notation for the game model, as used in game records: `"SA"` for a card, `"3NT"`
+    for a bid, `"P"`, `"X"` and `"XX"` for the other calls and `"N"` for a seat. Parsing is lenient
+    about case and also accepts the suit symbols printed by `Display`.
+ */
+pub trait Notation: Sized {
+  fn notation(&self) -> String;
+
+  fn from_notation(text: &str) -> Option<Self>;
+}
+
+impl Notation for Suit {
+  fn notation(&self) -> String {
+    self.letter().to_string()
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+      (Some(c), None) => Suit::try_from(c).ok(),
+      _ => None,
+    }
+  }
+}
+
+impl Notation for Rank {
+  fn notation(&self) -> String {
+    self.rank_char().to_string()
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+      (Some(c), None) => Rank::try_from(c).ok(),
+      _ => None,
+    }
+  }
+}
+
+impl Notation for Card {
+  fn notation(&self) -> String {
+    format!("{}{}", self.suit().letter(), self.rank().rank_char())
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+      (Some(suit), Some(rank), None) => {
+        Some(Card::from(Suit::try_from(suit).ok()?, Rank::try_from(rank).ok()?))
+      }
+      _ => None,
+    }
+  }
+}
+
+impl Notation for Seat {
+  fn notation(&self) -> String {
+    match self {
+      Seat::North => "N",
+      Seat::East => "E",
+      Seat::South => "S",
+      Seat::West => "W",
+    }.to_string()
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    match text.to_ascii_uppercase().as_str() {
+      "N" => Some(Seat::North),
+      "E" => Some(Seat::East),
+      "S" => Some(Seat::South),
+      "W" => Some(Seat::West),
+      _ => None,
+    }
+  }
+}
+
+impl Notation for Strain {
+  fn notation(&self) -> String {
+    match self {
+      Strain::Trump(suit) => suit.notation(),
+      Strain::Notrump => "NT".to_string(),
+    }
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    match text.to_ascii_uppercase().as_str() {
+      "NT" | "N" => Some(Strain::Notrump),
+      _ => Suit::from_notation(text).map(Strain::Trump),
+    }
+  }
+}
+
+impl Notation for Bid {
+  fn notation(&self) -> String {
+    format!("{}{}", self.level(), self.strain().notation())
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    let mut chars = text.chars();
+    let level = chars.next()?.to_digit(10)? as u8;
+    if !(1..=7).contains(&level) {
+      return None;
+    }
+    Some(Bid::from(level, Strain::from_notation(chars.as_str())?))
+  }
+}
+
+impl Notation for Call {
+  fn notation(&self) -> String {
+    match self {
+      Call::Bid(bid) => bid.notation(),
+      Call::Pass => "P".to_string(),
+      Call::Double => "X".to_string(),
+      Call::Redouble => "XX".to_string(),
+    }
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    match text.to_ascii_uppercase().as_str() {
+      "P" | "PASS" => Some(Call::Pass),
+      "X" | "D" | "DBL" => Some(Call::Double),
+      "XX" | "R" | "RDBL" => Some(Call::Redouble),
+      _ => Bid::from_notation(text).map(Call::Bid),
+    }
+  }
+}
+
+impl Notation for DoubleLevel {
+  fn notation(&self) -> String {
+    match self {
+      DoubleLevel::Undoubled => "",
+      DoubleLevel::Doubled => "X",
+      DoubleLevel::Redoubled => "XX",
+    }.to_string()
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    match text.to_ascii_uppercase().as_str() {
+      "" => Some(DoubleLevel::Undoubled),
+      "X" => Some(DoubleLevel::Doubled),
+      "XX" => Some(DoubleLevel::Redoubled),
+      _ => None,
+    }
+  }
+}
+
+/** A contract is written as the bid, any doubling and the declarer: `"4SX N"`.
+ */
+impl Notation for Contract {
+  fn notation(&self) -> String {
+    format!("{}{}{} {}", self.level(), self.strain().notation(), self.doubled().notation(),
+            self.declarer().notation())
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    let (bid, declarer) = text.split_once(' ')?;
+    let bid_end = bid.trim_end_matches(['X', 'x']).len();
+    let (bid, doubled) = bid.split_at(bid_end);
+    Some(Contract::new(
+      Bid::from_notation(bid)?,
+      DoubleLevel::from_notation(doubled)?,
+      Seat::from_notation(declarer.trim())?,
+    ))
+  }
+}
+
+/** A result is written as the contract followed by the tricks over or under it, as in
+    `"4S N +1"`, `"3NT S ="` or `"2HX E -2"`, or as `"Passout"`.
+ */
+impl Notation for HandResult {
+  fn notation(&self) -> String {
+    match self {
+      HandResult::Passout => "Passout".to_string(),
+      HandResult::Played(contract, 0) => format!("{} =", contract.notation()),
+      HandResult::Played(contract, diff) => format!("{} {:+}", contract.notation(), diff),
+    }
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    if text.eq_ignore_ascii_case("passout") {
+      return Some(HandResult::Passout);
+    }
+    let (contract, diff) = text.rsplit_once(' ')?;
+    let contract = Contract::from_notation(contract)?;
+    let diff = if diff == "=" {
+      0
+    } else if diff.starts_with(['+', '-']) {
+      diff.parse::<i8>().ok()?
+    } else {
+      return None;
+    };
+    let tricks = contract.level() as i8 + 6 + diff;
+    if (0..=13).contains(&tricks) {
+      Some(HandResult::Played(contract, diff))
+    } else {
+      None
+    }
+  }
+}
+
+/** Vulnerability is written as in PBN: `"None"`, `"NS"`, `"EW"` or `"All"`.
+ */
+impl Notation for Vulnerability {
+  fn notation(&self) -> String {
+    match self {
+      Vulnerability::Neither => "None",
+      Vulnerability::NS => "NS",
+      Vulnerability::EW => "EW",
+      Vulnerability::Both => "All",
+    }.to_string()
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    match text.to_ascii_uppercase().as_str() {
+      "NONE" | "LOVE" | "-" => Some(Vulnerability::Neither),
+      "NS" => Some(Vulnerability::NS),
+      "EW" => Some(Vulnerability::EW),
+      "ALL" | "BOTH" => Some(Vulnerability::Both),
+      _ => None,
+    }
+  }
+}
+
+/** A hand is written as in PBN, with the ranks of each suit from spades down to clubs separated by
+    dots: `"AKQ2.T98.765.432"`. A void is an empty suit.
+ */
+impl Notation for PlayerHand {
+  fn notation(&self) -> String {
+    let sorted = self.sort();
+    Suit::iter()
+        .rev()
+        .map(|suit| sorted[suit].iter().map(Rank::rank_char).collect::<String>())
+        .collect::<Vec<_>>()
+        .join(".")
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    let holdings: Vec<&str> = text.split('.').collect();
+    if holdings.len() != 4 {
+      return None;
+    }
+    let mut hand = PlayerHand::default();
+    for (suit, holding) in Suit::iter().rev().zip(holdings) {
+      for c in holding.chars().filter(|&c| c != '-') {
+        let card = Card::from(suit, Rank::try_from(c).ok()?);
+        if hand.has_card(card) {
+          return None;
+        }
+        hand.add_card(card);
+      }
+    }
+    Some(hand)
+  }
+}
+
+fn range_notation(min: u8, max: u8, top: u8) -> String {
+  if min == max {
+    min.to_string()
+  } else if max >= top {
+    format!("{}+", min)
+  } else {
+    format!("{}-{}", min, max)
+  }
+}
+
+fn range_from_notation(text: &str, top: u8) -> Option<(u8, u8)> {
+  let range = if let Some(min) = text.strip_suffix('+') {
+    (min.parse().ok()?, top)
+  } else if let Some((min, max)) = text.split_once('-') {
+    (min.parse().ok()?, max.parse().ok()?)
+  } else {
+    let exact = text.parse().ok()?;
+    (exact, exact)
+  };
+  (range.0 <= range.1 && range.1 <= top).then_some(range)
+}
+
+/** A meaning is written as the ranges it restricts, separated by commas, in the syntax of bidding
+    system files: `"hcp 15-17, 2-5 S, 5+ H"`. A meaning that shows nothing is `"any"`.
+ */
+impl Notation for CallMeaning {
+  fn notation(&self) -> String {
+    let mut parts = Vec::new();
+    if self.hcp != (0, 37) {
+      parts.push(format!("hcp {}", range_notation(self.hcp.0, self.hcp.1, 37)));
+    }
+    for suit in Suit::iter().rev() {
+      let (min, max) = self.lengths[suit];
+      if (min, max) != (0, 13) {
+        parts.push(format!("{} {}", range_notation(min, max, 13), suit.notation()));
+      }
+    }
+    if parts.is_empty() {
+      "any".to_string()
+    } else {
+      parts.join(", ")
+    }
+  }
+
+  fn from_notation(text: &str) -> Option<Self> {
+    let mut meaning = CallMeaning::default();
+    for part in text.split(',').map(str::trim) {
+      if part.eq_ignore_ascii_case("any") {
+        continue;
+      }
+      let (first, second) = part.split_once(' ')?;
+      if first.eq_ignore_ascii_case("hcp") {
+        meaning.hcp = range_from_notation(second.trim(), 37)?;
+      } else {
+        meaning.lengths[Suit::from_notation(second.trim())?] = range_from_notation(first, 13)?;
+      }
+    }
+    Some(meaning)
+  }
+}
+
+/** Implements `Serialize` and `Deserialize` through the type's notation, so that it appears in
+    records as a short string.
+ */
+#[cfg(feature = "serde")]
+macro_rules! serde_via_notation {
+  ($($t:ty),*) => {
+    $(
+      impl serde::Serialize for $t {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+          serializer.serialize_str(&self.notation())
+        }
+      }
+
+      impl<'de> serde::Deserialize<'de> for $t {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+          let text = String::deserialize(deserializer)?;
+          <$t>::from_notation(&text).ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid {}: {:?}", stringify!($t), text))
+          })
+        }
+      }
+    )*
+  };
+}
+
+#[cfg(feature = "serde")]
+serde_via_notation!(Suit, Rank, Card, Seat, Strain, Bid, Call, CallMeaning, DoubleLevel, Contract,
+                    HandResult, Vulnerability, PlayerHand);
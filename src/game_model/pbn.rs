@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use crate::game_model::Seat;
+use crate::game_model::bidding::{Auction, Call, CallAnnotation};
+use crate::game_model::notation::Notation;
+
+fn call_text(call: Call) -> String {
+  match call {
+    Call::Pass => "Pass".to_string(),
+    _ => call.notation(),
+  }
+}
+
+/** Writes the auction section of a PBN game: the `Auction` tag, the calls four to a line and a
+    `Note` tag for each explained call. Alerts are marked with `!` and notes are referred to as
+    `=1=`, `=2=` and so on.
+ */
+pub fn write_auction(auction: &Auction) -> String {
+  let mut text = format!("[Auction \"{}\"]\n", auction.dealer().notation());
+  let mut notes = Vec::new();
+  let calls: Vec<String> = auction.calls().enumerate().map(|(i, &call)| {
+    let mut token = call_text(call);
+    if auction.is_alerted(i) {
+      token.push('!');
+    }
+    if let Some(note) = auction.annotation(i).and_then(CallAnnotation::note) {
+      notes.push(note);
+      token.push_str(&format!(" ={}=", notes.len()));
+    }
+    token
+  }).collect();
+  for line in calls.chunks(4) {
+    text.push_str(&line.join(" "));
+    text.push('\n');
+  }
+  for (i, note) in notes.iter().enumerate() {
+    text.push_str(&format!("[Note \"{}:{}\"]\n", i + 1, note));
+  }
+  text
+}
+
+fn tag_value<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+  let rest = line.trim().strip_prefix('[')?.strip_prefix(name)?;
+  rest.trim().strip_suffix(']')?.trim().strip_prefix('"')?.strip_suffix('"')
+}
+
+/** Reads the auction section of a PBN game, which may be embedded in the rest of the game's tags.
+    `AP` ends the auction with passes. Returns `None` if there is no auction or a call is illegal.
+ */
+pub fn read_auction(text: &str) -> Option<Auction> {
+  let mut lines = text.lines();
+  let dealer = lines.by_ref().find_map(|line| tag_value(line, "Auction"))?;
+  let mut auction = Auction::new(Seat::from_notation(dealer)?);
+  let mut alerts = Vec::new();
+  let mut references = Vec::new();
+  let mut notes = BTreeMap::new();
+  let mut in_section = true;
+  for line in lines {
+    if line.trim_start().starts_with('[') {
+      in_section = false;
+      if let Some((number, note)) = tag_value(line, "Note").and_then(|note| note.split_once(':')) {
+        notes.insert(number.trim().to_string(), note.to_string());
+      }
+      continue;
+    }
+    if !in_section {
+      continue;
+    }
+    for token in line.split_whitespace() {
+      if let Some(number) = token.strip_prefix('=').and_then(|t| t.strip_suffix('=')) {
+        references.push((auction.len().checked_sub(1)?, number.to_string()));
+      } else if token.eq_ignore_ascii_case("AP") {
+        while !auction.is_complete() {
+          auction.make_call(Call::Pass);
+          alerts.push(false);
+        }
+      } else if token == "*" || token == "-" || token.starts_with('$') {
+        continue;
+      } else {
+        let call = token.trim_end_matches('!');
+        if auction.is_complete() || !auction.make_call(Call::from_notation(call)?) {
+          return None;
+        }
+        alerts.push(call.len() < token.len());
+      }
+    }
+  }
+  let mut noted: BTreeMap<usize, &str> = BTreeMap::new();
+  for (index, number) in &references {
+    noted.insert(*index, notes.get(number).map(String::as_str)?);
+  }
+  for (index, &alert) in alerts.iter().enumerate() {
+    if let Some(annotation) = CallAnnotation::from_note(alert, noted.get(&index).copied()) {
+      auction.annotate(index, annotation);
+    }
+  }
+  Some(auction)
+}
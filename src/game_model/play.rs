@@ -3,27 +3,38 @@ use std::ops::{Index, IndexMut};
 use enum_map::EnumMap;
 use crate::game_model::{HandResult, Seat};
 use crate::game_model::bidding::{Contract, Strain};
-use crate::game_model::cards::Card;
+use crate::game_model::cards::{Card, PlayerHand};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::game_model::serialization::PlayRecord"))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::game_model::serialization::PlayRecord"))]
 pub struct Play {
   contract: Contract,
   tricks: Vec<Trick>,
   declarer_tricks: u8,
   defense_tricks: u8,
+  claim: Option<Claim>,
 }
 
 impl Play {
   pub fn new(contract: Contract) -> Self {
     Play {
       contract,
-      tricks: Vec::new(),
+      tricks: Vec::with_capacity(13), // reserved up front so that make/undo never allocate
       declarer_tricks: 0,
       defense_tricks: 0,
+      claim: None,
     }
   }
 
+  /** Attempts to play a card. The card is rejected if it is not `seat`'s turn, or if play is over
+      or suspended by a claim.
+   */
   pub fn make_play(&mut self, seat: Seat, card: Card) -> bool {
+    if self.is_complete() || self.pending_claim().is_some() || seat != self.next_player() {
+      return false;
+    }
     // this play is a new trick if there is no incomplete trick in the trick vector
     if usize::from(self.declarer_tricks + self.defense_tricks) == self.tricks.len() {
       self.make_lead(seat, card)
@@ -56,6 +67,64 @@ impl Play {
     true
   }
 
+  /** Takes back the last card played, returning who played it. Any claim is withdrawn first. This
+      never allocates, so solvers can use `make_play` and `undo` as make/unmake.
+   */
+  pub fn undo(&mut self) -> Option<(Seat, Card)> {
+    if self.claim.take().is_some() {
+      self.count_tricks();
+    }
+    let strain = self.contract.strain();
+    let trick = self.tricks.last_mut()?;
+    if let Some(winner) = trick.winner(strain) {
+      if winner.is_opponent(self.contract.declarer()) {
+        self.defense_tricks -= 1;
+      } else {
+        self.declarer_tricks -= 1;
+      }
+    }
+    let seat = trick.last_player()?;
+    let card = trick[seat].take()?;
+    if trick.card_count() == 0 {
+      self.tricks.pop();
+    }
+    Some((seat, card))
+  }
+
+  fn count_tricks(&mut self) {
+    let strain = self.contract.strain();
+    let declarer = self.contract.declarer();
+    self.declarer_tricks = 0;
+    self.defense_tricks = 0;
+    for winner in self.tricks.iter().filter_map(|trick| trick.winner(strain)) {
+      if winner.is_opponent(declarer) {
+        self.defense_tricks += 1;
+      } else {
+        self.declarer_tricks += 1;
+      }
+    }
+  }
+
+  /** Returns the cards of `hand`, the hand of the seat due to play, that may be played next: those
+      not yet played, following suit to the trick in progress if the hand can.
+   */
+  pub fn legal_plays(&self, hand: &PlayerHand) -> Vec<Card> {
+    let mut cards: Vec<Card> = hand.cards().iter().copied()
+        .filter(|&card| self.tricks.iter().all(|trick| trick.cards.values().all(|&played| played != Some(card))))
+        .collect();
+    let led = self.tricks.last().filter(|trick| trick.card_count() < 4).and_then(|trick| trick[trick.leader]);
+    if let Some(led) = led.filter(|led| cards.iter().any(|card| card.suit() == led.suit())) {
+      cards.retain(|card| card.suit() == led.suit());
+    }
+    cards
+  }
+
+  /** Returns the number of cards played so far.
+   */
+  pub fn cards_played(&self) -> usize {
+    self.tricks.iter().map(Trick::card_count).sum()
+  }
+
   pub fn contract(&self) -> Contract {
     self.contract
   }
@@ -64,6 +133,129 @@ impl Play {
     self.declarer_tricks
   }
 
+  pub fn defense_tricks(&self) -> u8 {
+    self.defense_tricks
+  }
+
+  /** Returns the number of tricks not yet won by either side, including a trick in progress.
+   */
+  pub fn remaining_tricks(&self) -> u8 {
+    13 - self.declarer_tricks - self.defense_tricks
+  }
+
+  /** Returns the seat due to play next: the opening leader, the winner of the last trick, or the
+      next seat around an incomplete trick.
+   */
+  pub fn next_player(&self) -> Seat {
+    match self.tricks.last() {
+      None => self.declarer().next_seat(),
+      Some(trick) => trick.winner(self.contract.strain()).unwrap_or_else(|| {
+        let mut seat = trick.leader();
+        while trick[seat].is_some() {
+          seat = seat.next_seat();
+        }
+        seat
+      }),
+    }
+  }
+
+  /** Claims `tricks` of the remaining tricks for the claimer's side. Play is suspended until the
+      claim is accepted or disputed. Dummy cannot claim, and a claim cannot be made while another is
+      pending.
+   */
+  pub fn claim(&mut self, seat: Seat, tricks: u8, statement: &str) -> bool {
+    if self.is_complete()
+        || self.claim.is_some()
+        || seat == self.declarer().partner()
+        || tricks > self.remaining_tricks() {
+      return false;
+    }
+    self.claim = Some(Claim {
+      claimer: seat,
+      tricks,
+      statement: statement.to_string(),
+      concession: false,
+      accepted_by: EnumMap::default(),
+    });
+    true
+  }
+
+  /** Concedes `tricks` of the remaining tricks to the opponents. A concession takes effect
+      immediately.
+   */
+  pub fn concede(&mut self, seat: Seat, tricks: u8) -> bool {
+    if tricks > self.remaining_tricks() {
+      return false;
+    }
+    let remaining = self.remaining_tricks();
+    if !self.claim(seat, remaining - tricks, "concedes") {
+      return false;
+    }
+    if let Some(claim) = &mut self.claim {
+      claim.concession = true;
+    }
+    self.settle_claim();
+    true
+  }
+
+  pub fn pending_claim(&self) -> Option<&Claim> {
+    self.claim.as_ref().filter(|_| !self.is_complete())
+  }
+
+  /** Returns the claim that ended the play, if any.
+   */
+  pub fn settled_claim(&self) -> Option<&Claim> {
+    self.claim.as_ref().filter(|_| self.is_complete())
+  }
+
+  /** Records that an opponent of the claimer accepts the pending claim. The claim takes effect once
+      every opponent who is not dummy has accepted it.
+   */
+  pub fn accept_claim(&mut self, seat: Seat) -> bool {
+    let declarer = self.declarer();
+    let pending = self.is_responder_to_pending_claim(seat);
+    let claim = match &mut self.claim {
+      Some(claim) if pending => claim,
+      _ => return false,
+    };
+    claim.accepted_by[seat] = true;
+    let all_accepted = claim_responders(claim.claimer, declarer)
+        .all(|responder| claim.accepted_by[responder]);
+    if all_accepted {
+      self.settle_claim();
+    }
+    true
+  }
+
+  /** Disputes the pending claim. The claim is withdrawn and play continues.
+   */
+  pub fn dispute_claim(&mut self, seat: Seat) -> bool {
+    if self.is_responder_to_pending_claim(seat) {
+      self.claim = None;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn is_responder_to_pending_claim(&self, seat: Seat) -> bool {
+    self.pending_claim().is_some_and(|claim| claim.is_responder(seat, self.declarer()))
+  }
+
+  fn settle_claim(&mut self) {
+    if let Some(claim) = &self.claim {
+      let remaining = self.remaining_tricks();
+      let (claimed, conceded) = (claim.tricks, remaining - claim.tricks);
+      if claim.claimer.is_opponent(self.declarer()) {
+        self.defense_tricks += claimed;
+        self.declarer_tricks += conceded;
+      } else {
+        self.declarer_tricks += claimed;
+        self.defense_tricks += conceded;
+      }
+    }
+  }
+
   pub fn declarer(&self) -> Seat {
     self.contract.declarer()
   }
@@ -88,16 +280,101 @@ impl Play {
   }
 }
 
-#[derive(Debug)]
+/** A claim of some of the remaining tricks, with a statement of how they will be won.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Claim {
+  claimer: Seat,
+  tricks: u8,
+  statement: String,
+  concession: bool,
+  accepted_by: EnumMap<Seat, bool>,
+}
+
+impl Claim {
+  pub fn claimer(&self) -> Seat {
+    self.claimer
+  }
+
+  pub fn tricks(&self) -> u8 {
+    self.tricks
+  }
+
+  pub fn statement(&self) -> &str {
+    &self.statement
+  }
+
+  /** Returns whether the claim was a concession, which takes effect without being accepted. */
+  pub fn is_concession(&self) -> bool {
+    self.concession
+  }
+
+  pub fn is_accepted_by(&self, seat: Seat) -> bool {
+    self.accepted_by[seat]
+  }
+
+  /** Returns whether `seat` is one of the players who must accept the claim: both defenders when
+      the declaring side claims, and declarer when a defender claims.
+   */
+  pub fn is_responder(&self, seat: Seat, declarer: Seat) -> bool {
+    claim_responders(self.claimer, declarer).any(|responder| responder == seat)
+  }
+}
+
+fn claim_responders(claimer: Seat, declarer: Seat) -> impl Iterator<Item=Seat> {
+  let dummy = declarer.partner();
+  [claimer.next_seat(), claimer.prev_seat()].into_iter().filter(move |&seat| seat != dummy)
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::game_model::serialization::TrickRecord"))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::game_model::serialization::TrickRecord"))]
 pub struct Trick {
   cards: EnumMap<Seat, Option<Card>>,
   leader: Seat,
 }
 
 impl Trick {
+  /** Builds a trick from the cards played to it in order, starting with the lead. Returns `None`
+      if there are no cards or more than four.
+   */
+  #[cfg(feature = "serde")]
+  pub(crate) fn from_cards(leader: Seat, cards: &[Card]) -> Option<Self> {
+    if cards.is_empty() || cards.len() > 4 {
+      return None;
+    }
+    let mut trick = Trick { cards: EnumMap::default(), leader };
+    let mut seat = leader;
+    for &card in cards {
+      trick[seat] = Some(card);
+      seat = seat.next_seat();
+    }
+    Some(trick)
+  }
+
   pub fn leader(&self) -> Seat {
     self.leader
   }
+
+  pub fn card_count(&self) -> usize {
+    self.cards.values().filter(|card| card.is_some()).count()
+  }
+
+  /** Returns the cards of the trick in the order they were played.
+   */
+  pub fn cards_in_order(&self) -> impl Iterator<Item=(Seat, Card)> + '_ {
+    let mut seat = self.leader;
+    (0..4).map_while(move |_| {
+      let played = self.cards[seat].map(|card| (seat, card));
+      seat = seat.next_seat();
+      played
+    })
+  }
+
+  fn last_player(&self) -> Option<Seat> {
+    self.cards_in_order().last().map(|(seat, _)| seat)
+  }
 }
 
 impl Trick {
@@ -111,12 +388,9 @@ impl Trick {
       let mut seat = self.leader.next_seat();
       while seat != self.leader {
         let card = self.cards[seat].unwrap();
-        match best.compare_with_trump(card, trump) {
-          Ordering::Less => {
-            best = card;
-            winner = seat;
-          },
-          _ => {}
+        if best.compare_with_trump(card, trump) == Ordering::Less {
+          best = card;
+          winner = seat;
         }
         seat = seat.next_seat()
       }
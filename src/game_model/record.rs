@@ -0,0 +1,152 @@
+use std::fmt::{Display, Formatter};
+use enum_map::EnumMap;
+use serde::{Deserialize, Serialize};
+use crate::game_model::{Board, BridgeGame, HandResult, Seat};
+use crate::game_model::bidding::Auction;
+use crate::game_model::play::Play;
+
+/** The version of the record format written by this crate. It is bumped whenever a change would
+    stop older readers from understanding a record; the schema for each version is kept in
+    `schema/`.
+ */
+pub const RECORD_VERSION: u32 = 1;
+
+/** A complete record of a game as JSON: the board, the players, when it was played, every call and
+    card, the result and any notes attached to it. Timestamps are seconds since the Unix epoch.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+  pub version: u32,
+  pub board: Board,
+  #[serde(default)]
+  pub players: EnumMap<Seat, Option<String>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub started_at: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub finished_at: Option<u64>,
+  pub auction: Auction,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub play: Option<Play>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub result: Option<HandResult>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+}
+
+/** A note on a game. It may refer to the game as a whole, or to a call or card by its index,
+    counting from zero.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+  pub target: AnnotationTarget,
+  pub text: String,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationTarget {
+  Game,
+  Call(usize),
+  Card(usize),
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+  Json(serde_json::Error),
+  /** The record was written by a newer version of the format. */
+  UnsupportedVersion(u32),
+  /** The calls and cards in the record cannot be played on its board. */
+  IllegalGame(String),
+}
+
+impl Display for RecordError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RecordError::Json(err) => write!(f, "invalid game record: {}", err),
+      RecordError::UnsupportedVersion(version) => {
+        write!(f, "game record version {} is newer than {}", version, RECORD_VERSION)
+      }
+      RecordError::IllegalGame(reason) => write!(f, "illegal game record: {}", reason),
+    }
+  }
+}
+
+impl std::error::Error for RecordError {}
+
+impl From<serde_json::Error> for RecordError {
+  fn from(err: serde_json::Error) -> Self {
+    RecordError::Json(err)
+  }
+}
+
+impl GameRecord {
+  pub fn from_game(game: &BridgeGame) -> Self {
+    GameRecord {
+      version: RECORD_VERSION,
+      board: game.board().clone(),
+      players: EnumMap::default(),
+      started_at: None,
+      finished_at: None,
+      auction: game.auction().clone(),
+      play: game.play().cloned(),
+      result: game.result().copied(),
+      annotations: Vec::new(),
+    }
+  }
+
+  /** Rebuilds the game by replaying the record on its board. Fails if a call or card could not have
+      been made, or if the recorded result does not match the play.
+   */
+  pub fn to_game(&self) -> Result<BridgeGame, RecordError> {
+    let illegal = |reason: String| Err(RecordError::IllegalGame(reason));
+    let mut game = BridgeGame::from_board(self.board.clone());
+    for (i, &call) in self.auction.calls().enumerate() {
+      if !game.make_annotated_call(call, self.auction.annotation(i).cloned()) {
+        return illegal(format!("call {} ({:?}) is not legal", i + 1, call));
+      }
+    }
+    match (game.play.is_some(), &self.play) {
+      (true, Some(play)) => {
+        if game.play().map(Play::contract) != Some(play.contract()) {
+          return illegal("the play does not match the contract".to_string());
+        }
+        let cards = play.tricks().iter().flat_map(|trick| trick.cards_in_order());
+        for (seat, card) in cards {
+          if !game.make_play(seat, card) {
+            return illegal(format!("{:?} cannot play {}", seat, card));
+          }
+        }
+        // the recorded play has already replayed its own claim, so it can stand in for ours
+        game.result = play.result();
+        game.play = Some(play.clone());
+      }
+      (false, None) => {}
+      (true, None) => return illegal("the auction is over but the play is missing".to_string()),
+      (false, Some(_)) => return illegal("there is play without a contract".to_string()),
+    }
+    if self.result.is_some() && self.result != game.result {
+      return illegal("the result does not match the play".to_string());
+    }
+    Ok(game)
+  }
+
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(self).expect("game records always serialize")
+  }
+
+  /** Reads a record from JSON. The version is checked before anything else, so that a record from
+      a newer format is reported as such instead of as malformed.
+   */
+  pub fn from_json(json: &str) -> Result<Self, RecordError> {
+    #[derive(Deserialize)]
+    struct Version {
+      version: u32,
+    }
+
+    let Version { version } = serde_json::from_str(json)?;
+    if version > RECORD_VERSION {
+      return Err(RecordError::UnsupportedVersion(version));
+    }
+    Ok(serde_json::from_str(json)?)
+  }
+}
@@ -0,0 +1,254 @@
+use enum_map::EnumMap;
+use strum::IntoEnumIterator;
+use crate::game_model::{Board, BridgeGame, HandResult, Seat, Side, Vulnerability};
+use crate::game_model::bidding::{Contract, Strain};
+use crate::game_model::cards::{Rank, Suit};
+
+/** The scoring form kept by a `RubberScore`.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RubberKind {
+  /** Classic rubber bridge: best of three games, with vulnerability earned by winning a game. */
+  Rubber,
+  /** Four-deal Chicago, with a fixed vulnerability schedule and game bonuses paid as they are made.
+   */
+  Chicago,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Line {
+  Above,
+  Below,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ScoreItem {
+  Tricks,
+  Overtricks,
+  Insult,
+  Slam,
+  Honours,
+  Undertricks,
+  Game,
+  Partscore,
+  Rubber,
+}
+
+/** A single entry on the score sheet. `deal` is the index of the deal that produced it, counting
+    from zero; entries added when an unfinished rubber is settled use the number of deals played.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RubberEntry {
+  pub deal: usize,
+  pub side: Side,
+  pub line: Line,
+  pub item: ScoreItem,
+  pub points: i32,
+}
+
+/** A rubber bridge or Chicago score sheet. Completed games are recorded in order; the ledger keeps
+    track of games won and part-scores towards the current game, and decides the vulnerability of
+    the next deal. The vulnerability printed on the board is ignored.
+ */
+#[derive(Debug, Clone)]
+pub struct RubberScore {
+  kind: RubberKind,
+  entries: Vec<RubberEntry>,
+  deals: usize,
+  games: EnumMap<Side, u8>,
+  partscores: EnumMap<Side, i32>,
+  settled: bool,
+}
+
+impl RubberScore {
+  pub fn new(kind: RubberKind) -> Self {
+    RubberScore {
+      kind,
+      entries: Vec::new(),
+      deals: 0,
+      games: EnumMap::default(),
+      partscores: EnumMap::default(),
+      settled: false,
+    }
+  }
+
+  pub fn rubber() -> Self {
+    RubberScore::new(RubberKind::Rubber)
+  }
+
+  pub fn chicago() -> Self {
+    RubberScore::new(RubberKind::Chicago)
+  }
+
+  pub fn kind(&self) -> RubberKind {
+    self.kind
+  }
+
+  pub fn entries(&self) -> &[RubberEntry] {
+    &self.entries
+  }
+
+  pub fn deals_played(&self) -> usize {
+    self.deals
+  }
+
+  pub fn games_won(&self, side: Side) -> u8 {
+    self.games[side]
+  }
+
+  /** Returns the part-score a side has towards the current game.
+   */
+  pub fn partscore(&self, side: Side) -> i32 {
+    self.partscores[side]
+  }
+
+  pub fn is_complete(&self) -> bool {
+    self.settled || match self.kind {
+      RubberKind::Rubber => self.games.values().any(|&games| games == 2),
+      RubberKind::Chicago => self.deals == 4,
+    }
+  }
+
+  /** Returns the vulnerability of the next deal. In rubber bridge a side is vulnerable once it has
+      won a game. In Chicago the first deal is played with neither side vulnerable, the second and
+      third with only the dealer's side vulnerable, and the fourth with both sides vulnerable.
+   */
+  pub fn vulnerability(&self) -> Vulnerability {
+    match self.kind {
+      RubberKind::Rubber => match (self.games[Side::NorthSouth] > 0, self.games[Side::EastWest] > 0) {
+        (false, false) => Vulnerability::Neither,
+        (true, false) => Vulnerability::NS,
+        (false, true) => Vulnerability::EW,
+        (true, true) => Vulnerability::Both,
+      },
+      RubberKind::Chicago => match self.deals % 4 {
+        0 => Vulnerability::Neither,
+        1 => Vulnerability::EW, // East deals the second hand
+        2 => Vulnerability::NS, // South deals the third hand
+        _ => Vulnerability::Both,
+      },
+    }
+  }
+
+  pub fn total(&self, side: Side) -> i32 {
+    self.entries.iter().filter(|e| e.side == side).map(|e| e.points).sum()
+  }
+
+  pub fn line_total(&self, side: Side, line: Line) -> i32 {
+    self.entries.iter().filter(|e| e.side == side && e.line == line).map(|e| e.points).sum()
+  }
+
+  /** Records a completed game. Returns `false`, leaving the ledger untouched, if the game is not
+      finished or the rubber is already complete.
+   */
+  pub fn record(&mut self, game: &BridgeGame) -> bool {
+    match game.result() {
+      Some(result) => self.record_result(result, game.board()),
+      None => false,
+    }
+  }
+
+  /** Records the result of a deal played on `board`. The board is only consulted for honours.
+   */
+  pub fn record_result(&mut self, result: &HandResult, board: &Board) -> bool {
+    if self.is_complete() {
+      return false;
+    }
+    let vul = self.vulnerability();
+    if let HandResult::Played(contract, diff) = *result {
+      if let Some((side, points)) = honours(board, contract.strain()) {
+        self.add(side, Line::Above, ScoreItem::Honours, points);
+      }
+      let declarers = contract.declarer().side();
+      let is_vul = vul.is_vulnerable(contract.declarer());
+      if diff < 0 {
+        let penalty = -contract.doubled().score_for_set(-diff as i32, is_vul);
+        self.add(declarers.opponents(), Line::Above, ScoreItem::Undertricks, penalty);
+      } else {
+        self.record_make(contract, diff as i32, is_vul);
+      }
+    }
+    if self.kind == RubberKind::Rubber {
+      if let Some(winner) = Side::iter().find(|&side| self.games[side] == 2) {
+        let bonus = if self.games[winner.opponents()] == 0 { 700 } else { 500 };
+        self.add(winner, Line::Above, ScoreItem::Rubber, bonus);
+      }
+    }
+    self.deals += 1;
+    true
+  }
+
+  fn record_make(&mut self, contract: Contract, overtricks: i32, vul: bool) {
+    let side = contract.declarer().side();
+    let trick_points = contract.contract_points();
+    self.add(side, Line::Below, ScoreItem::Tricks, trick_points);
+    self.add(side, Line::Above, ScoreItem::Overtricks, contract.overtrick_points(overtricks, vul));
+    self.add(side, Line::Above, ScoreItem::Insult, contract.insult_bonus());
+    self.add(side, Line::Above, ScoreItem::Slam, contract.slam_bonus(vul));
+    self.partscores[side] += trick_points;
+    if self.partscores[side] >= 100 {
+      self.games[side] += 1;
+      self.partscores = EnumMap::default();
+      if self.kind == RubberKind::Chicago {
+        self.add(side, Line::Above, ScoreItem::Game, if vul { 500 } else { 300 });
+      }
+    } else if self.kind == RubberKind::Chicago && self.deals == 3 {
+      self.add(side, Line::Above, ScoreItem::Partscore, 100);
+    }
+  }
+
+  /** Settles a rubber that ends before either side has won two games: a side with a game scores
+      300, and a side with a part-score in the unfinished game scores 100. Chicago needs no
+      settlement. Returns `false` if there is nothing to settle.
+   */
+  pub fn settle(&mut self) -> bool {
+    if self.kind != RubberKind::Rubber || self.is_complete() {
+      return false;
+    }
+    for side in Side::iter() {
+      if self.games[side] == 1 {
+        self.add(side, Line::Above, ScoreItem::Game, 300);
+      }
+      if self.partscores[side] > 0 {
+        self.add(side, Line::Above, ScoreItem::Partscore, 100);
+      }
+    }
+    self.settled = true;
+    true
+  }
+
+  fn add(&mut self, side: Side, line: Line, item: ScoreItem, points: i32) {
+    if points != 0 {
+      self.entries.push(RubberEntry { deal: self.deals, side, line, item, points });
+    }
+  }
+}
+
+/** Finds the honours held in a single hand: 150 for all five trump honours or all four aces at
+    notrump, 100 for four of the five trump honours. As in the laws, honours are scored by the side
+    holding them whether or not it declared.
+ */
+pub fn honours(board: &Board, strain: Strain) -> Option<(Side, i32)> {
+  Seat::iter().find_map(|seat| {
+    let hand = board.player_hand(seat).sort();
+    let points = match strain {
+      Strain::Trump(trump) => {
+        let count = hand[trump].iter().filter(|&&rank| rank >= Rank::Ten).count();
+        match count {
+          5 => 150,
+          4 => 100,
+          _ => 0,
+        }
+      }
+      Strain::Notrump => {
+        let aces = Suit::iter().filter(|&suit| hand[suit].contains(&Rank::Ace)).count();
+        if aces == 4 { 150 } else { 0 }
+      }
+    };
+    if points > 0 {
+      Some((seat.side(), points))
+    } else {
+      None
+    }
+  })
+}
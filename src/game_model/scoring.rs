@@ -0,0 +1,123 @@
+use crate::game_model::{HandResult, Vulnerability};
+
+/** Upper bound (inclusive) of each band of the WBF IMP scale. A point difference above the last
+    bound is worth the maximum of 24 IMPs.
+ */
+const IMP_SCALE: [i32; 24] = [
+  10, 40, 80, 120, 160, 210, 260, 310, 360, 420, 490, 590,
+  740, 890, 1090, 1290, 1490, 1740, 1990, 2240, 2490, 2990, 3490, 3990,
+];
+
+/** Converts a point difference to IMPs on the WBF scale. The sign of the difference is kept.
+ */
+pub fn imps(diff: i32) -> i32 {
+  let magnitude = IMP_SCALE.iter().take_while(|&&bound| diff.abs() > bound).count() as i32;
+  magnitude * diff.signum()
+}
+
+/** Computes the North-South score of every result of a board.
+ */
+pub fn ns_scores(results: &[HandResult], vul: Vulnerability) -> Vec<i32> {
+  results.iter().map(|result| result.ns_score(vul)).collect()
+}
+
+/** Computes the datum of a set of scores: their mean, rounded to the nearest 10.
+ */
+pub fn datum(scores: &[i32]) -> i32 {
+  if scores.is_empty() {
+    return 0;
+  }
+  let mean = scores.iter().map(|&s| s as f64).sum::<f64>() / scores.len() as f64;
+  (mean / 10.0).round() as i32 * 10
+}
+
+/** Computes the Butler datum of a set of scores. The highest and the lowest score are discarded
+    before averaging, unless that would leave nothing to average.
+ */
+pub fn butler_datum(scores: &[i32]) -> i32 {
+  if scores.len() <= 2 {
+    return datum(scores);
+  }
+  let mut sorted = scores.to_vec();
+  sorted.sort_unstable();
+  datum(&sorted[1..sorted.len() - 1])
+}
+
+/** Scores each result of a board in IMPs against the plain datum, from North-South's point of view.
+ */
+pub fn imps_vs_datum(results: &[HandResult], vul: Vulnerability) -> Vec<i32> {
+  let scores = ns_scores(results, vul);
+  let datum = datum(&scores);
+  scores.iter().map(|&score| imps(score - datum)).collect()
+}
+
+/** Scores each result of a board in IMPs against the Butler datum, from North-South's point of
+    view.
+ */
+pub fn butler(results: &[HandResult], vul: Vulnerability) -> Vec<i32> {
+  let scores = ns_scores(results, vul);
+  let datum = butler_datum(&scores);
+  scores.iter().map(|&score| imps(score - datum)).collect()
+}
+
+/** Scores each result of a board in cross-IMPs: the total of the IMPs won or lost against every
+    other result, from North-South's point of view. Divide by one less than the number of results
+    to get the average per comparison.
+ */
+pub fn cross_imps(results: &[HandResult], vul: Vulnerability) -> Vec<i32> {
+  let scores = ns_scores(results, vul);
+  scores.iter()
+      .enumerate()
+      .map(|(i, &score)| {
+        scores.iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, &other)| imps(score - other))
+            .sum()
+      })
+      .collect()
+}
+
+/** Matchpoints each result of a board with top scoring: two points for every result beaten and
+    one for every result tied, from North-South's point of view. The top is twice one less than the
+    number of results.
+ */
+pub fn matchpoints(results: &[HandResult], vul: Vulnerability) -> Vec<u32> {
+  let scores = ns_scores(results, vul);
+  scores.iter()
+      .enumerate()
+      .map(|(i, &score)| {
+        scores.iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, &other)| match score.cmp(&other) {
+              std::cmp::Ordering::Greater => 2,
+              std::cmp::Ordering::Equal => 1,
+              std::cmp::Ordering::Less => 0,
+            })
+            .sum()
+      })
+      .collect()
+}
+
+/** Matchpoints each result of a board and scales it with the Neuberg formula to the top of a board
+    played `expected` times. This is used when a board has fewer (or more) results than the rest of
+    the session.
+ */
+pub fn neuberg(results: &[HandResult], vul: Vulnerability, expected: usize) -> Vec<f64> {
+  let played = results.len() as f64;
+  matchpoints(results, vul)
+      .into_iter()
+      .map(|mp| (mp as f64 + 1.0) * expected as f64 / played - 1.0)
+      .collect()
+}
+
+/** The form of scoring a decision is made for: IMPs, matchpoints, or total points, where every
+    point counts the same.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ScoringForm {
+  Imps,
+  Matchpoints,
+  TotalPoints,
+}
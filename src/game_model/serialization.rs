@@ -0,0 +1,188 @@
+use enum_map::EnumMap;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use crate::game_model::{Board, Seat, Vulnerability};
+use crate::game_model::bidding::{Auction, Call, CallAnnotation, Contract};
+use crate::game_model::cards::{Card, PlayerHand};
+use crate::game_model::play::{Play, Trick};
+
+// The serialized forms of the stateful types hold only what was said and played. Deserializing
+// replays them through the game model, so a record that breaks the rules is rejected rather than
+// producing an inconsistent value.
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BoardRecord {
+  hands: EnumMap<Seat, PlayerHand>,
+  number: u32,
+  dealer: Seat,
+  vulnerability: Vulnerability,
+}
+
+impl TryFrom<BoardRecord> for Board {
+  type Error = String;
+
+  fn try_from(record: BoardRecord) -> Result<Self, Self::Error> {
+    if let Some((seat, hand)) = record.hands.iter().find(|(_, hand)| hand.len() != 13) {
+      return Err(format!("{:?} holds {} cards", seat, hand.len()));
+    }
+    for (seat, hand) in &record.hands {
+      for other in Seat::iter().filter(|&other| other != seat) {
+        if let Some(card) = hand.sort().iter()
+            .flat_map(|(suit, ranks)| ranks.iter().map(move |&rank| Card::from(suit, rank)))
+            .find(|&card| record.hands[other].has_card(card)) {
+          return Err(format!("{} is dealt to both {:?} and {:?}", card, seat, other));
+        }
+      }
+    }
+    Ok(Board {
+      hands: record.hands,
+      number: record.number,
+      dealer: record.dealer,
+      vulnerability: record.vulnerability,
+    })
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct AuctionRecord {
+  dealer: Seat,
+  calls: Vec<Call>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  annotations: Vec<CallAnnotationRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CallAnnotationRecord {
+  call: usize,
+  #[serde(flatten)]
+  annotation: CallAnnotation,
+}
+
+impl From<Auction> for AuctionRecord {
+  fn from(auction: Auction) -> Self {
+    AuctionRecord {
+      dealer: auction.dealer(),
+      calls: auction.calls().copied().collect(),
+      annotations: (0..auction.len())
+          .filter_map(|call| Some(CallAnnotationRecord { call, annotation: auction.annotation(call)?.clone() }))
+          .collect(),
+    }
+  }
+}
+
+impl TryFrom<AuctionRecord> for Auction {
+  type Error = String;
+
+  fn try_from(record: AuctionRecord) -> Result<Self, Self::Error> {
+    let mut auction = Auction::new(record.dealer);
+    for (i, call) in record.calls.into_iter().enumerate() {
+      if auction.is_complete() || !auction.make_call(call) {
+        return Err(format!("call {} ({:?}) is not legal", i + 1, call));
+      }
+    }
+    for record in record.annotations {
+      if !auction.annotate(record.call, record.annotation) {
+        return Err(format!("annotation of call {}, which was not made", record.call + 1));
+      }
+    }
+    Ok(auction)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TrickRecord {
+  leader: Seat,
+  cards: Vec<Card>,
+}
+
+impl From<Trick> for TrickRecord {
+  fn from(trick: Trick) -> Self {
+    TrickRecord {
+      leader: trick.leader(),
+      cards: trick.cards_in_order().map(|(_, card)| card).collect(),
+    }
+  }
+}
+
+impl TryFrom<TrickRecord> for Trick {
+  type Error = String;
+
+  fn try_from(record: TrickRecord) -> Result<Self, Self::Error> {
+    Trick::from_cards(record.leader, &record.cards)
+        .ok_or_else(|| format!("a trick holds 1 to 4 cards, not {}", record.cards.len()))
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ClaimRecord {
+  claimer: Seat,
+  tricks: u8,
+  statement: String,
+  #[serde(default)]
+  concession: bool,
+  accepted_by: Vec<Seat>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PlayRecord {
+  contract: Contract,
+  tricks: Vec<Trick>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  claim: Option<ClaimRecord>,
+}
+
+impl From<Play> for PlayRecord {
+  fn from(play: Play) -> Self {
+    let claim = play.pending_claim().or(play.settled_claim()).map(|claim| ClaimRecord {
+      claimer: claim.claimer(),
+      tricks: claim.tricks(),
+      statement: claim.statement().to_string(),
+      concession: claim.is_concession(),
+      accepted_by: Seat::iter().filter(|&seat| claim.is_accepted_by(seat)).collect(),
+    });
+    PlayRecord {
+      contract: play.contract(),
+      tricks: play.tricks().clone(),
+      claim,
+    }
+  }
+}
+
+impl TryFrom<PlayRecord> for Play {
+  type Error = String;
+
+  fn try_from(record: PlayRecord) -> Result<Self, Self::Error> {
+    let mut play = Play::new(record.contract);
+    let mut seen = Vec::with_capacity(52);
+    for (seat, card) in record.tricks.iter().flat_map(Trick::cards_in_order) {
+      if seen.contains(&card) {
+        return Err(format!("{} is played twice", card));
+      }
+      if !play.make_play(seat, card) {
+        return Err(format!("{:?} cannot play {} after {} cards", seat, card, seen.len()));
+      }
+      seen.push(card);
+    }
+    if let Some(claim) = record.claim {
+      replay_claim(&mut play, claim)?;
+    }
+    Ok(play)
+  }
+}
+
+/** Makes a recorded claim and its acceptances. A claim that was accepted by everyone is settled by
+    the last acceptance; one recorded as a concession is settled at once.
+ */
+fn replay_claim(play: &mut Play, claim: ClaimRecord) -> Result<(), String> {
+  let legal = if claim.concession {
+    play.concede(claim.claimer, play.remaining_tricks().saturating_sub(claim.tricks))
+  } else {
+    play.claim(claim.claimer, claim.tricks, &claim.statement)
+        && claim.accepted_by.iter().all(|&seat| play.accept_claim(seat))
+  };
+  if legal {
+    Ok(())
+  } else {
+    Err(format!("the claim by {:?} is not legal", claim.claimer))
+  }
+}
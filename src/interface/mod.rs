@@ -1,20 +1,107 @@
 // use cursive::Cursive;
 // use cursive::views::{DummyView, LinearLayout};
-use crate::game_model::bidding::{Auction, Call};
-use crate::game_model::Board;
+use std::time::{Duration, Instant};
+use crate::ai::trace::DecisionTrace;
+use crate::game_model::bidding::{Auction, Call, CallAnnotation};
+use crate::game_model::{Board, Vulnerability};
 // use crate::game_model::BridgeGame;
 use crate::game_model::cards::{Card, PlayerHand};
-use crate::game_model::play::Play;
+use crate::game_model::play::{Claim, Play};
 //
 // mod view;
 // mod control;
 // mod messages;
 //
+/** When a player must have chosen its card. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Deadline {
+  /** Think until the clock reaches this moment. */
+  At(Instant),
+  /** Solve at most this many sampled deals, however long that takes. The same player then chooses
+      the same card on every run, which keeps tests and matches reproducible.
+   */
+  Nodes(u64),
+}
+
+impl Deadline {
+  pub fn after(time: Duration) -> Self {
+    Deadline::At(Instant::now() + time)
+  }
+}
+
+/** The thinking a player may do in a whole hand. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimeControl {
+  Time(Duration),
+  Nodes(u64),
+}
+
+impl Default for TimeControl {
+  fn default() -> Self {
+    TimeControl::Nodes(2_000)
+  }
+}
+
+impl TimeControl {
+  /** The deadline for the next card of `play`. Trick `n` is given `14 - n` parts of the 91 in the
+      hand, since the early tricks decide most hands and the last few cards are often forced.
+      Declarer gets a share for dummy's cards as well as for their own.
+   */
+  pub fn deadline(&self, play: &Play) -> Deadline {
+    let trick = (play.cards_played() / 4 + 1).min(13) as u32;
+    let weight = 14 - trick;
+    match *self {
+      TimeControl::Time(time) => Deadline::after(time * weight / 91),
+      TimeControl::Nodes(nodes) => Deadline::Nodes(nodes * weight as u64 / 91),
+    }
+  }
+}
+
 pub trait Player {
-  fn new(hand: &PlayerHand) -> Self;
+  fn new(hand: &PlayerHand) -> Self where Self: Sized;
   fn get_call(&self, auction: &Auction) -> Call;
-  fn get_play(&mut self, play: &Play, board: &Board) -> Card;
+  /** Chooses the next card, which is dummy's when the player is declarer and dummy is to play. The
+      player should answer by `deadline`, and answers a little after it at worst.
+   */
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: Deadline) -> Card;
   fn notify_dummy(&mut self, dummy: &PlayerHand);
+
+  /** Called with the board's number and vulnerability once the player has its hand, before the
+      first call. The default does nothing.
+   */
+  fn notify_board(&mut self, _number: u32, _vulnerability: Vulnerability) {}
+
+  /** Called with the final auction once it is complete, before the opening lead. The default does
+      nothing.
+   */
+  fn notify_auction(&mut self, _auction: &Auction) {}
+
+  /** Describes a call the player has just chosen, for the opponents: whether it is alerted, what it
+      means in words and the hands it shows. The auction is the one the call was made after. The
+      default gives no description.
+   */
+  fn explain_call(&self, _auction: &Auction, _call: Call) -> Option<CallAnnotation> {
+    None
+  }
+
+  /** Offers to claim instead of playing a card, as the number of remaining tricks claimed and a
+      statement of how they will be won. The default never claims.
+   */
+  fn offer_claim(&mut self, _play: &Play, _board: &Board) -> Option<(u8, String)> {
+    None
+  }
+
+  /** Responds to an opponent's claim: `true` accepts it and `false` disputes it. The default
+      disputes every claim.
+   */
+  fn respond_to_claim(&mut self, _play: &Play, _board: &Board, _claim: &Claim) -> bool {
+    false
+  }
+
+  /** Why the player made its last call or play. The default keeps no trace. */
+  fn last_decision(&self) -> Option<DecisionTrace> {
+    None
+  }
 }
 //
 // pub fn run() {
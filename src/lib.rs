@@ -1,6 +1,10 @@
 pub mod interface;
-mod game_model;
-mod ai;
+pub mod game_model;
+pub mod ai;
+pub mod tournament;
+pub mod bluechip;
+#[cfg(feature = "server")]
+pub mod server;
 
 #[cfg(test)]
 mod tests;
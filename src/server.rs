@@ -0,0 +1,547 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use enum_map::{enum_map, EnumMap};
+use futures_util::{SinkExt, StreamExt};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender, WeakUnboundedSender};
+use tokio_tungstenite::tungstenite::Message as Frame;
+use crate::ai::baseline::GreedyPlayer;
+use crate::game_model::{Board, BridgeGame, HandResult, Seat, Vulnerability};
+use crate::game_model::bidding::{Call, CallAnnotation, Contract};
+use crate::game_model::cards::{Card, PlayerHand};
+use crate::game_model::play::Play;
+use crate::interface::{Player, TimeControl};
+
+/** What a client may send, one JSON object per WebSocket text message, named by its `type`:
+    `{"type": "join", "table": 1, "seat": "N", "name": "Ann"}`.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+  /** Opens a new table with every seat empty. */
+  CreateTable,
+  /** Takes an empty seat at a table. */
+  Join { table: u32, seat: Seat, name: String },
+  /** Takes back the seat given by an earlier `joined` after the connection was lost. */
+  Rejoin { table: u32, token: String },
+  /** Fills the empty seats with computer players and deals the first board. */
+  Start,
+  Call { call: Call },
+  /** Plays a card, which is dummy's when declarer is to play from dummy. */
+  Play { card: Card },
+  /** Deals the next board once the current one is finished. */
+  NextBoard,
+}
+
+/** What the server sends, in the same form as `ClientMessage`. */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+  TableCreated { table: u32 },
+  /** The client now holds `seat`. The token takes the seat back with `rejoin`. */
+  Joined { table: u32, seat: Seat, token: String },
+  /** The table as the client's seat sees it, sent whenever it changes. */
+  Table(TableView),
+  /** A message was not understood or not allowed. Nothing at the table changed. */
+  Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableView {
+  pub table: u32,
+  pub seat: Seat,
+  /** The name of each seat's player, `"computer"` for a computer player, or nothing if empty. */
+  pub players: EnumMap<Seat, Option<String>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub deal: Option<DealView>,
+}
+
+/** The board at the table with only the cards the seat may see: its own and, once the opening lead
+    has been made, dummy's. The hands hold the cards not yet played.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DealView {
+  pub board: u32,
+  pub dealer: Seat,
+  pub vulnerability: Vulnerability,
+  pub hand: Vec<Card>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub dummy: Option<Vec<Card>>,
+  pub calls: Vec<Call>,
+  /** The cards played, in order, with the seat that played each. */
+  pub cards: Vec<(Seat, Card)>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub contract: Option<Contract>,
+  /** The seat whose call or card is next. When it is dummy, declarer chooses the card. */
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub to_act: Option<Seat>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub result: Option<HandResult>,
+}
+
+type PlayerFactory = dyn Fn(&PlayerHand) -> Box<dyn Player + Send> + Send + Sync;
+
+/** One client connection, as the table knows it. */
+struct Client {
+  id: u64,
+  sender: UnboundedSender<ServerMessage>,
+}
+
+impl Client {
+  fn send(&self, message: ServerMessage) {
+    // A client that has gone away is noticed by its connection, which tells the table.
+    let _ = self.sender.send(message);
+  }
+}
+
+enum Occupant {
+  Empty,
+  /** A person, who keeps the seat while disconnected and can rejoin it with the token. */
+  Human { name: String, token: String, client: Option<Client> },
+  Computer,
+}
+
+/** What a computer player chose. */
+enum Decision {
+  Call(Call, Option<CallAnnotation>),
+  Card(Card),
+}
+
+enum Command {
+  Join { seat: Seat, name: String, client: Client },
+  Rejoin { token: String, client: Client },
+  Leave { client: u64 },
+  Act { client: Client, message: ClientMessage },
+  /** A computer player has made up its mind, and is handed back to the table with its decision. */
+  Decided { board: u32, seat: Seat, player: Box<dyn Player + Send>, decision: Decision },
+}
+
+fn error(message: &str) -> ServerMessage {
+  ServerMessage::Error { message: message.to_string() }
+}
+
+/** The seat that chooses the next call or card: declarer plays for dummy. */
+fn to_act(game: &BridgeGame) -> Option<Seat> {
+  if !game.auction().is_complete() {
+    return Some(game.auction().current_bidder());
+  }
+  let play = game.play().filter(|play| !play.is_complete())?;
+  let next = play.next_player();
+  Some(if next == play.declarer().partner() { play.declarer() } else { next })
+}
+
+fn cards_played(play: &Play) -> Vec<(Seat, Card)> {
+  play.tricks().iter().flat_map(|trick| trick.cards_in_order()).collect()
+}
+
+/** A table and everything at it, run as a task of its own that handles one command at a time.
+    Computer players start choosing their calls and cards as soon as it is their turn. They think
+    on a blocking thread, so the table keeps answering its clients meanwhile, and their decisions
+    come back as commands.
+ */
+struct Table {
+  id: u32,
+  seats: EnumMap<Seat, Occupant>,
+  /** The computer players, each missing while it thinks. */
+  computers: EnumMap<Seat, Option<Box<dyn Player + Send>>>,
+  commands: WeakUnboundedSender<Command>,
+  game: Option<BridgeGame>,
+  /** Whether the computers have been shown the final auction and dummy of the current board. */
+  told_auction: bool,
+  told_dummy: bool,
+  rng: StdRng,
+  factory: Arc<PlayerFactory>,
+  control: TimeControl,
+}
+
+impl Table {
+  async fn run(mut self, mut commands: UnboundedReceiver<Command>) {
+    while let Some(command) = commands.recv().await {
+      self.handle(command);
+    }
+  }
+
+  fn seat_of(&self, client: u64) -> Option<Seat> {
+    Seat::iter().find(|&seat| matches!(&self.seats[seat], Occupant::Human { client: Some(c), .. } if c.id == client))
+  }
+
+  fn handle(&mut self, command: Command) {
+    match command {
+      Command::Join { seat, name, client } => {
+        if !matches!(self.seats[seat], Occupant::Empty) {
+          return client.send(error("that seat is taken"));
+        }
+        let token = format!("{:016x}", self.rng.gen::<u64>());
+        client.send(ServerMessage::Joined { table: self.id, seat, token: token.clone() });
+        self.seats[seat] = Occupant::Human { name, token, client: Some(client) };
+      }
+      Command::Rejoin { token, client } => {
+        let seat = Seat::iter().find(|&seat| matches!(&self.seats[seat], Occupant::Human { token: t, .. } if *t == token));
+        match seat.map(|seat| (seat, &mut self.seats[seat])) {
+          Some((seat, Occupant::Human { token, client: current, .. })) => {
+            client.send(ServerMessage::Joined { table: self.id, seat, token: token.clone() });
+            *current = Some(client);
+          }
+          _ => return client.send(error("no seat at this table has that token")),
+        }
+      }
+      Command::Leave { client } => {
+        if let Some(seat) = self.seat_of(client) {
+          if let Occupant::Human { client, .. } = &mut self.seats[seat] {
+            *client = None;
+          }
+        }
+        return;
+      }
+      Command::Act { client, message } => {
+        let seat = match self.seat_of(client.id) {
+          Some(seat) => seat,
+          None => return client.send(error("you have no seat at this table")),
+        };
+        if let Err(message) = self.act(seat, message) {
+          return client.send(error(message));
+        }
+        self.run_computers();
+      }
+      Command::Decided { board, seat, player, decision } => {
+        if self.game.as_ref().map(|game| game.board().number()) != Some(board) {
+          return;
+        }
+        self.computers[seat] = Some(player);
+        self.decide(seat, decision);
+        self.run_computers();
+      }
+    }
+    self.broadcast();
+  }
+
+  fn act(&mut self, seat: Seat, message: ClientMessage) -> Result<(), &'static str> {
+    match message {
+      ClientMessage::Start => {
+        if self.game.is_some() {
+          return Err("the table has already started");
+        }
+        for occupant in self.seats.values_mut().filter(|occupant| matches!(occupant, Occupant::Empty)) {
+          *occupant = Occupant::Computer;
+        }
+        self.deal(1);
+      }
+      ClientMessage::NextBoard => match &self.game {
+        Some(game) if game.is_complete() => self.deal(game.board().number() + 1),
+        _ => return Err("the board is not finished"),
+      },
+      ClientMessage::Call { call } => {
+        let game = self.game.as_mut().ok_or("the table has not started")?;
+        if to_act(game) != Some(seat) || game.auction().is_complete() || !game.make_call(call) {
+          return Err("that call is not allowed now");
+        }
+      }
+      ClientMessage::Play { card } => {
+        let game = self.game.as_mut().ok_or("the table has not started")?;
+        let play = game.play().filter(|_| to_act(game) == Some(seat)).ok_or("it is not your turn to play")?;
+        let next = play.next_player();
+        if !game.make_play(next, card) {
+          return Err("that card may not be played");
+        }
+      }
+      _ => return Err("already seated at a table"),
+    }
+    Ok(())
+  }
+
+  fn deal(&mut self, number: u32) {
+    let board = Board::random(number, &mut self.rng);
+    for seat in Seat::iter() {
+      self.computers[seat] = match self.seats[seat] {
+        Occupant::Computer => {
+          let mut player = (self.factory)(board.player_hand(seat));
+          player.notify_board(number, board.vulnerability());
+          Some(player)
+        }
+        _ => None,
+      };
+    }
+    self.game = Some(BridgeGame::from_board(board));
+    self.told_auction = false;
+    self.told_dummy = false;
+    self.run_computers();
+  }
+
+  /** Shows the computers the final auction and dummy as soon as the board reaches them. */
+  fn tell_computers(&mut self) {
+    let game = match &self.game {
+      Some(game) => game,
+      None => return,
+    };
+    if !self.told_auction && game.auction().is_complete() {
+      self.told_auction = true;
+      for (seat, player) in self.computers.iter_mut() {
+        if let Some(player) = player {
+          player.notify_auction(&game.auction().disclosed_to(seat));
+        }
+      }
+    }
+    if let Some(play) = game.play().filter(|play| !self.told_dummy && play.cards_played() > 0) {
+      self.told_dummy = true;
+      let dummy = play.declarer().partner();
+      for (seat, player) in self.computers.iter_mut() {
+        if let Some(player) = player.as_mut().filter(|_| seat != dummy) {
+          player.notify_dummy(game.board().player_hand(dummy));
+        }
+      }
+    }
+  }
+
+  /** Asks the computer whose turn it is, if any, for its call or card. The computer is taken from
+      its seat and thinks on a blocking thread, then sends its decision back to the table.
+   */
+  fn run_computers(&mut self) {
+    self.tell_computers();
+    let game = match &self.game {
+      Some(game) => game,
+      None => return,
+    };
+    let seat = match to_act(game) {
+      Some(seat) => seat,
+      None => return,
+    };
+    let mut player = match self.computers[seat].take() {
+      Some(player) => player,
+      None => return,
+    };
+    let board = game.board().clone();
+    let auction = game.auction().clone();
+    let play = game.play().cloned();
+    let deadline = play.as_ref().map(|play| self.control.deadline(play));
+    let commands = self.commands.clone();
+    tokio::task::spawn_blocking(move || {
+      let decision = match (&play, deadline) {
+        (Some(play), Some(deadline)) => Decision::Card(player.get_play(play, &board, deadline)),
+        _ => {
+          let auction = auction.disclosed_to(seat);
+          let call = player.get_call(&auction);
+          Decision::Call(call, player.explain_call(&auction, call))
+        }
+      };
+      if let Some(commands) = commands.upgrade() {
+        let _ = commands.send(Command::Decided { board: board.number(), seat, player, decision });
+      }
+    });
+  }
+
+  /** Makes a computer's call or play. A computer that makes an illegal call passes instead, and one
+      that chooses an illegal card plays its lowest legal card.
+   */
+  fn decide(&mut self, seat: Seat, decision: Decision) {
+    let game = match &mut self.game {
+      Some(game) if to_act(game) == Some(seat) => game,
+      _ => return,
+    };
+    match (game.play().cloned(), decision) {
+      (None, Decision::Call(call, annotation)) => {
+        let legal = game.make_annotated_call(call, annotation);
+        if !legal {
+          game.make_call(Call::Pass);
+        }
+      }
+      (Some(play), Decision::Card(card)) => {
+        let next = play.next_player();
+        if !game.make_play(next, card) {
+          let lowest = play.legal_plays(game.board().player_hand(next)).into_iter().min_by_key(|card| (card.rank(), card.suit())).expect("the player to play holds a card");
+          game.make_play(next, lowest);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn view(&self, seat: Seat) -> TableView {
+    let players = enum_map! { seat => match &self.seats[seat] {
+      Occupant::Empty => None,
+      Occupant::Human { name, .. } => Some(name.clone()),
+      Occupant::Computer => Some("computer".to_string()),
+    } };
+    let deal = self.game.as_ref().map(|game| {
+      let board = game.board();
+      let cards = game.play().map(cards_played).unwrap_or_default();
+      let remaining = |owner: Seat| -> Vec<Card> {
+        board.player_hand(owner).cards().iter().copied().filter(|&card| !cards.contains(&(owner, card))).collect()
+      };
+      let dummy = game.play().map(|play| play.declarer().partner()).filter(|&dummy| dummy != seat && !cards.is_empty());
+      let next = match game.play() {
+        Some(play) => Some(play.next_player()).filter(|_| !play.is_complete()),
+        None => Some(game.auction().current_bidder()).filter(|_| !game.auction().is_complete()),
+      };
+      DealView {
+        board: board.number(),
+        dealer: board.dealer(),
+        vulnerability: board.vulnerability(),
+        hand: remaining(seat),
+        dummy: dummy.map(remaining),
+        calls: game.auction().calls().copied().collect(),
+        cards: cards.clone(),
+        contract: game.play().map(Play::contract),
+        to_act: next,
+        result: game.result().copied(),
+      }
+    });
+    TableView { table: self.id, seat, players, deal }
+  }
+
+  fn broadcast(&self) {
+    for (seat, occupant) in self.seats.iter() {
+      if let Occupant::Human { client: Some(client), .. } = occupant {
+        client.send(ServerMessage::Table(self.view(seat)));
+      }
+    }
+  }
+}
+
+/** Hosts tables that people play at from a browser or any other WebSocket client. Each table runs
+    as its own task, so games at different tables go on at the same time. Clients see only their
+    own cards and, after the opening lead, dummy's.
+ */
+pub struct GameServer {
+  tables: Mutex<HashMap<u32, UnboundedSender<Command>>>,
+  next_table: AtomicU32,
+  next_client: AtomicU64,
+  factory: Arc<PlayerFactory>,
+  control: TimeControl,
+}
+
+impl Default for GameServer {
+  fn default() -> Self {
+    GameServer {
+      tables: Mutex::new(HashMap::new()),
+      next_table: AtomicU32::new(1),
+      next_client: AtomicU64::new(0),
+      factory: Arc::new(|hand: &PlayerHand| Box::new(GreedyPlayer::new(hand)) as Box<dyn Player + Send>),
+      control: TimeControl::default(),
+    }
+  }
+}
+
+impl GameServer {
+  pub fn new() -> Self {
+    GameServer::default()
+  }
+
+  /** Fills empty seats with `P` instead of the greedy baseline player. */
+  pub fn with_computer<P: Player + Send + 'static>(mut self) -> Self {
+    self.factory = Arc::new(|hand: &PlayerHand| Box::new(P::new(hand)) as Box<dyn Player + Send>);
+    self
+  }
+
+  /** Sets how much the computer players may think in a hand. */
+  pub fn with_time_control(mut self, control: TimeControl) -> Self {
+    self.control = control;
+    self
+  }
+
+  fn create_table(&self) -> u32 {
+    let id = self.next_table.fetch_add(1, Ordering::Relaxed);
+    let (commands, receiver) = unbounded_channel();
+    let table = Table {
+      id,
+      seats: enum_map! { _ => Occupant::Empty },
+      computers: EnumMap::default(),
+      commands: commands.downgrade(),
+      game: None,
+      told_auction: false,
+      told_dummy: false,
+      rng: StdRng::from_entropy(),
+      factory: self.factory.clone(),
+      control: self.control,
+    };
+    tokio::spawn(table.run(receiver));
+    self.tables.lock().unwrap().insert(id, commands);
+    id
+  }
+
+  fn table(&self, id: u32) -> Option<UnboundedSender<Command>> {
+    self.tables.lock().unwrap().get(&id).cloned()
+  }
+
+  /** Accepts connections from `listener` until it fails, serving each on a task of its own. */
+  pub async fn listen(self: Arc<Self>, listener: TcpListener) -> std::io::Result<()> {
+    loop {
+      let (stream, _) = listener.accept().await?;
+      tokio::spawn(self.clone().serve(stream));
+    }
+  }
+
+  /** Serves one client, from the WebSocket handshake until it disconnects. Any stream will do, so
+      clients can also be connected in memory.
+   */
+  pub async fn serve<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(self: Arc<Self>, stream: S) {
+    let socket = match tokio_tungstenite::accept_async(stream).await {
+      Ok(socket) => socket,
+      Err(_) => return,
+    };
+    let (mut sink, mut source) = socket.split();
+    let (sender, mut outgoing) = unbounded_channel::<ServerMessage>();
+    tokio::spawn(async move {
+      while let Some(message) = outgoing.recv().await {
+        let text = serde_json::to_string(&message).expect("server messages are serializable");
+        if sink.send(Frame::Text(text)).await.is_err() {
+          break;
+        }
+      }
+    });
+    let id = self.next_client.fetch_add(1, Ordering::Relaxed);
+    let mut table: Option<UnboundedSender<Command>> = None;
+    while let Some(Ok(frame)) = source.next().await {
+      let text = match frame {
+        Frame::Text(text) => text,
+        Frame::Close(_) => break,
+        _ => continue,
+      };
+      let message = match serde_json::from_str::<ClientMessage>(&text) {
+        Ok(message) => message,
+        Err(err) => {
+          let _ = sender.send(ServerMessage::Error { message: format!("bad message: {}", err) });
+          continue;
+        }
+      };
+      let client = Client { id, sender: sender.clone() };
+      let (number, command) = match message {
+        ClientMessage::CreateTable => {
+          client.send(ServerMessage::TableCreated { table: self.create_table() });
+          continue;
+        }
+        ClientMessage::Join { table: number, seat, name } => (number, Command::Join { seat, name, client }),
+        ClientMessage::Rejoin { table: number, token } => (number, Command::Rejoin { token, client }),
+        message => match &table {
+          Some(commands) => {
+            let _ = commands.send(Command::Act { client, message });
+            continue;
+          }
+          None => {
+            client.send(error("join a table first"));
+            continue;
+          }
+        },
+      };
+      match self.table(number) {
+        Some(commands) => {
+          if let Some(previous) = table.replace(commands.clone()) {
+            let _ = previous.send(Command::Leave { client: id });
+          }
+          let _ = commands.send(command);
+        }
+        None => {
+          let _ = sender.send(error("there is no such table"));
+        }
+      }
+    }
+    if let Some(commands) = table {
+      let _ = commands.send(Command::Leave { client: id });
+    }
+  }
+}
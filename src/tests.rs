@@ -1,5 +1,13 @@
-use crate::game_model::bidding::{Auction, Bid, Call, Strain};
-use crate::game_model::{Board, dealer, Seat};
+use crate::game_model::bidding::{Auction, Bid, Call, Contract, DoubleLevel, Strain};
+use enum_map::EnumMap;
+use strum::IntoEnumIterator;
+use crate::game_model::{Board, BridgeGame, dealer, HandResult, Seat, Side, Vulnerability, vulnerability};
+use crate::game_model::cards::PlayerHand;
+use crate::game_model::history::{GameEvent, GameHistory};
+use crate::game_model::play::Play;
+use crate::game_model::rubber::{Line, RubberScore};
+use crate::game_model::notation::Notation;
+use crate::game_model::scoring;
 use crate::game_model::cards::{Card, Deck, Rank, Suit};
 
 #[test]
@@ -10,6 +18,25 @@ fn deal_hands() {
   }
 }
 
+#[test]
+fn board_vulnerability_cycle() {
+  use Vulnerability::*;
+  let expected = [
+    Neither, NS, EW, Both, NS, EW, Both, Neither,
+    EW, Both, Neither, NS, Both, Neither, NS, EW,
+  ];
+  for (i, &vul) in expected.iter().enumerate() {
+    let number = i as u32 + 1;
+    assert_eq!(vulnerability(number), vul);
+    assert_eq!(vulnerability(number + 16), vul);
+    let board = Board::new(number);
+    assert_eq!(board.vulnerability(), vul);
+    assert_eq!(board.dealer(), dealer(number));
+  }
+  assert!(NS.is_vulnerable(Seat::South) && !NS.is_vulnerable(Seat::West));
+  assert!(EW.is_vulnerable(Seat::East) && !EW.is_vulnerable(Seat::North));
+}
+
 #[test]
 fn make_some_bids() {
   let mut auction = Auction::new(dealer(1));
@@ -41,3 +68,1807 @@ fn make_some_plays() {
   play.make_play(Seat::North, Card::from(Suit::Spades, Rank::Ace));
   println!("{:?}", play.tricks()[0].winner(Strain::Notrump));
 }
+
+fn played(level: u8, strain: Strain, declarer: Seat, diff: i8) -> HandResult {
+  HandResult::Played(Contract::new(Bid::from(level, strain), DoubleLevel::Undoubled, declarer), diff)
+}
+
+#[test]
+fn imp_scale() {
+  let expected = [
+    (0, 0), (10, 0), (20, 1), (40, 1), (50, 2), (80, 2), (90, 3), (120, 3), (130, 4), (160, 4),
+    (170, 5), (210, 5), (220, 6), (260, 6), (270, 7), (310, 7), (320, 8), (360, 8), (370, 9),
+    (420, 9), (430, 10), (490, 10), (500, 11), (590, 11), (600, 12), (740, 12), (750, 13),
+    (890, 13), (900, 14), (1090, 14), (1100, 15), (1290, 15), (1300, 16), (1490, 16),
+    (1500, 17), (1740, 17), (1750, 18), (1990, 18), (2000, 19), (2240, 19), (2250, 20),
+    (2490, 20), (2500, 21), (2990, 21), (3000, 22), (3490, 22), (3500, 23), (3990, 23),
+    (4000, 24), (7600, 24),
+  ];
+  for (diff, imps) in expected {
+    assert_eq!(scoring::imps(diff), imps);
+    assert_eq!(scoring::imps(-diff), -imps);
+  }
+}
+
+#[test]
+fn imps_against_datum() {
+  let spades = Strain::Trump(Suit::Spades);
+  let results = [
+    played(4, spades, Seat::North, 0), // 420
+    played(4, spades, Seat::South, 0), // 420
+    played(3, Strain::Notrump, Seat::South, 0), // 400
+    played(4, spades, Seat::North, 1), // 450
+    played(4, spades, Seat::North, -1), // -50
+    played(4, Strain::Trump(Suit::Hearts), Seat::East, -2), // 100
+  ];
+  let vul = Vulnerability::Neither;
+  assert_eq!(scoring::ns_scores(&results, vul), vec![420, 420, 400, 450, -50, 100]);
+  // datum 1740 / 6 = 290
+  assert_eq!(scoring::datum(&scoring::ns_scores(&results, vul)), 290);
+  assert_eq!(scoring::imps_vs_datum(&results, vul), vec![4, 4, 3, 4, -8, -5]);
+  // Butler drops 450 and -50: 1340 / 4 = 335, rounded to 340
+  assert_eq!(scoring::butler(&results, vul), vec![2, 2, 2, 3, -9, -6]);
+}
+
+#[test]
+fn cross_imps_are_zero_sum() {
+  let spades = Strain::Trump(Suit::Spades);
+  let results = [
+    played(4, spades, Seat::North, 0), // 420
+    played(4, spades, Seat::North, -1), // -50
+    played(2, spades, Seat::West, -1), // 50
+  ];
+  let cross = scoring::cross_imps(&results, Vulnerability::Neither);
+  // 420 v -50 = 10, 420 v 50 = 9, -50 v 50 = -3
+  assert_eq!(cross, vec![19, -13, -6]);
+  assert_eq!(cross.iter().sum::<i32>(), 0);
+}
+
+#[test]
+fn matchpoints_and_neuberg() {
+  let spades = Strain::Trump(Suit::Spades);
+  let results = [
+    played(4, spades, Seat::North, 0), // 420
+    played(4, spades, Seat::South, 0), // 420
+    played(4, spades, Seat::North, 1), // 450
+    played(3, Strain::Notrump, Seat::North, -3), // -150
+    played(4, spades, Seat::North, -1), // -50
+  ];
+  let vul = Vulnerability::Neither;
+  let mps = scoring::matchpoints(&results, vul);
+  assert_eq!(mps, vec![5, 5, 8, 0, 2]);
+  assert_eq!(mps.iter().sum::<u32>(), 20);
+  // a board played 5 times in a field of 6: top of 8 becomes (8 + 1) * 6 / 5 - 1 = 9.8
+  let adjusted = scoring::neuberg(&results, vul, 6);
+  let expected = [6.2, 6.2, 9.8, 0.2, 2.6];
+  for (actual, expected) in adjusted.iter().zip(expected) {
+    assert!((actual - expected).abs() < 1e-9);
+  }
+}
+
+/** A board where North holds every spade, East every heart, South every diamond and West every
+    club.
+ */
+fn suited_board(number: u32) -> Board {
+  let suits = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+  let mut hands: EnumMap<Seat, PlayerHand> = EnumMap::default();
+  for (seat, suit) in Seat::iter().zip(suits) {
+    for rank in Rank::iter() {
+      hands[seat].add_card(Card::from(suit, rank));
+    }
+  }
+  Board::from_hands(number, hands)
+}
+
+fn doubled(level: u8, strain: Strain, declarer: Seat, diff: i8) -> HandResult {
+  HandResult::Played(Contract::new(Bid::from(level, strain), DoubleLevel::Doubled, declarer), diff)
+}
+
+#[test]
+fn rubber_score() {
+  let board = suited_board(1);
+  let mut rubber = RubberScore::rubber();
+  let deals = [
+    (played(2, Strain::Trump(Suit::Spades), Seat::North, 1), Vulnerability::Neither),
+    (doubled(3, Strain::Trump(Suit::Hearts), Seat::East, -2), Vulnerability::Neither),
+    (played(2, Strain::Notrump, Seat::South, 0), Vulnerability::Neither),
+    (played(5, Strain::Trump(Suit::Clubs), Seat::West, 0), Vulnerability::NS),
+    (doubled(4, Strain::Trump(Suit::Spades), Seat::North, 0), Vulnerability::Both),
+  ];
+  for (result, vul) in deals.iter() {
+    assert!(!rubber.is_complete());
+    assert_eq!(rubber.vulnerability(), *vul);
+    assert!(rubber.record_result(result, &board));
+  }
+  assert!(rubber.is_complete());
+  assert!(!rubber.record_result(&HandResult::Passout, &board));
+  assert_eq!(rubber.games_won(Side::NorthSouth), 2);
+  assert_eq!(rubber.games_won(Side::EastWest), 1);
+  // 60 + 70 + 240 below; 150 + 30, 300, 150 + 50 + 500 above
+  assert_eq!(rubber.line_total(Side::NorthSouth, Line::Below), 370);
+  assert_eq!(rubber.line_total(Side::NorthSouth, Line::Above), 1180);
+  // 100 below; 150 + 150 above
+  assert_eq!(rubber.line_total(Side::EastWest, Line::Below), 100);
+  assert_eq!(rubber.total(Side::EastWest), 400);
+}
+
+#[test]
+fn unfinished_rubber() {
+  let board = suited_board(1);
+  let mut rubber = RubberScore::rubber();
+  rubber.record_result(&played(3, Strain::Notrump, Seat::North, 0), &board);
+  rubber.record_result(&played(1, Strain::Notrump, Seat::East, 0), &board);
+  assert!(rubber.settle());
+  assert!(rubber.is_complete());
+  // 100 below, 300 for the game
+  assert_eq!(rubber.total(Side::NorthSouth), 400);
+  // 40 below, 100 for the part-score
+  assert_eq!(rubber.total(Side::EastWest), 140);
+}
+
+#[test]
+fn chicago_score() {
+  let board = suited_board(1);
+  let mut chicago = RubberScore::chicago();
+  let deals = [
+    (played(2, Strain::Trump(Suit::Spades), Seat::North, 0), Vulnerability::Neither),
+    (played(1, Strain::Notrump, Seat::East, 0), Vulnerability::EW),
+    (played(2, Strain::Trump(Suit::Spades), Seat::South, 1), Vulnerability::NS),
+    (played(2, Strain::Trump(Suit::Hearts), Seat::West, 0), Vulnerability::Both),
+  ];
+  for (result, vul) in deals.iter() {
+    assert_eq!(chicago.vulnerability(), *vul);
+    assert!(chicago.record_result(result, &board));
+  }
+  assert!(chicago.is_complete());
+  assert_eq!(chicago.games_won(Side::NorthSouth), 1);
+  // 60 + 60 below; 150 + 150 honours, 30 overtrick, 500 vulnerable game
+  assert_eq!(chicago.total(Side::NorthSouth), 950);
+  // 40 + 60 below; 150 honours, 100 for the part-score on the last deal
+  assert_eq!(chicago.total(Side::EastWest), 350);
+}
+
+#[test]
+fn record_passed_out_game() {
+  let mut game = BridgeGame::new(1);
+  for _ in 0..4 {
+    assert!(game.make_call(Call::Pass));
+  }
+  assert!(game.is_complete());
+  assert!(!game.make_call(Call::Pass));
+  let mut rubber = RubberScore::rubber();
+  assert!(rubber.record(&game));
+  assert_eq!(rubber.deals_played(), 1);
+  assert!(rubber.entries().is_empty());
+}
+
+#[test]
+fn game_rejects_revokes() {
+  use rand::SeedableRng;
+  let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+  let mut revokes = 0;
+  for number in 1..=20 {
+    let mut game = BridgeGame::from_board(Board::random(number, &mut rng));
+    let opener = game.auction().current_bidder();
+    assert!(game.make_call(Call::Bid(Bid::from(1, Strain::Notrump))));
+    for _ in 0..3 {
+      assert!(game.make_call(Call::Pass));
+    }
+    let leader = opener.next_seat();
+    let lead = game.board().player_hand(leader).cards()[0];
+    assert!(game.make_play(leader, lead));
+    let next = leader.next_seat();
+    let hand = game.board().player_hand(next).clone();
+    if hand.cards().iter().any(|card| card.suit() == lead.suit()) {
+      if let Some(&revoke) = hand.cards().iter().find(|card| card.suit() != lead.suit()) {
+        assert!(!game.make_play(next, revoke));
+        revokes += 1;
+      }
+    }
+    let follow = game.play().unwrap().legal_plays(&hand)[0];
+    assert!(game.make_play(next, follow));
+  }
+  assert!(revokes > 0);
+}
+
+/** Making scores from the official duplicate score table, indexed by doubling, vulnerability,
+    strain (minor, major, notrump) and level.
+ */
+const MAKING_SCORES: [[[[i32; 7]; 3]; 2]; 3] = [
+  [ // undoubled
+    [
+      [70, 90, 110, 130, 400, 920, 1440],
+      [80, 110, 140, 420, 450, 980, 1510],
+      [90, 120, 400, 430, 460, 990, 1520],
+    ],
+    [
+      [70, 90, 110, 130, 600, 1370, 2140],
+      [80, 110, 140, 620, 650, 1430, 2210],
+      [90, 120, 600, 630, 660, 1440, 2220],
+    ],
+  ],
+  [ // doubled
+    [
+      [140, 180, 470, 510, 550, 1090, 1630],
+      [160, 470, 530, 590, 650, 1210, 1770],
+      [180, 490, 550, 610, 670, 1230, 1790],
+    ],
+    [
+      [140, 180, 670, 710, 750, 1540, 2330],
+      [160, 670, 730, 790, 850, 1660, 2470],
+      [180, 690, 750, 810, 870, 1680, 2490],
+    ],
+  ],
+  [ // redoubled
+    [
+      [230, 560, 640, 720, 800, 1380, 1960],
+      [520, 640, 760, 880, 1000, 1620, 2240],
+      [560, 680, 800, 920, 1040, 1660, 2280],
+    ],
+    [
+      [230, 760, 840, 920, 1000, 1830, 2660],
+      [720, 840, 960, 1080, 1200, 2070, 2940],
+      [760, 880, 1000, 1120, 1240, 2110, 2980],
+    ],
+  ],
+];
+
+/** Penalties for going down one to thirteen tricks, indexed by doubling and vulnerability.
+ */
+const PENALTIES: [[[i32; 13]; 2]; 3] = [
+  [
+    [50, 100, 150, 200, 250, 300, 350, 400, 450, 500, 550, 600, 650],
+    [100, 200, 300, 400, 500, 600, 700, 800, 900, 1000, 1100, 1200, 1300],
+  ],
+  [
+    [100, 300, 500, 800, 1100, 1400, 1700, 2000, 2300, 2600, 2900, 3200, 3500],
+    [200, 500, 800, 1100, 1400, 1700, 2000, 2300, 2600, 2900, 3200, 3500, 3800],
+  ],
+  [
+    [200, 600, 1000, 1600, 2200, 2800, 3400, 4000, 4600, 5200, 5800, 6400, 7000],
+    [400, 1000, 1600, 2200, 2800, 3400, 4000, 4600, 5200, 5800, 6400, 7000, 7600],
+  ],
+];
+
+/** Value of each overtrick, indexed by doubling, vulnerability and strain.
+ */
+const OVERTRICKS: [[[i32; 3]; 2]; 3] = [
+  [[20, 30, 30], [20, 30, 30]],
+  [[100, 100, 100], [200, 200, 200]],
+  [[200, 200, 200], [400, 400, 400]],
+];
+
+#[test]
+fn contract_scores_match_official_table() {
+  let doublings = [DoubleLevel::Undoubled, DoubleLevel::Doubled, DoubleLevel::Redoubled];
+  let strains = [
+    (Strain::Trump(Suit::Clubs), 0),
+    (Strain::Trump(Suit::Diamonds), 0),
+    (Strain::Trump(Suit::Hearts), 1),
+    (Strain::Trump(Suit::Spades), 1),
+    (Strain::Notrump, 2),
+  ];
+  for (d, &doubling) in doublings.iter().enumerate() {
+    for (v, vul) in [Vulnerability::Neither, Vulnerability::Both].into_iter().enumerate() {
+      for &(strain, s) in strains.iter() {
+        for level in 1..=7u8 {
+          for declarer in Seat::iter() {
+            let contract = Contract::new(Bid::from(level, strain), doubling, declarer);
+            for tricks in 0..=13i8 {
+              let diff = tricks - (level as i8 + 6);
+              let expected = if diff < 0 {
+                -PENALTIES[d][v][(-diff - 1) as usize]
+              } else {
+                MAKING_SCORES[d][v][s][level as usize - 1] + OVERTRICKS[d][v][s] * diff as i32
+              };
+              assert_eq!(contract.score(diff, vul), expected, "{}{:?} {:?} {}", contract.level(),
+                         strain, doubling, diff);
+            }
+          }
+        }
+      }
+    }
+  }
+}
+
+#[test]
+fn scores_are_monotonic_and_antisymmetric() {
+  let doublings = [DoubleLevel::Undoubled, DoubleLevel::Doubled, DoubleLevel::Redoubled];
+  let vuls = [Vulnerability::Neither, Vulnerability::NS, Vulnerability::EW, Vulnerability::Both];
+  let strains = [
+    Strain::Trump(Suit::Clubs),
+    Strain::Trump(Suit::Diamonds),
+    Strain::Trump(Suit::Hearts),
+    Strain::Trump(Suit::Spades),
+    Strain::Notrump,
+  ];
+  for &doubling in doublings.iter() {
+    for vul in vuls {
+      for &strain in strains.iter() {
+        for level in 1..=7u8 {
+          for declarer in Seat::iter() {
+            let contract = Contract::new(Bid::from(level, strain), doubling, declarer);
+            let mut previous = None;
+            for tricks in 0..=13i8 {
+              let diff = tricks - (level as i8 + 6);
+              let result = HandResult::Played(contract, diff);
+              let score = result.score(vul);
+              assert_eq!(score > 0, diff >= 0);
+              if let Some(previous) = previous {
+                assert!(score > previous);
+              }
+              previous = Some(score);
+              // the same result from the other side of the table
+              let ns = result.ns_score(vul);
+              assert_eq!(ns, if declarer.side() == Side::NorthSouth { score } else { -score });
+            }
+          }
+        }
+      }
+    }
+  }
+}
+
+fn three_notrump_by_north() -> Play {
+  let mut auction = Auction::new(dealer(1));
+  auction.make_call(Call::Bid(Bid::from(3, Strain::Notrump)));
+  auction.make_call(Call::Pass);
+  auction.make_call(Call::Pass);
+  auction.make_call(Call::Pass);
+  auction.play().unwrap()
+}
+
+#[test]
+fn accepted_claim_ends_play() {
+  let mut play = three_notrump_by_north();
+  assert_eq!(play.next_player(), Seat::East);
+  play.make_play(Seat::East, Card::from(Suit::Spades, Rank::Queen));
+  assert_eq!(play.next_player(), Seat::South);
+  assert!(!play.claim(Seat::South, 9, "dummy cannot claim"));
+  assert!(!play.claim(Seat::North, 14, "too many"));
+  assert!(play.claim(Seat::North, 9, "nine top tricks"));
+  assert!(!play.make_play(Seat::South, Card::from(Suit::Spades, Rank::Two)));
+  assert!(!play.accept_claim(Seat::South));
+  assert!(play.accept_claim(Seat::East));
+  assert!(play.result().is_none());
+  assert!(play.accept_claim(Seat::West));
+  assert!(play.is_complete());
+  assert_eq!(play.settled_claim().map(|c| c.tricks()), Some(9));
+  assert_eq!(play.declarer_tricks(), 9);
+  assert!(matches!(play.result(), Some(HandResult::Played(_, 0))));
+}
+
+#[test]
+fn disputed_claim_resumes_play() {
+  let mut play = three_notrump_by_north();
+  assert!(play.claim(Seat::West, 5, "the setting tricks"));
+  assert!(play.pending_claim().unwrap().is_responder(Seat::North, Seat::North));
+  assert!(!play.dispute_claim(Seat::South));
+  assert!(play.dispute_claim(Seat::North));
+  assert!(play.pending_claim().is_none());
+  assert!(play.make_play(Seat::East, Card::from(Suit::Hearts, Rank::Four)));
+}
+
+#[test]
+fn concession_takes_effect_immediately() {
+  let mut play = three_notrump_by_north();
+  assert!(play.concede(Seat::North, 5));
+  assert!(play.is_complete());
+  assert_eq!(play.declarer_tricks(), 8);
+  assert!(matches!(play.result(), Some(HandResult::Played(_, -1))));
+  assert!(play.settled_claim().unwrap().is_concession());
+
+  let mut play = three_notrump_by_north();
+  assert!(play.claim(Seat::North, 8, "concedes"));
+  assert!(!play.pending_claim().unwrap().is_concession());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn concessions_are_recorded() {
+  let mut play = three_notrump_by_north();
+  assert!(play.concede(Seat::North, 5));
+  let replayed: Play = serde_json::from_value(serde_json::to_value(&play).unwrap()).unwrap();
+  assert!(replayed.is_complete());
+  assert_eq!(replayed.settled_claim(), play.settled_claim());
+
+  // a claim is not taken for a concession by its statement
+  let mut play = three_notrump_by_north();
+  assert!(play.claim(Seat::North, 8, "concedes"));
+  let replayed: Play = serde_json::from_value(serde_json::to_value(&play).unwrap()).unwrap();
+  assert!(!replayed.is_complete());
+  assert_eq!(replayed.pending_claim(), play.pending_claim());
+}
+
+#[test]
+fn undo_calls() {
+  let mut auction = Auction::new(dealer(1));
+  auction.make_call(Call::Bid(Bid::from(1, Strain::Trump(Suit::Spades))));
+  auction.make_call(Call::Double);
+  assert!(!auction.make_call(Call::Double));
+  assert_eq!(auction.undo(), Some(Call::Double));
+  assert_eq!(auction.current_bidder(), Seat::East);
+  assert!(auction.make_call(Call::Double));
+  auction.make_call(Call::Pass);
+  auction.make_call(Call::Bid(Bid::from(4, Strain::Trump(Suit::Spades))));
+  auction.make_call(Call::Pass);
+  auction.make_call(Call::Pass);
+  auction.make_call(Call::Pass);
+  assert!(auction.is_complete());
+  assert_eq!(auction.undo(), Some(Call::Pass));
+  assert!(!auction.is_complete());
+  assert_eq!(auction.undo(), Some(Call::Pass));
+  assert_eq!(auction.undo(), Some(Call::Pass));
+  assert_eq!(auction.undo(), Some(Call::Bid(Bid::from(4, Strain::Trump(Suit::Spades)))));
+  // the double of 1S is back in force, so West cannot double again but North may redouble
+  assert_eq!(auction.current_bidder(), Seat::West);
+  assert!(!auction.make_call(Call::Double));
+  assert!(auction.make_call(Call::Pass));
+  assert!(auction.make_call(Call::Redouble));
+  assert_eq!(auction.len(), 5);
+  let mut empty = Auction::new(Seat::North);
+  assert_eq!(empty.undo(), None);
+}
+
+#[test]
+fn undo_cards() {
+  let mut play = three_notrump_by_north();
+  let cards = [
+    (Seat::East, Card::from(Suit::Spades, Rank::Queen)),
+    (Seat::South, Card::from(Suit::Spades, Rank::Two)),
+    (Seat::West, Card::from(Suit::Spades, Rank::Five)),
+    (Seat::North, Card::from(Suit::Spades, Rank::Ace)),
+    (Seat::North, Card::from(Suit::Hearts, Rank::Two)),
+  ];
+  assert!(!play.make_play(Seat::West, cards[0].1));
+  for (seat, card) in cards {
+    assert!(play.make_play(seat, card));
+  }
+  assert_eq!(play.declarer_tricks(), 1);
+  assert_eq!(play.next_player(), Seat::East);
+  for &(seat, card) in cards.iter().rev() {
+    assert_eq!(play.undo(), Some((seat, card)));
+    assert!(play.make_play(seat, card));
+    assert_eq!(play.undo(), Some((seat, card)));
+  }
+  assert_eq!(play.undo(), None);
+  assert_eq!(play.cards_played(), 0);
+  assert_eq!(play.declarer_tricks(), 0);
+  assert_eq!(play.next_player(), Seat::East);
+}
+
+#[test]
+fn seek_through_history() {
+  let mut history = GameHistory::new(dealer(1));
+  let calls = [
+    Call::Bid(Bid::from(1, Strain::Notrump)),
+    Call::Pass,
+    Call::Bid(Bid::from(3, Strain::Notrump)),
+    Call::Pass,
+    Call::Pass,
+    Call::Pass,
+  ];
+  for call in calls {
+    assert!(history.record(GameEvent::Call(call)));
+  }
+  assert!(history.play().is_some());
+  let cards = [
+    (Seat::East, Card::from(Suit::Spades, Rank::Queen)),
+    (Seat::South, Card::from(Suit::Spades, Rank::Two)),
+    (Seat::West, Card::from(Suit::Spades, Rank::Five)),
+    (Seat::North, Card::from(Suit::Spades, Rank::Ace)),
+  ];
+  for (seat, card) in cards {
+    assert!(history.record(GameEvent::Card(seat, card)));
+  }
+  assert_eq!(history.len(), 10);
+
+  history.seek_call(2);
+  assert_eq!(history.auction().len(), 2);
+  assert!(history.play().is_none());
+  history.seek_card(3);
+  assert_eq!(history.play().unwrap().cards_played(), 3);
+  assert_eq!(history.play().unwrap().next_player(), Seat::North);
+  history.seek(usize::MAX);
+  assert_eq!(history.cursor(), 10);
+  assert_eq!(history.play().unwrap().declarer_tricks(), 1);
+
+  history.seek(7);
+  assert!(!history.record(GameEvent::Card(Seat::East, Card::from(Suit::Spades, Rank::Three))));
+  assert!(history.record(GameEvent::Card(Seat::South, Card::from(Suit::Spades, Rank::Three))));
+  assert_eq!(history.len(), 8);
+  assert!(!history.step_forward());
+  history.seek(0);
+  assert!(history.auction().is_empty());
+  assert!(!history.step_back());
+}
+
+#[test]
+fn notation_round_trips() {
+  for suit in Suit::iter() {
+    for rank in Rank::iter() {
+      let card = Card::from(suit, rank);
+      assert_eq!(Card::from_notation(&card.notation()), Some(card));
+    }
+  }
+  assert_eq!(Card::from_notation("♠a"), Some(Card::from(Suit::Spades, Rank::Ace)));
+  assert_eq!(Call::from_notation("3NT"), Some(Call::Bid(Bid::from(3, Strain::Notrump))));
+  assert_eq!(Call::from_notation("pass"), Some(Call::Pass));
+  assert_eq!(Call::from_notation("XX").map(|call| call.notation()), Some("XX".to_string()));
+  assert_eq!(Call::from_notation("8C"), None);
+  let result = doubled(4, Strain::Trump(Suit::Hearts), Seat::East, -2);
+  assert_eq!(result.notation(), "4HX E -2");
+  assert_eq!(HandResult::from_notation("4HX E -2"), Some(result));
+  assert_eq!(HandResult::from_notation("3NT S =").unwrap(), played(3, Strain::Notrump, Seat::South, 0));
+  assert_eq!(HandResult::from_notation("7NT N +1"), None);
+  let board = suited_board(1);
+  assert_eq!(board.player_hand(Seat::North).notation(), "AKQJT98765432...");
+  let hand = PlayerHand::from_notation("AKQ2.T98.765.432").unwrap();
+  assert_eq!(hand.len(), 13);
+  assert_eq!(hand.notation(), "AKQ2.T98.765.432");
+  assert!(PlayerHand::from_notation("AA.T98.765.432").is_none());
+}
+
+/** North declares one spade on the suited board, ruffs the heart lead, cashes a trump and claims
+    the rest.
+ */
+#[cfg(feature = "serde")]
+fn claimed_spade_partscore() -> BridgeGame {
+  let mut game = BridgeGame::from_board(suited_board(1));
+  for call in [Call::Bid(Bid::from(1, Strain::Trump(Suit::Spades))), Call::Pass, Call::Pass, Call::Pass] {
+    assert!(game.make_call(call));
+  }
+  let cards = [
+    (Seat::East, Card::from(Suit::Hearts, Rank::Ace)),
+    (Seat::South, Card::from(Suit::Diamonds, Rank::Two)),
+    (Seat::West, Card::from(Suit::Clubs, Rank::Two)),
+    (Seat::North, Card::from(Suit::Spades, Rank::Two)),
+    (Seat::North, Card::from(Suit::Spades, Rank::Ace)),
+  ];
+  for (seat, card) in cards {
+    assert!(game.make_play(seat, card));
+  }
+  assert!(game.claim(Seat::North, 12, "all trumps"));
+  assert!(game.accept_claim(Seat::East));
+  assert!(game.accept_claim(Seat::West));
+  game
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn game_record_round_trip() {
+  use crate::game_model::record::{Annotation, AnnotationTarget, GameRecord};
+  let game = claimed_spade_partscore();
+  assert_eq!(game.result(), Some(&played(1, Strain::Trump(Suit::Spades), Seat::North, 6)));
+  let mut record = GameRecord::from_game(&game);
+  record.players[Seat::North] = Some("Alice".to_string());
+  record.started_at = Some(1_700_000_000);
+  record.annotations.push(Annotation { target: AnnotationTarget::Card(3), text: "ruff".to_string() });
+  let json = record.to_json();
+  assert!(json.contains("\"1S\"") && json.contains("\"HA\"") && json.contains("\"1S N +6\""));
+
+  let read = GameRecord::from_json(&json).unwrap();
+  assert_eq!(read.players[Seat::North].as_deref(), Some("Alice"));
+  assert_eq!(read.annotations, record.annotations);
+  let replayed = read.to_game().unwrap();
+  assert_eq!(replayed.result(), game.result());
+  assert_eq!(replayed.play().unwrap().settled_claim(), game.play().unwrap().settled_claim());
+  let rewritten = serde_json::to_value(GameRecord::from_game(&replayed)).unwrap();
+  let original = serde_json::to_value(GameRecord::from_game(&game)).unwrap();
+  assert_eq!(rewritten, original);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn game_records_are_validated() {
+  use crate::game_model::record::{GameRecord, RecordError};
+  let json = GameRecord::from_game(&claimed_spade_partscore()).to_json();
+  let newer = json.replacen("\"version\": 1", "\"version\": 2", 1);
+  assert!(matches!(GameRecord::from_json(&newer), Err(RecordError::UnsupportedVersion(2))));
+  let out_of_turn = json.replacen("\"leader\": \"E\"", "\"leader\": \"W\"", 1);
+  assert!(matches!(GameRecord::from_json(&out_of_turn), Err(RecordError::Json(_))));
+  let wrong_card = json.replacen("\"HA\"", "\"DA\"", 1);
+  let record = GameRecord::from_json(&wrong_card).unwrap();
+  assert!(matches!(record.to_game(), Err(RecordError::IllegalGame(_))));
+}
+
+#[test]
+fn sampled_deals_fit_the_auction() {
+  use rand::SeedableRng;
+  use crate::ai::sampling::{DealSampler, infer_constraints};
+  let mut auction = Auction::new(Seat::North);
+  for call in [Call::Pass, Call::Bid(Bid::from(1, Strain::Trump(Suit::Hearts))), Call::Pass,
+               Call::Bid(Bid::from(4, Strain::Trump(Suit::Hearts))), Call::Pass, Call::Pass, Call::Pass] {
+    assert!(auction.make_call(call));
+  }
+  let constraints = infer_constraints(&auction);
+  assert_eq!(constraints[Seat::North].hcp(), (0, 11));
+  assert_eq!(constraints[Seat::East].length(Suit::Hearts), (5, 13));
+  assert_eq!(constraints[Seat::West].length(Suit::Hearts), (4, 13));
+  assert_eq!(constraints[Seat::South].hcp(), (0, 37));
+
+  let south = PlayerHand::from_notation("AKQ2.T98.765.432").unwrap();
+  let sampler = DealSampler::new().with_all_constraints(constraints).with_known_hand(Seat::South, &south);
+  let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+  let deals = sampler.samples(20, &mut rng);
+  assert_eq!(deals.len(), 20);
+  for hands in deals {
+    assert_eq!(hands[Seat::South].notation(), south.notation());
+    assert_eq!(hands.values().map(PlayerHand::len).sum::<usize>(), 52);
+    for seat in Seat::iter() {
+      assert!(constraints[seat].accepts(&hands[seat]));
+    }
+  }
+
+  // a known hand need not fit what its own calls showed
+  let east = PlayerHand::from_notation("AKQ2.T98.765.432").unwrap();
+  let sampler = DealSampler::new().with_all_constraints(constraints).with_known_hand(Seat::East, &east);
+  assert_eq!(sampler.samples(5, &mut rng).len(), 5);
+
+  // nor does the opening leader's
+  let auction = auction_of(Seat::West, &["1S", "P", "P", "2H", "P", "P", "P"]);
+  let west = PlayerHand::from_notation("AKQ.T98.7652.432").unwrap();
+  let leads = crate::ai::leads::analyse_leads(&auction, &west, Vulnerability::Neither, 5, &mut rng);
+  assert!(!matches!(leads, Err(crate::ai::leads::LeadError::NoSamples)));
+}
+
+#[test]
+fn lead_report_summarizes_samples() {
+  use crate::ai::leads::{LeadMetric, LeadReport};
+  let contract = Contract::new(Bid::from(4, Strain::Trump(Suit::Spades)), DoubleLevel::Undoubled, Seat::South);
+  let leads = [Card::from(Suit::Hearts, Rank::King), Card::from(Suit::Clubs, Rank::Two)];
+  let tricks = vec![vec![4, 3], vec![3, 3], vec![2, 4]];
+  let report = LeadReport::from_tricks(contract, Vulnerability::Neither, &leads, &tricks);
+  let [king, two] = report.leads() else { panic!() };
+  assert_eq!(king.average_tricks, 3.0);
+  assert_eq!(two.average_tricks, 10.0 / 3.0);
+  assert_eq!(king.set_probability, 1.0 / 3.0);
+  assert_eq!(two.set_probability, 1.0 / 3.0);
+  assert_eq!(king.matchpoints + two.matchpoints, 1.0);
+  assert_eq!(king.imps, -two.imps);
+  assert_eq!(report.best(LeadMetric::Tricks).unwrap().card, leads[1]);
+}
+
+fn auction_of(dealer: Seat, calls: &[&str]) -> Auction {
+  let mut auction = Auction::new(dealer);
+  for call in calls {
+    assert!(auction.make_call(Call::from_notation(call).unwrap()), "{} is illegal", call);
+  }
+  auction
+}
+
+#[test]
+fn auction_patterns() {
+  use crate::ai::bidding::AuctionPattern;
+  let stayman = AuctionPattern::parse("1N-2C").unwrap();
+  assert_eq!(stayman.to_string(), "1NT-2C");
+  assert!(stayman.matches(&auction_of(Seat::North, &["P", "1N", "P", "2C", "P"])));
+  assert!(!stayman.matches(&auction_of(Seat::North, &["1N", "P", "2C"])));
+  assert!(!stayman.matches(&auction_of(Seat::North, &["1N", "X", "2C", "P"])));
+  let overcall = AuctionPattern::parse("(1H)-X").unwrap();
+  assert!(overcall.matches(&auction_of(Seat::North, &["1H", "X", "P"])));
+  assert!(AuctionPattern::parse("").unwrap().matches(&auction_of(Seat::North, &["P", "P"])));
+  let blackwood = AuctionPattern::parse("...-4N").unwrap();
+  assert!(blackwood.matches(&auction_of(Seat::North, &["1S", "P", "3S", "P", "4N", "P"])));
+  assert!(AuctionPattern::parse("1N-2Z").is_none());
+}
+
+#[test]
+fn sayc_bids_standard_hands() {
+  use crate::ai::bidding::{balanced, hcp, length};
+  use crate::ai::bidding::sayc::sayc;
+  let system = sayc();
+  let choose = |auction: &Auction, hand: &str| {
+    system.choose(auction, &PlayerHand::from_notation(hand).unwrap()).call.notation()
+  };
+  let opening = Auction::new(Seat::North);
+  assert_eq!(choose(&opening, "AQ2.KJ5.QT94.A32"), "1NT");
+  assert_eq!(choose(&opening, "AKJ52.K5.Q94.632"), "1S");
+  assert_eq!(choose(&opening, "AK5.K5.Q942.J632"), "1D");
+  assert_eq!(choose(&opening, "KQJ974.52.J94.63"), "2S");
+  assert_eq!(choose(&opening, "K75.852.J94.Q632"), "P");
+
+  let over_notrump = auction_of(Seat::North, &["1N", "P"]);
+  assert_eq!(choose(&over_notrump, "KJ52.Q5.K942.632"), "2C");
+  assert_eq!(choose(&over_notrump, "K5.QJ852.942.632"), "2D");
+  assert_eq!(choose(&auction_of(Seat::North, &["1N", "P", "2C", "P"]), "AQ2.KJ54.QT9.A32"), "2H");
+  assert_eq!(choose(&auction_of(Seat::North, &["1N", "P", "2D", "P"]), "AQ2.K4.QT95.A432"), "2H");
+  assert_eq!(choose(&auction_of(Seat::North, &["1S", "P", "3S", "P", "4N", "P"]), "K5.QJ852.942.632"), "5C");
+  assert_eq!(choose(&over_notrump, "AKQ.KQ5.KJ42.Q32"), "6NT");
+  assert_eq!(choose(&auction_of(Seat::North, &["1N", "P", "2C", "P", "2D", "P"]), "AKQ2.KQ5.KJ4.Q32"), "6NT");
+  assert_eq!(choose(&auction_of(Seat::North, &["1N", "P", "2C", "P", "2S", "P"]), "AKQ2.KQ5.KJ4.Q32"), "6S");
+  assert_eq!(choose(&auction_of(Seat::North, &["1N", "P", "2D", "P", "2H", "P"]), "AK.KQ852.KJ4.Q32"), "6NT");
+  assert_eq!(choose(&auction_of(Seat::North, &["2N", "P", "3C", "P", "3D", "P"]), "KQ2.K952.KJ4.Q32"), "6NT");
+  assert_eq!(choose(&auction_of(Seat::North, &["1C", "X"]), "K5.Q52.942.QJ632"), "P");
+  assert_eq!(choose(&auction_of(Seat::North, &["1H"]), "AQ52.5.KJ42.Q632"), "X");
+
+  let hand = PlayerHand::from_notation("KJ52.Q5.K942.632").unwrap();
+  let choice = system.choose(&over_notrump, &hand);
+  assert!(choice.explanation.contains("Stayman"), "{}", choice.explanation);
+  let stayman = system.explain(&over_notrump, choice.call).unwrap();
+  assert_eq!(stayman.constraint.to_string(), "hcp 8+ & (4 S | 4 H)");
+  assert_eq!((hcp(15, 17) & balanced() & !length(Suit::Hearts, 5, 13)).to_string(),
+             "hcp 15-17 & balanced & !5+ H");
+}
+
+#[test]
+fn sayc_auctions_finish_legally() {
+  use crate::ai::bidding::sayc::sayc;
+  let system = sayc();
+  for number in 1..=200 {
+    let board = Board::new(number);
+    let mut auction = Auction::new(board.dealer());
+    while !auction.is_complete() {
+      assert!(auction.len() < 40, "runaway auction {}", auction);
+      let seat = auction.current_bidder();
+      let choice = system.choose(&auction, board.player_hand(seat));
+      assert!(auction.make_call(choice.call), "{} chose an illegal call", choice.explanation);
+    }
+  }
+}
+
+#[test]
+fn bidding_system_files() {
+  use crate::ai::bidding::Constraint;
+  use crate::ai::bidding::format::load;
+  use crate::ai::bidding::sayc::sayc;
+  let text = "hcp 8+ & (4 S | 4 H) & !balanced & S >= H";
+  assert_eq!(Constraint::parse(text).unwrap().to_string(), text);
+  assert!(Constraint::parse("hcp 8+ &").is_err());
+  assert!(Constraint::parse("4 Z").is_err());
+
+  let system = load("system Test\n\nafter 1N-2C\n  2D [alert priority 2] any ; no major\n").unwrap();
+  assert_eq!(system.name(), "Test");
+  assert!(system.rules()[0].alertable);
+  assert_eq!(system.rules()[0].priority, 2);
+  assert_eq!(load("opening\n  1N hcp 15-17\n  2Q any").unwrap_err().line, 3);
+  assert_eq!(load("  1N hcp 15-17").unwrap_err().line, 1);
+
+  let sayc = sayc();
+  assert_eq!(load(&sayc.to_text()).unwrap().to_text(), sayc.to_text());
+  let unnamed = load("opening\n  1N hcp 15-17\n").unwrap();
+  assert_eq!(load(&unnamed.to_text()).unwrap().rules(), unnamed.rules());
+}
+
+#[test]
+fn bidding_system_validation() {
+  use crate::ai::bidding::format::load;
+  use crate::ai::bidding::sayc::sayc;
+  use crate::ai::bidding::validate::validate;
+  let system = load("opening\n  1N hcp 15-17\n  1S hcp 12+ & 5+ S\n  2C hcp 15+\n  X any\n").unwrap();
+  let reports = validate(&system);
+  assert_eq!(reports.len(), 1);
+  let report = &reports[0];
+  assert!(report.uncovered > 0);
+  assert!(report.uncovered_example.unwrap().hcp < 12);
+  assert!(report.overlaps.iter().any(|o| (o.first, o.second) == (0, 1)));
+  assert_eq!(report.illegal, vec![3]);
+  assert!(!report.is_clean());
+
+  let opening = validate(&sayc()).into_iter().find(|report| report.pattern.to_string().is_empty()).unwrap();
+  assert_eq!(opening.uncovered, 0);
+  assert!(opening.illegal.is_empty());
+  // notrump auctions give every hand a call, slam hands included
+  let notrump = validate(&sayc()).into_iter()
+      .filter(|report| ["1NT", "2NT"].iter().any(|&opening| report.pattern.to_string().starts_with(opening)))
+      .collect::<Vec<_>>();
+  assert!(notrump.len() > 40);
+  for report in notrump {
+    assert_eq!(report.uncovered, 0, "{}", report);
+    assert!(report.illegal.is_empty(), "{}", report);
+  }
+}
+
+#[test]
+fn call_annotations() {
+  use crate::ai::alpha_mu::AlphaMuPlayer;
+  use crate::game_model::bidding::{CallAnnotation, CallMeaning};
+  use crate::game_model::{lin, pbn};
+  use crate::interface::Player;
+  let meaning = CallMeaning::from_notation("hcp 15-17, 2-5 S, 5+ H").unwrap();
+  assert_eq!(meaning.notation(), "hcp 15-17, 2-5 S, 5+ H");
+  assert_eq!(CallMeaning::default().notation(), "any");
+
+  let mut auction = auction_of(Seat::North, &["1N", "P"]);
+  let transfer = CallAnnotation::explained("transfer to hearts").alerted()
+      .with_meaning(CallMeaning::from_notation("5+ H").unwrap());
+  assert!(auction.make_annotated_call(Call::from_notation("2D").unwrap(), Some(transfer.clone())));
+  assert!(auction.make_call(Call::Pass));
+  assert!(auction.annotate(0, CallAnnotation::explained("15-17").with_meaning(meaning)));
+  assert!(auction.is_alerted(2));
+  assert_eq!(auction.explanation(0), Some("15-17"));
+  assert_eq!(auction.bidder(2), Seat::South);
+  assert_eq!(auction.to_string().matches('!').count(), 1);
+  assert_eq!(auction.undo(), Some(Call::Pass));
+  assert_eq!(auction.annotation(2), Some(&transfer));
+  assert!(auction.make_call(Call::Pass));
+
+  assert!(auction.disclosed_to(Seat::North).annotation(2).is_none());
+  assert!(auction.disclosed_to(Seat::South).annotation(0).is_none());
+  assert_eq!(auction.disclosed_to(Seat::East).annotation(2), Some(&transfer));
+
+  let annotations = |auction: &Auction| {
+    let calls: Vec<Call> = auction.calls().copied().collect();
+    (calls, (0..auction.len()).map(|i| auction.annotation(i).cloned()).collect::<Vec<_>>())
+  };
+  let text = pbn::write_auction(&auction);
+  assert!(text.contains("2D! =2="), "{}", text);
+  assert_eq!(annotations(&pbn::read_auction(&text).unwrap()), annotations(&auction));
+  let text = lin::write_auction(&auction);
+  assert!(text.starts_with("mb|1N|an|15-17 {hcp 15-17, 2-5 S, 5+ H}|mb|p|mb|2D!|"), "{}", text);
+  assert_eq!(annotations(&lin::read_auction(&text, Seat::North).unwrap()), annotations(&auction));
+  let passed = pbn::read_auction("[Dealer \"E\"]\n[Auction \"E\"]\n1C Pass 1H AP\n[Play \"S\"]").unwrap();
+  assert!(passed.is_complete());
+  assert!(pbn::read_auction("[Auction \"N\"]\n1C 1C").is_none());
+
+  let opener = AlphaMuPlayer::new(&PlayerHand::from_notation("AQ2.KJ5.QT94.A32").unwrap());
+  let explained = opener.explain_call(&Auction::new(Seat::North), Call::from_notation("1N").unwrap()).unwrap();
+  assert!(!explained.alert);
+  assert_eq!(explained.meaning.unwrap().notation(), "hcp 15-17, 2-5 S, 2-5 H, 2-5 D, 2-5 C");
+
+  #[cfg(feature = "serde")] {
+    let json = serde_json::to_string(&auction).unwrap();
+    let read: Auction = serde_json::from_str(&json).unwrap();
+    assert_eq!(annotations(&read), annotations(&auction));
+  }
+}
+
+#[test]
+fn simulated_bidding_scores_final_contracts() {
+  use crate::ai::bidding::simulation::{BiddingReport, candidate_calls, final_contract, simulate_calls, SimulationError};
+  use crate::ai::dds::DoubleDummyTable;
+  use crate::game_model::scoring::ScoringForm;
+  let auction = auction_of(Seat::North, &["1S", "2H", "2S", "3H"]);
+  let contract = |text| Some(Contract::from_notation(text).unwrap());
+  assert_eq!(final_contract(&auction, Call::Pass), Some(contract("3H E")));
+  assert_eq!(final_contract(&auction, Call::Double), Some(contract("3HX E")));
+  assert_eq!(final_contract(&auction, Call::from_notation("3S").unwrap()), Some(contract("3S N")));
+  assert_eq!(final_contract(&auction, Call::Redouble), None);
+  assert_eq!(final_contract(&Auction::new(Seat::East), Call::Pass), Some(None));
+  let candidates = candidate_calls(&auction);
+  assert!(candidates.contains(&Call::from_notation("4S").unwrap()));
+  assert!(candidates.contains(&Call::from_notation("3NT").unwrap()));
+  assert!(!candidates.contains(&Call::from_notation("3D").unwrap()));
+  assert!(!candidates.contains(&Call::Redouble));
+
+  let mut table = DoubleDummyTable::default();
+  for seat in [Seat::North, Seat::South] {
+    table[Strain::Trump(Suit::Spades)][seat] = 9;
+  }
+  for seat in [Seat::East, Seat::West] {
+    table[Strain::Trump(Suit::Hearts)][seat] = 9;
+  }
+  let calls: Vec<Call> = ["P", "X", "3S", "XX"].iter().map(|c| Call::from_notation(c).unwrap()).collect();
+  let report = BiddingReport::from_tables(&auction, Vulnerability::Neither, &calls, &[table]);
+  assert_eq!(report.calls().len(), 3);
+  let scores: Vec<f64> = report.calls().iter().map(|c| c.average_score).collect();
+  assert_eq!(scores, vec![-140.0, -530.0, 140.0]);
+  for form in [ScoringForm::Imps, ScoringForm::Matchpoints, ScoringForm::TotalPoints] {
+    assert_eq!(report.best(form).unwrap().call.notation(), "3S");
+  }
+
+  // the bidder's hand need not fit what the bidder's own calls showed
+  let auction = auction_of(Seat::North, &["1S", "P", "2S", "P"]);
+  let north = PlayerHand::from_notation("AKQ.KQ2.A432.432").unwrap();
+  let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(5);
+  let calls = [Call::Pass, Call::from_notation("3S").unwrap()];
+  let simulated = simulate_calls(&auction, &north, Vulnerability::Neither, &calls, 5, &mut rng);
+  assert!(!matches!(simulated, Err(SimulationError::NoSamples)));
+}
+
+#[test]
+fn defenders_signal_and_read_signals() {
+  use rand::SeedableRng;
+  use rand::rngs::StdRng;
+  use crate::ai::sampling::DealSampler;
+  use crate::ai::signals::*;
+  let ranks = |text: &str| text.chars().map(|c| Rank::try_from(c).unwrap()).collect::<Vec<_>>();
+  assert_eq!(lead_from(&ranks("KQ53"), LeadConvention::FourthBest), Some(Rank::King));
+  assert_eq!(lead_from(&ranks("KT853"), LeadConvention::FourthBest), Some(Rank::Five));
+  assert_eq!(lead_from(&ranks("KT853"), LeadConvention::ThirdAndFifth), Some(Rank::Three));
+  assert_eq!(lead_from(&ranks("K853"), LeadConvention::ThirdAndFifth), Some(Rank::Five));
+  assert_eq!(lead_from(&ranks("852"), LeadConvention::FourthBest), Some(Rank::Eight));
+  assert_eq!(lead_from(&ranks("K52"), LeadConvention::FourthBest), Some(Rank::Two));
+
+  let card = |text: &str| Card::from_notation(text).unwrap();
+  let mut play = Play::new(Contract::from_notation("4S S").unwrap());
+  assert!(play.make_play(Seat::West, card("HK")));
+  assert!(play.make_play(Seat::North, card("H2")));
+  let equals = [card("H8"), card("H3")];
+  let with_ace = PlayerHand::from_notation("A54.A83.K942.632").unwrap();
+  let without = PlayerHand::from_notation("A54.J83.K942.632").unwrap();
+  let standard = Carding::default();
+  let upside_down = Carding { style: SignalStyle::UpsideDown, ..Carding::default() };
+  assert_eq!(choose_equal_card(&equals, &play, &with_ace, &standard), Some(card("H8")));
+  assert_eq!(choose_equal_card(&equals, &play, &without, &standard), Some(card("H3")));
+  assert_eq!(choose_equal_card(&equals, &play, &with_ace, &upside_down), Some(card("H3")));
+
+  assert!(play.make_play(Seat::East, card("H8")));
+  let readings = read_signals(&play, Seat::East, &standard);
+  assert_eq!(readings.len(), 1);
+  assert_eq!(readings[0].signal, Signal::Encourage(true));
+  assert!(readings[0].agrees_with(&with_ace));
+  assert!(!readings[0].agrees_with(&without));
+  assert!(read_signals(&play, Seat::West, &standard).is_empty());
+
+  let mut rng = StdRng::seed_from_u64(38);
+  let deals = DealSampler::new().with_signals(readings).samples(400, &mut rng);
+  let agreeing = deals.iter().filter(|hands| hands[Seat::East].has_card(card("HA"))
+      || hands[Seat::East].has_card(card("HK")) || hands[Seat::East].has_card(card("HQ"))).count();
+  assert!(agreeing > 300, "{} of 400 deals agree with the signal", agreeing);
+
+  // a suit-preference discard is read by the same rule it is given by
+  let preference = Carding { discards: SignalKind::SuitPreference, ..Carding::default() };
+  let mut play = Play::new(Contract::from_notation("4S S").unwrap());
+  assert!(play.make_play(Seat::West, card("C2")));
+  assert!(play.make_play(Seat::North, card("C3")));
+  assert!(play.make_play(Seat::East, card("H9")));
+  let readings = read_signals(&play, Seat::East, &preference);
+  assert_eq!(readings[0].signal, Signal::PreferHigher(true));
+  for hand in ["54.J83.KQJ42.A32", "54.J83.Q9432.AK2", "54.J83.J9432.Q32"] {
+    let hand = PlayerHand::from_notation(hand).unwrap();
+    let intended = intended_signal(SignalKind::SuitPreference, Suit::Hearts, &hand, Strain::Trump(Suit::Spades));
+    assert_eq!(readings[0].agrees_with(&hand), intended == readings[0].signal, "{}", hand.notation());
+  }
+
+  // a defender signals only with cards it can see to be equal, without looking at declarer's hand
+  use crate::interface::{Deadline, Player};
+  let deal = |east: &str, south: &str| {
+    let hands = [(Seat::North, "KQJT.2.AQJT.AKQJ"), (Seat::East, east),
+                 (Seat::South, south), (Seat::West, "32.KQJT76.98.T98")];
+    let mut board = enum_map::EnumMap::default();
+    for (seat, hand) in hands {
+      board[seat] = PlayerHand::from_notation(hand).unwrap();
+    }
+    Board::from_hands(1, board)
+  };
+  let mut play = Play::new(Contract::from_notation("4S S").unwrap());
+  assert!(play.make_play(Seat::West, card("HK")));
+  assert!(play.make_play(Seat::North, card("H2")));
+  let board = deal("A54.A98.K432.632", "9876.543.765.754");
+  let mut east = SignallingDefender::<LowCardPlayer<true>>::new(board.player_hand(Seat::East));
+  assert_eq!(east.get_play(&play, &board, Deadline::Nodes(0)), card("H9"));
+  let mut east = east.with_carding(upside_down);
+  assert_eq!(east.get_play(&play, &board, Deadline::Nodes(0)), card("H8"));
+  // the eight and the three are not equal while the cards between them are unseen
+  let board = deal("A54.A83.K432.632", "9876.549.765.754");
+  let mut east = SignallingDefender::<LowCardPlayer<true>>::new(board.player_hand(Seat::East));
+  assert_eq!(east.get_play(&play, &board, Deadline::Nodes(0)), card("H3"));
+}
+
+#[test]
+fn declarer_techniques() {
+  use crate::ai::declarer::*;
+  let combination = |declarer: &str, dummy: &str| SuitCombination::parse(declarer, dummy).unwrap();
+  let probability = |lines: &[Line], technique: Technique| {
+    lines.iter().find(|line| line.technique == technique).map(|line| line.probability).unwrap()
+  };
+  assert!(SuitCombination::parse("AQ2", "A43").is_none());
+  assert_eq!(layouts(&[Rank::King, Rank::Jack, Rank::Ten], VacantPlaces::default()).len(), 8);
+  let total: f64 = layouts(&combination("AQ2", "543").missing(), VacantPlaces::default()).iter().map(|(_, p)| p).sum();
+  assert!((total - 1.0).abs() < 1e-9);
+
+  let simple = combination("AQ2", "543");
+  let finesse = Technique::Finesse { against: Rank::King, tenace: OurHand::Declarer };
+  let lines = lines(&simple, false, VacantPlaces::default());
+  assert_eq!(lines[0].technique, finesse);
+  assert!((lines[0].probability - 0.5).abs() < 1e-9);
+  assert_eq!(finesse.first_card(&simple, OurHand::Dummy), Some(Rank::Three));
+  assert_eq!(finesse.first_card(&simple, OurHand::Declarer), None);
+  // with the king known to be on the left, only a singleton drops
+  let lines_left = crate::ai::declarer::lines(&simple, false, VacantPlaces { lho: 13, rho: 0 });
+  assert_eq!(probability(&lines_left, finesse), 0.0);
+
+  // eight ever, nine never
+  let eight = crate::ai::declarer::lines(&combination("AKJ54", "632"), false, VacantPlaces::default());
+  assert!(matches!(eight[0].technique, Technique::Finesse { .. } | Technique::SafetyFinesse { .. }));
+  let nine = crate::ai::declarer::lines(&combination("AKJ54", "7632"), false, VacantPlaces::default());
+  assert_eq!(nine[0].technique, Technique::Drop(Rank::Queen));
+  assert!((nine[0].probability - 0.53).abs() < 0.01, "{}", nine[0].probability);
+
+  let ruffing = Technique::RuffingFinesse { against: Rank::King, sequence: OurHand::Dummy };
+  let ruff = combination("-", "AQJT");
+  assert!(techniques(&ruff, true).contains(&ruffing));
+  assert!(!techniques(&ruff, false).contains(&ruffing));
+  assert_eq!(ruffing.first_card(&ruff, OurHand::Dummy), Some(Rank::Queen));
+}
+
+#[test]
+fn suit_combination_analysis() {
+  use crate::ai::combinations::*;
+  use crate::ai::declarer::{Defender, OurHand, SuitCombination};
+  let analyser = |declarer: &str, dummy: &str| SuitAnalyser::new(SuitCombination::parse(declarer, dummy).unwrap());
+  let close = |a: f64, b: f64| (a - b).abs() < 0.005;
+
+  // the finesse, plus a singleton king dropping under the ace
+  let simple = analyser("AQ2", "543").analyse().unwrap();
+  assert_eq!(simple.layouts.len(), 128);
+  assert!(simple.probability(2) > 0.5 && simple.probability(2) < 0.52, "{}", simple);
+  assert!(close(simple.probability(1), 1.0));
+  assert_eq!(simple.lead, (OurHand::Declarer, Rank::Ace));
+
+  // no entry to dummy leaves no way to lead towards the queen
+  let blocked = analyser("AQ2", "543").with_lead(OurHand::Declarer).with_entries(0, 0).analyse().unwrap();
+  assert!(blocked.probability(2) < 0.1, "{}", blocked);
+
+  let singleton = analyser("AQ2", "543").with_length(Defender::Lho, 1, 1).analyse().unwrap();
+  assert_eq!(singleton.layouts.len(), 7);
+  assert!(close(singleton.probability(2), 1.0));
+  let king_left = analyser("AQ2", "543").with_card(Defender::Lho, Rank::King).analyse().unwrap();
+  assert!(king_left.probability(2) < 0.1, "{}", king_left);
+
+  let nine = analyser("AKJ54", "7632").with_objective(Objective::AtLeast(5)).analyse().unwrap();
+  assert!(close(nine.probability(5), 0.531), "{}", nine);
+  let eight = analyser("AKJ54", "632").with_objective(Objective::AtLeast(5)).analyse().unwrap();
+  assert!(eight.probability(5) > 0.36, "{}", eight);
+  assert!(analyser("-", "-").analyse().is_none());
+}
+
+#[test]
+fn post_mortem_costs() {
+  use crate::ai::postmortem::*;
+  let card = |text: &str| Card::from_notation(text).unwrap();
+  let mut play = Play::new(Contract::from_notation("4S S").unwrap());
+  for (seat, text) in [(Seat::West, "HK"), (Seat::North, "H2"), (Seat::East, "H3"), (Seat::South, "HA")] {
+    assert!(play.make_play(seat, card(text)));
+  }
+  assert!(play.make_play(Seat::South, card("C2")));
+  // the opening lead gives a trick away and declarer later gives two back
+  let analysis = PlayAnalysis::from_trace(&play, &[9, 10, 10, 10, 10, 8]).unwrap();
+  assert!(PlayAnalysis::from_trace(&play, &[9, 10]).is_none());
+  let costly: Vec<&CardAnalysis> = analysis.costly_cards().collect();
+  assert_eq!(costly.len(), 2);
+  assert_eq!((costly[0].seat, costly[0].cost), (Seat::West, 1));
+  assert_eq!((costly[1].seat, costly[1].card, costly[1].cost), (Seat::South, card("C2"), 2));
+  assert_eq!(analysis.errors(Side::NorthSouth), ErrorSummary { errors: 1, tricks: 2 });
+  assert_eq!(analysis.errors(Side::EastWest), ErrorSummary { errors: 1, tricks: 1 });
+  assert!(analysis.to_string().contains("trick 2: South"));
+
+  let mut session = SessionAnalysis::new();
+  session.push(analysis.clone());
+  session.push(analysis);
+  assert_eq!(session.errors()[Side::NorthSouth], ErrorSummary { errors: 2, tricks: 4 });
+  assert_eq!(session.errors()[Side::EastWest], ErrorSummary { errors: 2, tricks: 2 });
+}
+
+/** Plays the lowest card it may. It opens 1NT if `OPENS` and nobody has bid, and otherwise passes.
+ */
+struct LowCardPlayer<const OPENS: bool>;
+
+impl<const OPENS: bool> crate::interface::Player for LowCardPlayer<OPENS> {
+  fn new(_hand: &PlayerHand) -> Self {
+    LowCardPlayer
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    if OPENS && auction.calls().all(|&call| call == Call::Pass) {
+      Call::Bid(Bid::from(1, Strain::Notrump))
+    } else {
+      Call::Pass
+    }
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, _deadline: crate::interface::Deadline) -> Card {
+    let hand = board.player_hand(play.next_player());
+    play.legal_plays(hand).into_iter().min_by_key(|card| (card.rank(), card.suit())).unwrap()
+  }
+
+  fn notify_dummy(&mut self, _dummy: &PlayerHand) {}
+}
+
+#[test]
+fn duplicate_tournament() {
+  use crate::tournament::*;
+  type Opener = LowCardPlayer<true>;
+  type Passer = LowCardPlayer<false>;
+  let report = Tournament::seeded(8, 42).run::<Opener, Passer>().unwrap();
+  assert_eq!(report.boards.len(), 8);
+  assert!(report.boards.iter().any(|result| result.imps != 0), "{}", report);
+  assert!(report.boards.iter().all(|result| result.closed != HandResult::Passout && result.open != HandResult::Passout));
+  let mirrored = Tournament::seeded(8, 42).run::<Passer, Opener>().unwrap();
+  assert_eq!(mirrored.total_imps(), -report.total_imps());
+  let threaded = Tournament::seeded(8, 42).with_threads(3).run::<Opener, Passer>().unwrap();
+  assert_eq!(threaded, report);
+  let (low, high) = report.confidence_interval();
+  assert!(low <= report.mean_imps() && report.mean_imps() <= high);
+  let even = Tournament::seeded(4, 42).run::<Passer, Passer>().unwrap();
+  assert_eq!((even.total_imps(), even.confidence_interval()), (0, (0.0, 0.0)));
+
+  // an interrupted match picks up where its progress file ends
+  let path = std::env::temp_dir().join(format!("bridge-ai-tournament-{}.txt", std::process::id()));
+  let _ = std::fs::remove_file(&path);
+  let full = Tournament::seeded(8, 42).with_progress_file(&path).run::<Opener, Passer>().unwrap();
+  assert_eq!(full, report);
+  let lines: Vec<String> = std::fs::read_to_string(&path).unwrap().lines().map(str::to_string).collect();
+  assert_eq!(lines.len(), 8);
+  std::fs::write(&path, lines[..3].join("\n") + "\n").unwrap();
+  let resumed = Tournament::seeded(8, 42).with_progress_file(&path).with_threads(2).run::<Opener, Passer>().unwrap();
+  assert_eq!(resumed, report);
+  assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 8);
+  // a progress file from another match is not mixed into this one
+  assert!(matches!(Tournament::seeded(8, 7).with_progress_file(&path).run::<Opener, Passer>(),
+                   Err(TournamentError::Progress(1))));
+  assert!(matches!(Tournament::seeded(8, 42).with_progress_file(&path).run::<Passer, Opener>(),
+                   Err(TournamentError::Progress(1))));
+  assert!(matches!(Tournament::seeded(4, 42).with_progress_file(&path).run::<Opener, Passer>(),
+                   Err(TournamentError::Progress(_))));
+  assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 8);
+  std::fs::write(&path, "3\tnonsense\n").unwrap();
+  assert!(matches!(Tournament::seeded(8, 42).with_progress_file(&path).run::<Opener, Passer>(),
+                   Err(TournamentError::Progress(1))));
+  std::fs::remove_file(&path).unwrap();
+}
+
+/** Plays as `LowCardPlayer` does, but claims every remaining trick before each card. */
+struct ClaimingPlayer(LowCardPlayer<true>);
+
+impl crate::interface::Player for ClaimingPlayer {
+  fn new(hand: &PlayerHand) -> Self {
+    ClaimingPlayer(LowCardPlayer::new(hand))
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    self.0.get_call(auction)
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: crate::interface::Deadline) -> Card {
+    self.0.get_play(play, board, deadline)
+  }
+
+  fn notify_dummy(&mut self, _dummy: &PlayerHand) {}
+
+  fn offer_claim(&mut self, play: &Play, _board: &Board) -> Option<(u8, String)> {
+    Some((play.remaining_tricks(), "The rest are mine.".to_string()))
+  }
+}
+
+/** Plays as `LowCardPlayer` does, explaining each of its calls and keeping the final auction it is
+    shown.
+ */
+struct ExplainingPlayer(LowCardPlayer<true>, std::rc::Rc<std::cell::RefCell<Option<Auction>>>);
+
+impl crate::interface::Player for ExplainingPlayer {
+  fn new(hand: &PlayerHand) -> Self {
+    ExplainingPlayer(LowCardPlayer::new(hand), Default::default())
+  }
+
+  fn get_call(&self, auction: &Auction) -> Call {
+    self.0.get_call(auction)
+  }
+
+  fn explain_call(&self, auction: &Auction, _call: Call) -> Option<crate::game_model::bidding::CallAnnotation> {
+    let calls = auction.calls().count();
+    assert!(calls < 2 || auction.annotation(calls - 2).is_none(), "partner's explanation is shown when explaining");
+    Some(crate::game_model::bidding::CallAnnotation { explanation: Some("mine".to_string()), ..Default::default() })
+  }
+
+  fn notify_auction(&mut self, auction: &Auction) {
+    *self.1.borrow_mut() = Some(auction.clone());
+  }
+
+  fn get_play(&mut self, play: &Play, board: &Board, deadline: crate::interface::Deadline) -> Card {
+    self.0.get_play(play, board, deadline)
+  }
+
+  fn notify_dummy(&mut self, _dummy: &PlayerHand) {}
+}
+
+#[test]
+fn players_are_shown_only_disclosed_explanations() {
+  use crate::interface::{Player, TimeControl};
+  use crate::tournament::play_board;
+  let board = Board::new(1);
+  let shown: EnumMap<Seat, std::rc::Rc<std::cell::RefCell<Option<Auction>>>> = Default::default();
+  let mut players: EnumMap<Seat, Box<dyn Player>> = enum_map::enum_map! {
+    seat => Box::new(ExplainingPlayer(LowCardPlayer::new(board.player_hand(seat)), shown[seat].clone())) as Box<dyn Player>
+  };
+  let game = play_board(&board, &mut players, TimeControl::default()).unwrap();
+  assert_eq!(game.auction().calls().count(), 4);
+  for (seat, auction) in shown.iter() {
+    let auction = auction.borrow().clone().expect("every player is shown the auction");
+    for (i, _) in auction.calls().enumerate() {
+      let disclosed = auction.bidder(i) != seat.partner();
+      assert_eq!(auction.annotation(i).is_some(), disclosed, "{:?} shown call {}", seat, i);
+    }
+  }
+}
+
+#[test]
+fn disputed_claims_are_played_out() {
+  use crate::ai::alpha_mu::AlphaMuPlayer;
+  use crate::ai::baseline::GreedyPlayer;
+  use crate::interface::TimeControl;
+  use crate::tournament::*;
+  // the passers dispute every claim, so each board is played to the last card
+  let report = Tournament::seeded(4, 42).run::<ClaimingPlayer, LowCardPlayer<false>>().unwrap();
+  let expected = Tournament::seeded(4, 42).run::<LowCardPlayer<true>, LowCardPlayer<false>>().unwrap();
+  assert_eq!(report, expected);
+  let report = Tournament::seeded(2, 42).with_time_control(TimeControl::Nodes(64)).run::<AlphaMuPlayer, GreedyPlayer>().unwrap();
+  assert_eq!(report.boards.len(), 2);
+}
+
+#[test]
+fn empty_batches_need_no_solving() {
+  use crate::ai::batch::*;
+  let mut calls = 0;
+  let mut solver = BatchSolver::new().with_threads(2).with_memory(64).with_progress(|_| calls += 1);
+  assert_eq!(solver.solve_tricks(&[]), Ok(Vec::new()));
+  assert_eq!(solver.solve_plays(&[]), Ok(Vec::new()));
+  assert!(solver.solve_tables(&[]).unwrap().is_empty());
+  drop(solver);
+  assert_eq!(calls, 0);
+}
+
+#[test]
+fn transposed_positions_share_a_hash() {
+  use crate::ai::search::*;
+  // each seat holds one suit, so West wins every trick and leads again
+  let suits = enum_map::enum_map! {
+    Seat::West => Suit::Clubs, Seat::North => Suit::Diamonds, Seat::East => Suit::Hearts, Seat::South => Suit::Spades,
+  };
+  let hands = enum_map::enum_map! { seat => {
+    let mut hand = PlayerHand::default();
+    Rank::iter().for_each(|rank| hand.add_card(Card::from(suits[seat], rank)));
+    hand
+  } };
+  let board = Board::from_hands(1, hands);
+  let trick = |play: &mut Play, rank: Rank| {
+    for seat in [Seat::West, Seat::North, Seat::East, Seat::South] {
+      assert!(play.make_play(seat, Card::from(suits[seat], rank)));
+    }
+  };
+  let keys = ZobristKeys::new();
+  let start = Play::new(Contract::from_notation("3N S").unwrap());
+  let (mut first, mut second) = (start.clone(), start.clone());
+  trick(&mut first, Rank::Two);
+  trick(&mut first, Rank::Three);
+  trick(&mut second, Rank::Three);
+  trick(&mut second, Rank::Two);
+  assert_eq!(keys.hash(&first, &board), keys.hash(&second, &board));
+  assert_ne!(keys.hash(&first, &board), keys.hash(&start, &board));
+  assert_eq!(ZobristKeys::new().hash(&start, &board), keys.hash(&start, &board));
+  let mut led = first.clone();
+  assert!(led.make_play(Seat::West, Card::from(Suit::Clubs, Rank::Four)));
+  assert_ne!(keys.hash(&led, &board), keys.hash(&first, &board));
+  let other_strain = Play::new(Contract::from_notation("3S S").unwrap());
+  assert_ne!(keys.hash(&other_strain, &board), keys.hash(&start, &board));
+
+  let mut table = TranspositionTable::new(2);
+  assert!(table.get(4).is_none());
+  table.insert(4, 9u8);
+  assert_eq!(table.get(4), Some(&9));
+  table.insert(6, 7);
+  assert!(table.get(4).is_none());
+  assert_eq!((table.len(), table.capacity()), (1, 2));
+  assert_eq!(table.stats(), CacheStats { hits: 1, misses: 2, evictions: 1 });
+  assert!((table.stats().hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+  table.clear();
+  assert!(table.is_empty());
+  assert_eq!(SearchContext::default().solve_tricks(&[]), Ok(Vec::new()));
+}
+
+#[test]
+fn anytime_search_keeps_to_its_budget() {
+  use crate::ai::alpha_mu::AlphaMuPlayer;
+  use crate::ai::search::SearchClock;
+  use crate::interface::{Deadline, Player, TimeControl};
+  use std::time::{Duration, Instant};
+  let mut clock = SearchClock::start(Deadline::Nodes(5));
+  clock.count(3);
+  assert_eq!((clock.remaining_nodes(), clock.is_expired()), (Some(2), false));
+  clock.count(2);
+  assert!(clock.is_expired());
+  assert!(SearchClock::start(Deadline::At(Instant::now())).is_expired());
+  assert_eq!(SearchClock::start(Deadline::after(Duration::from_secs(60))).remaining_nodes(), None);
+
+  let mut play = Play::new(Contract::from_notation("3N S").unwrap());
+  assert_eq!(TimeControl::Nodes(91).deadline(&play), Deadline::Nodes(13));
+  assert!(matches!(TimeControl::Time(Duration::from_secs(91)).deadline(&play),
+                   Deadline::At(at) if at > Instant::now() + Duration::from_secs(12)));
+
+  // the search stops at once with no budget, and must still play a legal card
+  let suits = enum_map::enum_map! {
+    Seat::West => Suit::Clubs, Seat::North => Suit::Diamonds, Seat::East => Suit::Hearts, Seat::South => Suit::Spades,
+  };
+  let hands = enum_map::enum_map! { seat => {
+    let mut hand = PlayerHand::default();
+    Rank::iter().for_each(|rank| hand.add_card(Card::from(suits[seat], rank)));
+    hand
+  } };
+  let board = Board::from_hands(1, hands);
+  let mut west = AlphaMuPlayer::new(board.player_hand(Seat::West));
+  let mut south = AlphaMuPlayer::new(board.player_hand(Seat::South)).with_seed(7);
+  let lead = west.get_play(&play, &board, Deadline::Nodes(0));
+  assert_eq!(lead, Card::from(Suit::Clubs, Rank::Two));
+  assert!(play.make_play(Seat::West, lead));
+  assert_eq!(south.get_play(&play, &board, Deadline::Nodes(50)), Card::from(Suit::Diamonds, Rank::Two));
+  for _ in 0..47 {
+    let seat = play.next_player();
+    let card = board.player_hand(seat).cards().iter().copied()
+        .find(|&card| play.tricks().iter().all(|trick| trick[seat] != Some(card))).unwrap();
+    assert!(play.make_play(seat, card));
+  }
+  assert_eq!(TimeControl::Nodes(91).deadline(&play), Deadline::Nodes(1));
+}
+
+#[test]
+fn decisions_leave_a_trace() {
+  use crate::ai::alpha_mu::AlphaMuPlayer;
+  use crate::ai::trace::*;
+  use crate::interface::{Deadline, Player};
+  let hand = PlayerHand::from_notation("AKQ32.K2.A32.432").unwrap();
+  let player = AlphaMuPlayer::new(&hand);
+  assert!(player.last_decision().is_none());
+  let call = player.get_call(&Auction::new(Seat::North));
+  let trace = match player.last_decision() {
+    Some(DecisionTrace::Call(trace)) => trace,
+    other => panic!("expected a call trace, got {:?}", other),
+  };
+  assert_eq!(trace.chosen, call);
+  assert!(trace.rules.iter().any(|rule| rule.accepted));
+  assert!(trace.rules.iter().any(|rule| !rule.accepted));
+  assert!(trace.to_string().starts_with(&format!("called {}", call.notation())));
+
+  let board = Board::random(1, &mut <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(3));
+  let play = Play::new(Contract::from_notation("4H N").unwrap());
+  let mut east = AlphaMuPlayer::new(board.player_hand(Seat::East));
+  let card = east.get_play(&play, &board, Deadline::Nodes(0));
+  match east.last_decision() {
+    Some(DecisionTrace::Play(trace)) => {
+      assert_eq!((trace.seat, trace.chosen), (Seat::East, card));
+      assert_eq!(trace.scores.len(), 13);
+      assert!(trace.worlds.is_empty());
+    }
+    other => panic!("expected a play trace, got {:?}", other),
+  }
+
+  let (low, high) = (Card::from(Suit::Hearts, Rank::Two), Card::from(Suit::Hearts, Rank::Ace));
+  let trace = PlayTrace {
+    seat: Seat::West,
+    chosen: high,
+    reason: "the best of 2 deals".to_string(),
+    scores: vec![
+      CardScore { card: low, successes: 0, average_tricks: 2.5 },
+      CardScore { card: high, successes: 2, average_tricks: 4.0 },
+    ],
+    worlds: (0..2).map(|i| WorldTrace {
+      hands: Deck::new().deal_hands(),
+      tricks: vec![2 + i, 4],
+    }).collect(),
+  };
+  let text = trace.to_string();
+  assert!(text.contains("> ♥A: 4.00 tricks, target reached in 2 of 2 deals"), "{}", text);
+  assert!(text.contains("  ♥2: 2.50 tricks"), "{}", text);
+  #[cfg(feature = "serde")] {
+    let decision = DecisionTrace::Play(trace);
+    let read = DecisionTrace::from_json(&decision.to_json().unwrap()).unwrap();
+    assert_eq!(read, decision);
+  }
+}
+
+/** Checks that a finished game obeyed the rules: every call was legal, every seat played its own
+    thirteen cards and followed suit when it could, each trick was led by the winner of the last,
+    and the result agrees with the tricks taken.
+ */
+fn check_game(board: &Board, game: &BridgeGame) {
+  let mut auction = Auction::new(board.dealer());
+  for &call in game.auction().calls() {
+    assert!(auction.legal_calls().contains(&call), "{:?} after {:?}", call, auction);
+    assert!(auction.make_call(call));
+  }
+  assert!(auction.is_complete() && auction.legal_calls().is_empty());
+  let play = match game.play() {
+    None => {
+      assert_eq!(game.result(), Some(&HandResult::Passout));
+      return;
+    }
+    Some(play) => play,
+  };
+  let contract = play.contract();
+  assert_eq!((play.cards_played(), play.declarer_tricks() + play.defense_tricks()), (52, 13));
+  let mut replay = Play::new(contract);
+  let mut leader = contract.declarer().next_seat();
+  for trick in play.tricks() {
+    assert_eq!(trick.leader(), leader);
+    let led = trick[leader].unwrap();
+    for (seat, card) in trick.cards_in_order() {
+      let legal = replay.legal_plays(board.player_hand(seat));
+      assert!(legal.contains(&card), "{:?} played {} holding {:?}", seat, card, legal);
+      assert!(seat == leader || card.suit() == led.suit() || legal.iter().all(|c| c.suit() != led.suit()));
+      assert!(replay.make_play(seat, card));
+    }
+    leader = trick.winner(contract.strain()).unwrap();
+  }
+  for seat in Seat::iter() {
+    let mut played: Vec<Card> = play.tricks().iter().filter_map(|trick| trick[seat]).collect();
+    let mut held = board.player_hand(seat).cards().to_vec();
+    played.sort_by_key(|card| (card.suit(), card.rank()));
+    held.sort_by_key(|card| (card.suit(), card.rank()));
+    assert_eq!(played, held);
+  }
+  let made = play.declarer_tricks() as i8 - contract.level() as i8 - 6;
+  assert_eq!(game.result(), Some(&HandResult::Played(contract, made)));
+  while replay.undo().is_some() {}
+  assert_eq!(replay.cards_played(), 0);
+}
+
+#[test]
+fn baseline_players_keep_the_rules() {
+  use crate::ai::baseline::*;
+  use crate::interface::{Player, TimeControl};
+  use crate::tournament::play_board;
+  use rand::SeedableRng;
+  let mut rng = rand::rngs::StdRng::seed_from_u64(47);
+  let mut contracts = 0;
+  for number in 1..=2000u32 {
+    let board = Board::random(number, &mut rng);
+    let player = |seat: Seat| -> Box<dyn Player> {
+      let hand = board.player_hand(seat);
+      match (number + seat as u32) % 3 {
+        0 => Box::new(RandomPlayer::new(hand).with_seed(number as u64 * 4 + seat as u64)),
+        1 => Box::new(GreedyPlayer::new(hand)),
+        _ => Box::new(PassPlayer::new(hand)),
+      }
+    };
+    let mut players: EnumMap<Seat, Box<dyn Player>> = enum_map::enum_map! { seat => player(seat) };
+    let game = play_board(&board, &mut players, TimeControl::default()).unwrap();
+    check_game(&board, &game);
+    contracts += game.play().is_some() as u32;
+  }
+  assert!(contracts > 1200, "only {} of the hands were played", contracts);
+
+  // the same seed gives the same game
+  let board = Board::random(1, &mut rng);
+  let game = |seed: u64| {
+    let mut players: EnumMap<Seat, Box<dyn Player>> = enum_map::enum_map! {
+      seat => Box::new(RandomPlayer::new(board.player_hand(seat)).with_seed(seed + seat as u64)) as Box<dyn Player>
+    };
+    play_board(&board, &mut players, TimeControl::default()).unwrap()
+  };
+  assert_eq!(game(5).result(), game(5).result());
+  assert_eq!(game(5).auction().calls().count(), game(5).auction().calls().count());
+
+  // greedy bidding opens on twelve points and raises partner's major with support
+  let opener = GreedyPlayer::new(&PlayerHand::from_notation("AKJ32.K2.Q32.432").unwrap());
+  assert_eq!(opener.get_call(&Auction::new(Seat::North)), Call::from_notation("1S").unwrap());
+  let mut auction = Auction::new(Seat::North);
+  auction.make_call(Call::from_notation("1S").unwrap());
+  auction.make_call(Call::Pass);
+  let raiser = GreedyPlayer::new(&PlayerHand::from_notation("Q54.A543.K32.432").unwrap());
+  assert_eq!(raiser.get_call(&auction), Call::from_notation("2S").unwrap());
+  let weak = GreedyPlayer::new(&PlayerHand::from_notation("Q54.5432.432.432").unwrap());
+  assert_eq!(weak.get_call(&auction), Call::Pass);
+}
+
+#[test]
+fn blue_chip_table_over_tcp() {
+  use crate::ai::baseline::GreedyPlayer;
+  use crate::bluechip::{Message, TableClient, TableManager};
+  use crate::interface::{Player, TimeControl};
+  use crate::tournament::play_board;
+  use rand::SeedableRng;
+  for line in [
+    "Connecting \"bridge-ai\" as North using protocol version 18",
+    "West (\"team two\") seated",
+    "Teams : N/S : \"one\" E/W : \"two\"",
+    "Board number 7. Dealer South. Both vulnerable.",
+    "East's cards : S A K 3. H -. D T 9 8 5 2. C A J 9 4 3.",
+    "Dummy's cards : S Q J T 9 8 7 6 5 4 3. H A. D A. C -.",
+    "North ready for East's bid",
+    "South bids 1NT",
+    "West redoubles",
+    "East ready for dummy's card to trick 3",
+    "South ready for West's card to trick 13",
+    "North plays HT",
+  ] {
+    assert_eq!(Message::parse(line).map(|message| message.to_string()).as_deref(), Some(line));
+  }
+  assert!(matches!(Message::parse("North bids 1S Alert. 5+ spades\r\n"), Some(Message::Call(Seat::North, call)) if call == Call::from_notation("1S").unwrap()));
+  assert!(matches!(Message::parse("East plays D10"), Some(Message::Play(Seat::East, card)) if card == Card::from(Suit::Diamonds, Rank::Ten)));
+  assert!(Message::parse("North bids 8S").is_none());
+  assert!(Message::parse("East's cards : S A A.").is_none());
+  assert!(Message::parse("Nobody ready for deal").is_none());
+
+  // three remote greedy players and a local one reach the same results as four local ones
+  let mut manager = TableManager::bind("127.0.0.1:0").unwrap();
+  let address = manager.local_addr().unwrap();
+  let remote = [Seat::North, Seat::East, Seat::South];
+  let clients: Vec<_> = remote.iter().map(|&seat| std::thread::spawn(move || {
+    TableClient::connect(address, seat, "remote").unwrap().play_session::<GreedyPlayer>().unwrap()
+  })).collect();
+  manager.seat_players(&remote).unwrap();
+  let mut rng = rand::rngs::StdRng::seed_from_u64(49);
+  let mut results = Vec::new();
+  for number in 1..=8 {
+    let board = Board::random(number, &mut rng);
+    let game = manager.play_board(&board, |_, hand| Box::new(GreedyPlayer::new(hand))).unwrap();
+    check_game(&board, &game);
+    let mut players: EnumMap<Seat, Box<dyn Player>> = enum_map::enum_map! {
+      seat => Box::new(GreedyPlayer::new(board.player_hand(seat))) as Box<dyn Player>
+    };
+    let local = play_board(&board, &mut players, TimeControl::default()).unwrap();
+    assert_eq!(game.result(), local.result());
+    results.push(*game.result().unwrap());
+  }
+  manager.end_session().unwrap();
+  for client in clients {
+    assert_eq!(client.join().unwrap(), results);
+  }
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn web_tables_show_each_seat_only_its_cards() {
+  use std::sync::Arc;
+  use futures_util::{SinkExt, StreamExt};
+  use tokio_tungstenite::tungstenite::Message as Frame;
+  use crate::server::{ClientMessage, GameServer, ServerMessage, TableView};
+
+  struct Client(tokio_tungstenite::WebSocketStream<tokio::io::DuplexStream>);
+
+  impl Client {
+    async fn connect(server: &Arc<GameServer>) -> Self {
+      let (client, served) = tokio::io::duplex(1 << 16);
+      tokio::spawn(server.clone().serve(served));
+      Client(tokio_tungstenite::client_async("ws://localhost/", client).await.unwrap().0)
+    }
+
+    async fn send(&mut self, message: ClientMessage) {
+      self.0.send(Frame::Text(serde_json::to_string(&message).unwrap())).await.unwrap();
+    }
+
+    async fn receive(&mut self) -> ServerMessage {
+      loop {
+        if let Frame::Text(text) = self.0.next().await.unwrap().unwrap() {
+          return serde_json::from_str(&text).unwrap();
+        }
+      }
+    }
+
+    async fn view(&mut self) -> TableView {
+      match self.receive().await {
+        ServerMessage::Table(view) => view,
+        message => panic!("expected the table, got {:?}", message),
+      }
+    }
+  }
+
+  let server = Arc::new(GameServer::new());
+  let mut ann = Client::connect(&server).await;
+  ann.send(ClientMessage::CreateTable).await;
+  let table = match ann.receive().await {
+    ServerMessage::TableCreated { table } => table,
+    message => panic!("expected a table, got {:?}", message),
+  };
+  ann.send(ClientMessage::Join { table, seat: Seat::North, name: "Ann".to_string() }).await;
+  assert!(matches!(ann.receive().await, ServerMessage::Joined { seat: Seat::North, .. }));
+  ann.view().await;
+  let mut bob = Client::connect(&server).await;
+  bob.send(ClientMessage::Join { table, seat: Seat::North, name: "Bob".to_string() }).await;
+  assert!(matches!(bob.receive().await, ServerMessage::Error { .. }));
+  bob.send(ClientMessage::Join { table, seat: Seat::South, name: "Bob".to_string() }).await;
+  let token = match bob.receive().await {
+    ServerMessage::Joined { token, .. } => token,
+    message => panic!("expected a seat, got {:?}", message),
+  };
+  bob.view().await;
+  ann.view().await;
+  bob.send(ClientMessage::Call { call: Call::Pass }).await;
+  assert!(matches!(bob.receive().await, ServerMessage::Error { .. }));
+
+  // the empty seats are filled and the humans bid and play until the board is over, Bob's
+  // connection dropping part of the way through
+  ann.send(ClientMessage::Start).await;
+  let mut clients = [ann, bob];
+  let mut views = [clients[0].view().await, clients[1].view().await];
+  assert_eq!(views[0].players[Seat::East].as_deref(), Some("computer"));
+  assert_eq!(views[1].players[Seat::North].as_deref(), Some("Ann"));
+  let dealt: Vec<Card> = views.iter().flat_map(|view| view.deal.as_ref().unwrap().hand.clone()).collect();
+  assert_eq!(dealt.len(), 26);
+  assert!(dealt.iter().all(|card| dealt.iter().filter(|&other| other == card).count() == 1));
+  let mut reconnected = false;
+  loop {
+    for view in &views {
+      let deal = view.deal.as_ref().unwrap();
+      let played = deal.cards.iter().filter(|&&(seat, _)| seat == view.seat).count();
+      assert_eq!(deal.hand.len(), 13 - played);
+      let dummy = deal.contract.map(|contract| contract.declarer().partner());
+      assert_eq!(deal.dummy.is_some(), !deal.cards.is_empty() && dummy != Some(view.seat));
+      if let Some(cards) = &deal.dummy {
+        assert!(cards.iter().all(|card| !deal.hand.contains(card)));
+      }
+    }
+    let deal = views[0].deal.clone().unwrap();
+    let next = match deal.to_act {
+      Some(seat) => seat,
+      None => break,
+    };
+    let actor = match deal.contract {
+      Some(contract) if next == contract.declarer().partner() => contract.declarer(),
+      _ => next,
+    };
+    // computers play without being asked, each decision showing as a new view
+    let i = match views.iter().position(|view| view.seat == actor) {
+      Some(i) => i,
+      None => {
+        views = [clients[0].view().await, clients[1].view().await];
+        continue;
+      }
+    };
+    if !reconnected && views[0].deal.as_ref().unwrap().cards.len() >= 8 {
+      let before = views[1].clone();
+      clients[1] = Client::connect(&server).await;
+      clients[1].send(ClientMessage::Rejoin { table, token: token.clone() }).await;
+      assert!(matches!(clients[1].receive().await, ServerMessage::Joined { seat: Seat::South, .. }));
+      views = [clients[0].view().await, clients[1].view().await];
+      assert_eq!(views[1], before);
+      reconnected = true;
+    }
+    let deal = views[i].deal.as_ref().unwrap();
+    let message = if deal.contract.is_none() {
+      let opened = deal.calls.iter().any(|call| matches!(call, Call::Bid(_)));
+      ClientMessage::Call { call: if opened { Call::Pass } else { Call::from_notation("1C").unwrap() } }
+    } else {
+      let hand = if next == actor { &deal.hand } else { deal.dummy.as_ref().unwrap() };
+      let position = deal.cards.len() % 4;
+      let led = (position > 0).then(|| deal.cards[deal.cards.len() - position].1.suit());
+      ClientMessage::Play { card: hand.iter().copied().find(|card| Some(card.suit()) == led).unwrap_or(hand[0]) }
+    };
+    clients[i].send(message).await;
+    views = [clients[0].view().await, clients[1].view().await];
+  }
+  assert!(reconnected);
+  let result = views[0].deal.as_ref().unwrap().result;
+  assert!(matches!(result, Some(HandResult::Played(..))));
+  assert_eq!(views[1].deal.as_ref().unwrap().result, result);
+
+  clients[1].send(ClientMessage::NextBoard).await;
+  let view = clients[0].view().await;
+  assert_eq!(view.deal.as_ref().map(|deal| (deal.board, deal.hand.len())), Some((2, 13)));
+}
+
+mod properties {
+  use proptest::prelude::*;
+  use rand::SeedableRng;
+  use rand::rngs::StdRng;
+  use strum::IntoEnumIterator;
+  use crate::game_model::{Board, HandResult, Seat, Vulnerability};
+  use crate::game_model::bidding::{Auction, Bid, Call, Contract, DoubleLevel, Strain};
+  use crate::game_model::cards::{Card, Rank, Suit};
+  use crate::game_model::play::Play;
+
+  fn seat() -> impl Strategy<Value=Seat> {
+    (0..4usize).prop_map(|i| Seat::iter().nth(i).unwrap())
+  }
+
+  fn strain() -> impl Strategy<Value=Strain> {
+    (0..5usize).prop_map(|i| Suit::iter().map(Strain::Trump).chain([Strain::Notrump]).nth(i).unwrap())
+  }
+
+  fn contract() -> impl Strategy<Value=Contract> {
+    (1..=7u8, strain(), 0..3usize, seat()).prop_map(|(level, strain, doubled, declarer)| {
+      let doubled = [DoubleLevel::Undoubled, DoubleLevel::Doubled, DoubleLevel::Redoubled][doubled];
+      Contract::new(Bid::from(level, strain), doubled, declarer)
+    })
+  }
+
+  fn deal() -> impl Strategy<Value=Board> {
+    (1..=32u32, any::<u64>()).prop_map(|(number, seed)| Board::random(number, &mut StdRng::seed_from_u64(seed)))
+  }
+
+  /** Four different cards. */
+  fn trick_cards() -> impl Strategy<Value=Vec<Card>> {
+    proptest::sample::subsequence((0..52usize).collect::<Vec<_>>(), 4).prop_shuffle().prop_map(|indices| {
+      indices.into_iter()
+          .map(|i| Card::from(Suit::iter().nth(i / 13).unwrap(), Rank::iter().nth(i % 13).unwrap()))
+          .collect()
+    })
+  }
+
+  /** Plays `cards` as one trick led by `leader` and returns the card that won it. */
+  fn winning_card(cards: &[Card], leader: Seat, strain: Strain) -> (Seat, Card) {
+    let declarer = leader.prev_seat();
+    let mut play = Play::new(Contract::new(Bid::from(1, strain), DoubleLevel::Undoubled, declarer));
+    let mut seat = leader;
+    for &card in cards {
+      assert!(play.make_play(seat, card));
+      seat = seat.next_seat();
+    }
+    let winner = play.tricks()[0].winner(strain).unwrap();
+    assert_eq!(play.next_player(), winner);
+    (winner, play.tricks()[0][winner].unwrap())
+  }
+
+  fn vulnerability() -> impl Strategy<Value=Vulnerability> {
+    (0..4usize).prop_map(|i| [Vulnerability::Neither, Vulnerability::NS, Vulnerability::EW, Vulnerability::Both][i])
+  }
+
+  proptest! {
+    #[test]
+    fn deals_are_four_hands_of_distinct_cards(board in deal()) {
+      let mut cards: Vec<Card> = Vec::new();
+      for seat in Seat::iter() {
+        prop_assert_eq!(board.player_hand(seat).len(), 13);
+        cards.extend_from_slice(board.player_hand(seat).cards());
+      }
+      cards.sort_by_key(|card| (card.suit(), card.rank()));
+      cards.dedup();
+      prop_assert_eq!(cards.len(), 52);
+    }
+
+    #[test]
+    fn auctions_end_only_after_the_passes(dealer in seat(), choices in proptest::collection::vec(any::<prop::sample::Index>(), 0..60)) {
+      let mut auction = Auction::new(dealer);
+      for choice in choices {
+        if auction.is_complete() {
+          break;
+        }
+        let legal = auction.legal_calls();
+        prop_assert!(auction.make_call(*choice.get(&legal)));
+        let calls: Vec<Call> = auction.calls().copied().collect();
+        let any_bid = calls.iter().any(|call| matches!(call, Call::Bid(_)));
+        let trailing = calls.iter().rev().take_while(|&&call| call == Call::Pass).count();
+        let expected = if any_bid { trailing >= 3 } else { calls.len() == 4 };
+        prop_assert_eq!(auction.is_complete(), expected, "{:?}", calls);
+      }
+      if !auction.is_complete() {
+        prop_assert!(!auction.legal_calls().is_empty());
+        prop_assert!(auction.legal_calls().contains(&Call::Pass));
+      }
+    }
+
+    #[test]
+    fn the_same_card_wins_whoever_leads(cards in trick_cards(), first in seat(), second in seat(), strain in strain()) {
+      let (winner, card) = winning_card(&cards, first, strain);
+      let (other_winner, other_card) = winning_card(&cards, second, strain);
+      prop_assert_eq!(card, other_card);
+      let offset = |from: Seat, to: Seat| (0..4).position(|n| (0..n).fold(from, |seat, _| seat.next_seat()) == to);
+      prop_assert_eq!(offset(first, winner), offset(second, other_winner));
+    }
+
+    #[test]
+    fn the_winning_card_beats_the_others(cards in trick_cards(), leader in seat(), strain in strain()) {
+      let (_, winner) = winning_card(&cards, leader, strain);
+      prop_assert!(winner.suit() == cards[0].suit() || matches!(strain, Strain::Trump(trump) if winner.suit() == trump));
+      for &card in cards.iter().filter(|&&card| card != winner) {
+        prop_assert_eq!(winner.compare_with_trump(card, strain), std::cmp::Ordering::Greater);
+      }
+    }
+
+    #[test]
+    fn play_conserves_cards(board in deal(), contract in contract(), choices in proptest::collection::vec(any::<prop::sample::Index>(), 52)) {
+      let mut play = Play::new(contract);
+      for choice in choices {
+        let seat = play.next_player();
+        let legal = play.legal_plays(board.player_hand(seat));
+        prop_assert!(play.make_play(seat, *choice.get(&legal)));
+      }
+      prop_assert!(play.is_complete());
+      prop_assert_eq!((play.tricks().len(), play.declarer_tricks() + play.defense_tricks()), (13, 13));
+      for seat in Seat::iter() {
+        let played: Vec<Card> = play.tricks().iter().filter_map(|trick| trick[seat]).collect();
+        prop_assert_eq!(played.len(), 13);
+        prop_assert!(played.iter().all(|&card| board.player_hand(seat).has_card(card)));
+        prop_assert!(play.legal_plays(board.player_hand(seat)).is_empty());
+      }
+      let made = play.declarer_tricks() as i8 - contract.level() as i8 - 6;
+      prop_assert_eq!(play.result(), Some(HandResult::Played(contract, made)));
+    }
+
+    #[test]
+    fn scores_are_antisymmetric_between_sides(contract in contract(), tricks in 0..=13i8, vul in vulnerability()) {
+      let mirrored = Contract::new(Bid::from(contract.level(), contract.strain()), contract.doubled(),
+                                   contract.declarer().next_seat());
+      let mirrored_vul = match vul {
+        Vulnerability::NS => Vulnerability::EW,
+        Vulnerability::EW => Vulnerability::NS,
+        other => other,
+      };
+      let diff = tricks - contract.level() as i8 - 6;
+      let result = HandResult::Played(contract, diff);
+      prop_assert_eq!(result.ns_score(vul), -HandResult::Played(mirrored, diff).ns_score(mirrored_vul));
+      prop_assert_eq!(result.ns_score(vul).abs(), result.score(vul).abs());
+      prop_assert_eq!(result.score(vul) > 0, diff >= 0);
+    }
+  }
+}
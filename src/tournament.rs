@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use enum_map::{enum_map, EnumMap};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use strum::IntoEnumIterator;
+use crate::game_model::{Board, BridgeGame, HandResult, Seat, Side};
+use crate::game_model::bidding::Call;
+use crate::game_model::cards::Card;
+use crate::game_model::notation::Notation;
+use crate::game_model::scoring::imps;
+use crate::interface::{Player, TimeControl};
+
+/** A player at the table broke the rules of the game. */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TableError {
+  IllegalCall(Seat, Call),
+  IllegalPlay(Seat, Card),
+}
+
+impl Display for TableError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TableError::IllegalCall(seat, call) => write!(f, "{:?} made an illegal call: {:?}", seat, call),
+      TableError::IllegalPlay(seat, card) => write!(f, "{:?} played an illegal card: {}", seat, card),
+    }
+  }
+}
+
+impl std::error::Error for TableError {}
+
+/** Plays a board from the first call to the last card. Each player is shown the auction as it was
+    disclosed to them; declarer's player also plays dummy's cards and may claim before any card.
+    After a disputed claim, a card must be played before anyone may claim again. Each card is asked
+    for with its share of `control`.
+ */
+pub fn play_board(board: &Board, players: &mut EnumMap<Seat, Box<dyn Player>>, control: TimeControl)
+                  -> Result<BridgeGame, TableError> {
+  play_board_with_claims(board, players, control, true)
+}
+
+/** Plays a board as `play_board` does, but only offers declarer the chance to claim if `claims`
+    is set. Tables whose protocol has no claims have every card played.
+ */
+pub(crate) fn play_board_with_claims(board: &Board, players: &mut EnumMap<Seat, Box<dyn Player>>,
+                                     control: TimeControl, claims: bool) -> Result<BridgeGame, TableError> {
+  let mut game = BridgeGame::from_board(board.clone());
+  for player in players.values_mut() {
+    player.notify_board(board.number(), board.vulnerability());
+  }
+  while !game.auction().is_complete() {
+    let seat = game.auction().current_bidder();
+    let auction = game.auction().disclosed_to(seat);
+    let call = players[seat].get_call(&auction);
+    let annotation = players[seat].explain_call(&auction, call);
+    if !game.make_annotated_call(call, annotation) {
+      return Err(TableError::IllegalCall(seat, call));
+    }
+  }
+  for (seat, player) in players.iter_mut() {
+    player.notify_auction(&game.auction().disclosed_to(seat));
+  }
+  let mut disputed_at = None;
+  while let Some(play) = game.play().filter(|play| !play.is_complete()).cloned() {
+    let declarer = play.declarer();
+    let seat = play.next_player();
+    let controller = if seat == declarer.partner() { declarer } else { seat };
+    let may_claim = claims && disputed_at != Some(play.cards_played());
+    let offer = if may_claim { players[controller].offer_claim(&play, board) } else { None };
+    if let Some((tricks, statement)) = offer {
+      if game.claim(controller, tricks, &statement) {
+        let claim = game.play().and_then(|play| play.pending_claim()).cloned().expect("the claim was just made");
+        for responder in Seat::iter().filter(|&responder| claim.is_responder(responder, declarer)) {
+          let claimed = game.play().cloned().expect("play is under way");
+          if players[responder].respond_to_claim(&claimed, board, &claim) {
+            game.accept_claim(responder);
+          } else {
+            game.dispute_claim(responder);
+            disputed_at = Some(play.cards_played());
+            break;
+          }
+        }
+        continue;
+      }
+    }
+    let card = players[controller].get_play(&play, board, control.deadline(&play));
+    if !game.make_play(seat, card) {
+      return Err(TableError::IllegalPlay(seat, card));
+    }
+    if play.cards_played() == 0 {
+      let dummy = board.player_hand(declarer.partner());
+      for (seat, player) in players.iter_mut() {
+        if seat != declarer.partner() {
+          player.notify_dummy(dummy);
+        }
+      }
+    }
+  }
+  Ok(game)
+}
+
+/** One board of a match, played at both tables. The first team sits North-South in the open room
+    and East-West in the closed room.
+ */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BoardResult {
+  pub board: u32,
+  pub open: HandResult,
+  pub closed: HandResult,
+  /** The IMPs won by the first team. */
+  pub imps: i32,
+}
+
+impl BoardResult {
+  fn new(board: &Board, open: HandResult, closed: HandResult) -> Self {
+    let vul = board.vulnerability();
+    BoardResult { board: board.number(), open, closed, imps: imps(open.ns_score(vul) - closed.ns_score(vul)) }
+  }
+}
+
+#[derive(Debug)]
+pub enum TournamentError {
+  /** A board could not be played to the end. */
+  Table(u32, TableError),
+  Io(std::io::Error),
+  /** A line of the progress file could not be read, counting from one. */
+  Progress(usize),
+}
+
+impl Display for TournamentError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TournamentError::Table(board, err) => write!(f, "board {}: {}", board, err),
+      TournamentError::Io(err) => write!(f, "progress file: {}", err),
+      TournamentError::Progress(line) => write!(f, "progress file: line {} is not a board result", line),
+    }
+  }
+}
+
+impl std::error::Error for TournamentError {}
+
+impl From<std::io::Error> for TournamentError {
+  fn from(err: std::io::Error) -> Self {
+    TournamentError::Io(err)
+  }
+}
+
+/** A duplicate team match between two kinds of player. Every board is played twice with the teams
+    switching seats, so that both teams hold the same cards and luck in the deal cancels out.
+ */
+pub struct Tournament {
+  boards: Vec<Board>,
+  threads: usize,
+  progress: Option<PathBuf>,
+  control: TimeControl,
+}
+
+impl Tournament {
+  pub fn new(boards: Vec<Board>) -> Self {
+    Tournament { boards, threads: 1, progress: None, control: TimeControl::default() }
+  }
+
+  /** A match on `count` boards dealt from `seed`, numbered from one. */
+  pub fn seeded(count: u32, seed: u64) -> Self {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Tournament::new((1..=count).map(|number| Board::random(number, &mut rng)).collect())
+  }
+
+  /** Plays boards on several threads at once. The report is the same for any number of threads. */
+  pub fn with_threads(mut self, threads: usize) -> Self {
+    self.threads = threads.max(1);
+    self
+  }
+
+  /** Records each finished board in a file, one per line. Boards already recorded there are not
+      played again, so an interrupted match can be resumed by running it with the same file. A file
+      written by a match with other deals or other teams is an error.
+   */
+  pub fn with_progress_file(mut self, path: impl Into<PathBuf>) -> Self {
+    self.progress = Some(path.into());
+    self
+  }
+
+  /** Sets how much each player may think in a hand. Players given time rather than nodes can
+      choose differently from run to run, and then so can the report.
+   */
+  pub fn with_time_control(mut self, control: TimeControl) -> Self {
+    self.control = control;
+    self
+  }
+
+  pub fn boards(&self) -> &[Board] {
+    &self.boards
+  }
+
+  /** Reads the boards already finished from the progress file. Each line records a board's number,
+      its deal, the teams and the results at both tables; a line whose deal or teams differ from
+      this match's is rejected rather than mixed into the report.
+   */
+  fn read_progress(&self, teams: &str) -> Result<HashMap<u32, (HandResult, HandResult)>, TournamentError> {
+    let mut done = HashMap::new();
+    let file = match &self.progress {
+      Some(path) if path.exists() => File::open(path)?,
+      _ => return Ok(done),
+    };
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+      let line = line?;
+      if line.trim().is_empty() {
+        continue;
+      }
+      let mut fields = line.split('\t');
+      let parsed = (|| {
+        let number = fields.next()?.parse().ok()?;
+        let board = self.boards.iter().find(|board| board.number() == number)?;
+        if fields.next()? != deal_notation(board) || fields.next()? != teams {
+          return None;
+        }
+        let open = HandResult::from_notation(fields.next()?)?;
+        let closed = HandResult::from_notation(fields.next()?)?;
+        Some((number, (open, closed)))
+      })();
+      let (number, results) = parsed.ok_or(TournamentError::Progress(i + 1))?;
+      done.insert(number, results);
+    }
+    Ok(done)
+  }
+
+  /** Plays the match with `A` as the first team and `B` as the second. */
+  pub fn run<A: Player + 'static, B: Player + 'static>(&self) -> Result<MatchReport, TournamentError> {
+    let teams = format!("{} v {}", std::any::type_name::<A>(), std::any::type_name::<B>());
+    let done = self.read_progress(&teams)?;
+    let mut results: Vec<Option<BoardResult>> = self.boards.iter()
+        .map(|board| done.get(&board.number()).map(|&(open, closed)| BoardResult::new(board, open, closed)))
+        .collect();
+    let pending: Vec<usize> = (0..self.boards.len()).filter(|&i| results[i].is_none()).collect();
+    let progress = match &self.progress {
+      Some(path) => Some(Mutex::new(OpenOptions::new().create(true).append(true).open(path)?)),
+      None => None,
+    };
+    let next = AtomicUsize::new(0);
+    let finished = Mutex::new(Vec::new());
+    let worker = || -> Result<(), TournamentError> {
+      loop {
+        let i = match pending.get(next.fetch_add(1, Ordering::Relaxed)) {
+          Some(&i) => i,
+          None => return Ok(()),
+        };
+        let board = &self.boards[i];
+        let open = play_table::<A, B>(board, self.control)?;
+        let closed = play_table::<B, A>(board, self.control)?;
+        if let Some(file) = &progress {
+          let mut file = file.lock().unwrap();
+          writeln!(file, "{}\t{}\t{}\t{}\t{}",
+                   board.number(), deal_notation(board), teams, open.notation(), closed.notation())?;
+          file.flush()?;
+        }
+        finished.lock().unwrap().push((i, BoardResult::new(board, open, closed)));
+      }
+    };
+    std::thread::scope(|scope| {
+      let handles: Vec<_> = (0..self.threads).map(|_| scope.spawn(worker)).collect();
+      handles.into_iter().try_for_each(|handle| handle.join().expect("a tournament thread panicked"))
+    })?;
+    for (i, result) in finished.into_inner().unwrap() {
+      results[i] = Some(result);
+    }
+    Ok(MatchReport { boards: results.into_iter().map(|result| result.expect("every board was played")).collect() })
+  }
+}
+
+/** The four hands of a board as in a PBN deal tag, from North: `"N:AKQ2.T98.765.432 …"`. */
+fn deal_notation(board: &Board) -> String {
+  let hands: Vec<String> = Seat::iter().map(|seat| board.player_hand(seat).notation()).collect();
+  format!("N:{}", hands.join(" "))
+}
+
+/** Plays one table of a board with `N` sitting North-South and `E` East-West. */
+fn play_table<N: Player + 'static, E: Player + 'static>(board: &Board, control: TimeControl)
+                                                       -> Result<HandResult, TournamentError> {
+  let player = |seat: Seat| -> Box<dyn Player> {
+    let hand = board.player_hand(seat);
+    match seat.side() {
+      Side::NorthSouth => Box::new(N::new(hand)),
+      Side::EastWest => Box::new(E::new(hand)),
+    }
+  };
+  let mut players: EnumMap<Seat, Box<dyn Player>> = enum_map! { seat => player(seat) };
+  let game = play_board(board, &mut players, control).map_err(|err| TournamentError::Table(board.number(), err))?;
+  Ok(*game.result().expect("a board is played to the end"))
+}
+
+/** The outcome of a match, board by board in the order the boards were given. */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MatchReport {
+  pub boards: Vec<BoardResult>,
+}
+
+impl MatchReport {
+  /** The IMPs won by the first team over the whole match. */
+  pub fn total_imps(&self) -> i32 {
+    self.boards.iter().map(|result| result.imps).sum()
+  }
+
+  pub fn mean_imps(&self) -> f64 {
+    if self.boards.is_empty() {
+      return 0.0;
+    }
+    self.total_imps() as f64 / self.boards.len() as f64
+  }
+
+  /** The sample standard deviation of the IMPs per board. */
+  pub fn standard_deviation(&self) -> f64 {
+    let n = self.boards.len();
+    if n < 2 {
+      return 0.0;
+    }
+    let mean = self.mean_imps();
+    let squares: f64 = self.boards.iter().map(|result| (result.imps as f64 - mean).powi(2)).sum();
+    (squares / (n - 1) as f64).sqrt()
+  }
+
+  /** The 95% confidence interval for the first team's mean IMPs per board, using the normal
+      approximation. An interval that excludes zero means one team is better.
+   */
+  pub fn confidence_interval(&self) -> (f64, f64) {
+    let mean = self.mean_imps();
+    let margin = if self.boards.is_empty() {
+      0.0
+    } else {
+      1.96 * self.standard_deviation() / (self.boards.len() as f64).sqrt()
+    };
+    (mean - margin, mean + margin)
+  }
+}
+
+impl Display for MatchReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let (low, high) = self.confidence_interval();
+    write!(f, "{} boards, {:+} IMPs, {:+.2} IMPs/board (95% CI {:+.2} to {:+.2})",
+           self.boards.len(), self.total_imps(), self.mean_imps(), low, high)
+  }
+}